@@ -241,8 +241,12 @@ async fn main() -> anyhow::Result<()> {
     writeln!(stdout, "DID: {}", identity.did_key())?;
 
     // loads all conversations, pushing their streams into the `StreamMap` to poll.
-    for conversation in instance.list_conversations().await.unwrap_or_default() {
-        let id = conversation.id();
+    for summary in instance
+        .list_conversations(Default::default())
+        .await
+        .unwrap_or_default()
+    {
+        let id = summary.conversation().id();
 
         let stream = instance.get_conversation_stream(id).await?;
         stream_map.insert(id, stream);
@@ -505,8 +509,8 @@ async fn main() -> anyhow::Result<()> {
                         ListConversations => {
                             let mut table = Table::new();
                             table.set_header(vec!["Name", "ID", "Created", "Updated", "Recipients"]);
-                            let list = instance.list_conversations().await?;
-                            for convo in list.iter() {
+                            let list = instance.list_conversations(Default::default()).await?;
+                            for convo in list.iter().map(|summary| summary.conversation()) {
                                 let mut recipients = vec![];
                                 for recipient in convo.recipients() {
                                     let username = get_username(&instance,  recipient).await;
@@ -1048,6 +1052,37 @@ async fn message_event_handle<M: MultiPass, R: RayGun>(
                             }
                         }
                         MessageType::Event => {}
+                        MessageType::VoiceNote => {
+                            let duration = message
+                                .voice_note()
+                                .map(|voice_note| voice_note.duration())
+                                .unwrap_or_default();
+                            writeln!(
+                                stdout,
+                                "[{}] @> Voice note ({}s)",
+                                username,
+                                duration / 1000
+                            )?;
+                        }
+                        MessageType::ContactCard => {
+                            if let Some(identity) = message.contact_card() {
+                                writeln!(
+                                    stdout,
+                                    "[{}] @> Shared contact {} ({})",
+                                    username,
+                                    identity.username(),
+                                    identity.did_key()
+                                )?;
+                            }
+                        }
+                        MessageType::Poll => {
+                            writeln!(stdout, "[{}] @> Poll: {}", username, lines.join("\n"))?;
+                            if let Some(poll) = message.poll() {
+                                for (index, option) in poll.options().iter().enumerate() {
+                                    writeln!(stdout, ">> {index}: {option}")?;
+                                }
+                            }
+                        }
                     }
                 }
             }
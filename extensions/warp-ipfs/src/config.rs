@@ -131,6 +131,10 @@ pub struct StoreSetting {
     pub announce_to_mesh: bool,
     /// Function to call to provide data for a default profile picture if one is not apart of the identity
     pub default_profile_picture: Option<DefaultPfpFn>,
+    /// Maximum amount of messages that can be pinned within a conversation
+    pub max_pinned_messages: usize,
+    /// Marks the identity as an automated account
+    pub bot: bool,
 }
 
 impl std::fmt::Debug for StoreSetting {
@@ -153,6 +157,8 @@ impl Default for StoreSetting {
             with_friends: false,
             default_profile_picture: None,
             announce_to_mesh: false,
+            max_pinned_messages: 50,
+            bot: false,
         }
     }
 }
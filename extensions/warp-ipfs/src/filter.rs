@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use warp::crypto::DID;
+use warp::raygun::{Message, QuarantinedMessage};
+
+use crate::store::MAX_QUARANTINED_MESSAGES;
+
+/// Kind of inbound message that is being filtered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    /// A new message
+    New,
+    /// An edit to an existing message. The message will contain the edited lines
+    Edit,
+}
+
+/// Location the inbound message is destined for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterSource {
+    Conversation {
+        conversation_id: Uuid,
+    },
+    Community {
+        community_id: Uuid,
+        channel_id: Uuid,
+    },
+}
+
+impl FilterSource {
+    /// Channel of the community the message is destined for
+    pub fn channel_id(&self) -> Option<Uuid> {
+        match self {
+            FilterSource::Conversation { .. } => None,
+            FilterSource::Community { channel_id, .. } => Some(*channel_id),
+        }
+    }
+}
+
+/// Outcome of a [`MessageFilter`]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Store the message as normal
+    #[default]
+    Accept,
+    /// Drop the message
+    Reject { reason: String },
+    /// Hold the message back until it is released
+    Quarantine { reason: String },
+}
+
+/// Hook invoked for every inbound message before it is stored.
+///
+/// Messages sent by the local identity are not passed through the filter.
+#[async_trait::async_trait]
+pub trait MessageFilter: Send + Sync + 'static {
+    async fn filter(
+        &self,
+        source: FilterSource,
+        kind: FilterKind,
+        message: &Message,
+    ) -> FilterAction;
+}
+
+pub type MessageFilterHandle = Arc<dyn MessageFilter>;
+
+/// Inbound message held back by the filter, along with the event used to store it once released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry<E> {
+    pub sender: DID,
+    pub source: FilterSource,
+    pub event: E,
+    pub message: QuarantinedMessage,
+}
+
+/// Quarantined messages, keyed by message id, in the order they were quarantined
+pub type Quarantine<E> = IndexMap<Uuid, QuarantineEntry<E>>;
+
+/// Pass an inbound message through the message filter, holding it in the quarantine if the filter
+/// requests it. Returns `true` if the message should be stored
+pub async fn filter_message<E>(
+    filter: &MessageFilterHandle,
+    quarantine: &mut Quarantine<E>,
+    source: FilterSource,
+    sender: &DID,
+    kind: FilterKind,
+    message: &Message,
+    event: impl FnOnce() -> E,
+) -> bool {
+    let message_id = message.id();
+
+    match filter.filter(source, kind, message).await {
+        FilterAction::Accept => true,
+        FilterAction::Reject { reason } => {
+            tracing::info!(?source, %message_id, %reason, "message rejected by filter");
+            false
+        }
+        FilterAction::Quarantine { reason } => {
+            tracing::info!(?source, %message_id, %reason, "message quarantined by filter");
+            quarantine.shift_remove(&message_id);
+            if quarantine.len() >= MAX_QUARANTINED_MESSAGES {
+                quarantine.shift_remove_index(0);
+            }
+            let message = QuarantinedMessage::new(
+                message.clone(),
+                kind == FilterKind::Edit,
+                reason,
+                Utc::now(),
+            );
+            quarantine.insert(
+                message_id,
+                QuarantineEntry {
+                    sender: sender.clone(),
+                    source,
+                    event: event(),
+                    message,
+                },
+            );
+            false
+        }
+    }
+}
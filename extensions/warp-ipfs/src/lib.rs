@@ -29,6 +29,7 @@ use warp::raygun::community::{
 };
 
 use crate::config::{Bootstrap, DiscoveryType};
+use crate::filter::{MessageFilter, MessageFilterHandle};
 use crate::store::discovery::Discovery;
 use crate::store::phonebook::PhoneBook;
 use crate::store::{ecdh_decrypt, PeerIdExt};
@@ -63,13 +64,17 @@ use warp::multipass::{
 };
 use warp::raygun::{
     community::{
-        Community, CommunityChannel, CommunityChannelType, CommunityInvite, RayGunCommunity,
+        Community, CommunityAuditLogEntry, CommunityAuditLogOptions, CommunityBan,
+        CommunityChannel, CommunityChannelCategory, CommunityChannelType, CommunityInvite,
+        CommunityListing, RayGunCommunity,
     },
-    AttachmentEventStream, Conversation, ConversationImage, EmbedState, GroupPermissionOpt,
-    Location, Message, MessageEvent, MessageEventStream, MessageOptions, MessageReference,
-    MessageStatus, Messages, PinState, RayGun, RayGunAttachment, RayGunConversationInformation,
+    AttachmentEventStream, AttachmentFilter, Conversation, ConversationAttachment,
+    ConversationImage, ConversationQuery, ConversationSummary, Draft, EmbedState,
+    GroupPermissionOpt, Location, Message, MessageBuilder, MessageEvent, MessageEventStream,
+    MessageOptions, MessageReference, MessageStatus, Messages, PinState, PinnedMessage, Poll,
+    PollResults, QuarantinedMessage, RayGun, RayGunAttachment, RayGunConversationInformation,
     RayGunEventKind, RayGunEventStream, RayGunEvents, RayGunGroupConversation, RayGunStream,
-    ReactionState,
+    ReactionState, SavedMessage, SyncStatus,
 };
 use warp::tesseract::{Tesseract, TesseractEvent};
 use warp::warp::Warp;
@@ -77,10 +82,13 @@ use warp::{Extension, SingleHandle};
 
 mod behaviour;
 pub mod config;
+pub mod filter;
 pub mod shuttle;
 pub mod store;
 mod thumbnail;
 mod utils;
+#[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+mod voice_note;
 
 const PUBSUB_MAX_BUF: usize = 8_388_608;
 
@@ -97,6 +105,7 @@ pub type WarpIpfsInstance = Warp<WarpIpfs, WarpIpfs, WarpIpfs>;
 
 struct Inner {
     config: Config,
+    message_filter: Option<MessageFilterHandle>,
     identity_guard: tokio::sync::Mutex<()>,
     init_guard: tokio::sync::Mutex<()>,
     span: RwLock<Span>,
@@ -119,6 +128,7 @@ pub struct WarpIpfsBuilder {
     // use_raygun: bool,
     // use_constellation: bool,
     tesseract: Option<Tesseract>,
+    message_filter: Option<MessageFilterHandle>,
 }
 
 impl WarpIpfsBuilder {
@@ -131,6 +141,12 @@ impl WarpIpfsBuilder {
         self.tesseract = Some(tesseract);
         self
     }
+
+    /// Set a filter that inbound messages are passed through before being stored
+    pub fn set_message_filter<F: MessageFilter>(mut self, filter: F) -> Self {
+        self.message_filter = Some(Arc::new(filter));
+        self
+    }
 }
 
 impl core::future::IntoFuture for WarpIpfsBuilder {
@@ -138,12 +154,21 @@ impl core::future::IntoFuture for WarpIpfsBuilder {
     type Output = WarpIpfsInstance;
 
     fn into_future(self) -> Self::IntoFuture {
-        async move { WarpIpfs::new(self.config, self.tesseract).await }.boxed()
+        async move { WarpIpfs::initialize(self.config, self.tesseract, self.message_filter).await }
+            .boxed()
     }
 }
 
 impl WarpIpfs {
     pub async fn new(config: Config, tesseract: impl Into<Option<Tesseract>>) -> WarpIpfsInstance {
+        Self::initialize(config, tesseract, None).await
+    }
+
+    async fn initialize(
+        config: Config,
+        tesseract: impl Into<Option<Tesseract>>,
+        message_filter: Option<MessageFilterHandle>,
+    ) -> WarpIpfsInstance {
         let multipass_tx = EventSubscription::new();
         let raygun_tx = EventSubscription::new();
         let constellation_tx = EventSubscription::new();
@@ -176,6 +201,7 @@ impl WarpIpfs {
 
         let inner = Arc::new(Inner {
             config,
+            message_filter,
             components: Default::default(),
             identity_guard: Default::default(),
             init_guard: Default::default(),
@@ -658,6 +684,8 @@ impl WarpIpfs {
 
         let message_store = MessageStore::new(
             &ipfs,
+            &self.inner.config,
+            self.inner.message_filter.clone(),
             discovery,
             &filestore,
             self.raygun_tx.clone(),
@@ -1305,6 +1333,12 @@ impl Friends for WarpIpfs {
         store.send_request(pubkey).await
     }
 
+    async fn send_request_from_card(&mut self, message: &Message) -> Result<(), Error> {
+        let identity = message.contact_card().ok_or(Error::MessageNotContactCard)?;
+        let mut store = self.identity_store(true).await?;
+        store.send_request(identity.did_key()).await
+    }
+
     async fn accept_request(&mut self, pubkey: &DID) -> Result<(), Error> {
         let mut store = self.identity_store(true).await?;
         store.accept_request(pubkey).await
@@ -1411,6 +1445,11 @@ impl IdentityInformation for WarpIpfs {
         store.identity_platform(did).await
     }
 
+    async fn identity_is_bot(&self, did: &DID) -> Result<bool, Error> {
+        let store = self.identity_store(true).await?;
+        store.identity_is_bot(did).await
+    }
+
     async fn identity_relationship(&self, did: &DID) -> Result<identity::Relationship, Error> {
         let store = self.identity_store(true).await?;
         store.lookup(did).await?;
@@ -1464,8 +1503,41 @@ impl RayGun for WarpIpfs {
             .await
     }
 
-    async fn list_conversations(&self) -> Result<Vec<Conversation>, Error> {
-        self.messaging_store()?.list_conversations().await
+    async fn list_conversations(
+        &self,
+        query: ConversationQuery,
+    ) -> Result<Vec<ConversationSummary>, Error> {
+        self.messaging_store()?.list_conversations(query).await
+    }
+
+    async fn add_conversation_label(
+        &mut self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .add_conversation_label(conversation_id, label)
+            .await
+    }
+
+    async fn remove_conversation_label(
+        &mut self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .remove_conversation_label(conversation_id, label)
+            .await
+    }
+
+    async fn list_conversation_labels(&self) -> Result<Vec<String>, Error> {
+        self.messaging_store()?.list_conversation_labels().await
+    }
+
+    async fn mark_conversation_read(&mut self, conversation_id: Uuid) -> Result<(), Error> {
+        self.messaging_store()?
+            .mark_conversation_read(conversation_id)
+            .await
     }
 
     async fn get_message_count(&self, conversation_id: Uuid) -> Result<usize, Error> {
@@ -1526,6 +1598,16 @@ impl RayGun for WarpIpfs {
             .await
     }
 
+    async fn send_with(
+        &mut self,
+        conversation_id: Uuid,
+        message: MessageBuilder,
+    ) -> Result<Uuid, Error> {
+        self.messaging_store()?
+            .send_message_with(conversation_id, message)
+            .await
+    }
+
     async fn edit(
         &mut self,
         conversation_id: Uuid,
@@ -1572,6 +1654,139 @@ impl RayGun for WarpIpfs {
             .await
     }
 
+    async fn list_pinned(&self, conversation_id: Uuid) -> Result<Vec<PinnedMessage>, Error> {
+        self.messaging_store()?.list_pinned(conversation_id).await
+    }
+
+    async fn set_draft(
+        &mut self,
+        conversation_id: Uuid,
+        lines: Vec<String>,
+        attachments: Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .set_draft(conversation_id, lines, attachments)
+            .await
+    }
+
+    async fn get_draft(&self, conversation_id: Uuid) -> Result<Option<Draft>, Error> {
+        self.messaging_store()?.get_draft(conversation_id).await
+    }
+
+    async fn sync_status(&self, conversation_id: Uuid) -> Result<SyncStatus, Error> {
+        self.messaging_store()?.sync_status(conversation_id).await
+    }
+
+    async fn force_resync(&mut self, conversation_id: Uuid, peer: &DID) -> Result<(), Error> {
+        self.messaging_store()?
+            .force_resync(conversation_id, peer)
+            .await
+    }
+
+    async fn save_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .save_message(conversation_id, message_id, note)
+            .await
+    }
+
+    async fn unsave_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .unsave_message(conversation_id, message_id)
+            .await
+    }
+
+    async fn list_saved_messages(&self) -> Result<Vec<SavedMessage>, Error> {
+        self.messaging_store()?.list_saved_messages().await
+    }
+
+    async fn list_attachments(
+        &self,
+        conversation_id: Uuid,
+        filter: AttachmentFilter,
+    ) -> Result<Vec<ConversationAttachment>, Error> {
+        self.messaging_store()?
+            .list_attachments(conversation_id, filter)
+            .await
+    }
+
+    async fn list_quarantined_messages(
+        &self,
+        conversation_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        self.messaging_store()?
+            .list_quarantined_messages(conversation_id)
+            .await
+    }
+
+    async fn release_quarantined_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .release_quarantined_message(conversation_id, message_id)
+            .await
+    }
+
+    async fn send_poll(&mut self, conversation_id: Uuid, poll: Poll) -> Result<Uuid, Error> {
+        self.messaging_store()?
+            .send_poll(conversation_id, poll)
+            .await
+    }
+
+    async fn vote_poll(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .vote_poll(conversation_id, message_id, options)
+            .await
+    }
+
+    async fn close_poll(&mut self, conversation_id: Uuid, message_id: Uuid) -> Result<(), Error> {
+        self.messaging_store()?
+            .close_poll(conversation_id, message_id)
+            .await
+    }
+
+    async fn poll_results(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        self.messaging_store()?
+            .poll_results(conversation_id, message_id)
+            .await
+    }
+
+    async fn send_contact_card(&mut self, conversation_id: Uuid, did: &DID) -> Result<Uuid, Error> {
+        self.messaging_store()?
+            .send_contact_card(conversation_id, did)
+            .await
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    async fn send_voice_note(
+        &mut self,
+        conversation_id: Uuid,
+        pcm: BoxStream<'static, Vec<f32>>,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+        self.messaging_store()?
+            .send_voice_note(conversation_id, pcm)
+            .await
+    }
+
     async fn reply(
         &mut self,
         conversation_id: Uuid,
@@ -1719,6 +1934,16 @@ impl RayGunCommunity for WarpIpfs {
     async fn list_communities_invited_to(&self) -> Result<Vec<(Uuid, CommunityInvite)>, Error> {
         self.messaging_store()?.list_communities_invited_to().await
     }
+    async fn search_public_communities(
+        &self,
+        query: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommunityListing>, Error> {
+        self.messaging_store()?
+            .search_public_communities(query, skip, limit)
+            .await
+    }
     async fn leave_community(&mut self, community_id: Uuid) -> Result<(), Error> {
         self.messaging_store()?.leave_community(community_id).await
     }
@@ -1751,6 +1976,15 @@ impl RayGunCommunity for WarpIpfs {
             .edit_community_banner(community_id, location)
             .await
     }
+    async fn set_community_listing(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .set_community_listing(community_id, invite_id)
+            .await
+    }
 
     async fn create_community_invite(
         &mut self,
@@ -1780,6 +2014,14 @@ impl RayGunCommunity for WarpIpfs {
             .get_community_invite(community_id, invite_id)
             .await
     }
+    async fn list_community_invites(
+        &self,
+        community_id: Uuid,
+    ) -> Result<Vec<CommunityInvite>, Error> {
+        self.messaging_store()?
+            .list_community_invites(community_id)
+            .await
+    }
     async fn request_join_community(&mut self, community_id: Uuid) -> Result<(), Error> {
         self.messaging_store()?
             .request_join_community(community_id)
@@ -1833,6 +2075,46 @@ impl RayGunCommunity for WarpIpfs {
             .edit_community_role_name(community_id, role_id, new_name)
             .await
     }
+    async fn edit_community_role_position(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        position: u32,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .edit_community_role_position(community_id, role_id, position)
+            .await
+    }
+    async fn edit_community_role_color(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        color: Option<u32>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .edit_community_role_color(community_id, role_id, color)
+            .await
+    }
+    async fn edit_community_role_hoisted(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        hoisted: bool,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .edit_community_role_hoisted(community_id, role_id, hoisted)
+            .await
+    }
+    async fn edit_community_role_mentionable(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        mentionable: bool,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .edit_community_role_mentionable(community_id, role_id, mentionable)
+            .await
+    }
     async fn grant_community_role(
         &mut self,
         community_id: Uuid,
@@ -1882,6 +2164,52 @@ impl RayGunCommunity for WarpIpfs {
             .get_community_channel(community_id, channel_id)
             .await
     }
+    async fn reorder_community_channels(
+        &mut self,
+        community_id: Uuid,
+        channels: Vec<Uuid>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .reorder_community_channels(community_id, channels)
+            .await
+    }
+    async fn create_community_channel_category(
+        &mut self,
+        community_id: Uuid,
+        name: &str,
+    ) -> Result<CommunityChannelCategory, Error> {
+        self.messaging_store()?
+            .create_community_channel_category(community_id, name)
+            .await
+    }
+    async fn delete_community_channel_category(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .delete_community_channel_category(community_id, category_id)
+            .await
+    }
+    async fn get_community_channel_category(
+        &self,
+        community_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<CommunityChannelCategory, Error> {
+        self.messaging_store()?
+            .get_community_channel_category(community_id, category_id)
+            .await
+    }
+    async fn move_channel_to_category(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .move_channel_to_category(community_id, channel_id, category_id)
+            .await
+    }
 
     async fn edit_community_name(&mut self, community_id: Uuid, name: &str) -> Result<(), Error> {
         self.messaging_store()?
@@ -1944,6 +2272,66 @@ impl RayGunCommunity for WarpIpfs {
             .remove_community_member(community_id, member)
             .await
     }
+    async fn ban_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+        reason: Option<String>,
+        expiry: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .ban_community_member(community_id, member, reason, expiry)
+            .await
+    }
+    async fn unban_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .unban_community_member(community_id, member)
+            .await
+    }
+    async fn list_community_bans(&self, community_id: Uuid) -> Result<Vec<CommunityBan>, Error> {
+        self.messaging_store()?
+            .list_community_bans(community_id)
+            .await
+    }
+    async fn timeout_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+        until: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .timeout_community_member(community_id, member, until)
+            .await
+    }
+
+    async fn transfer_community_ownership(
+        &mut self,
+        community_id: Uuid,
+        new_owner: DID,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .transfer_community_ownership(community_id, new_owner)
+            .await
+    }
+    async fn accept_community_ownership(&mut self, community_id: Uuid) -> Result<(), Error> {
+        self.messaging_store()?
+            .accept_community_ownership(community_id)
+            .await
+    }
+
+    async fn get_community_audit_log(
+        &self,
+        community_id: Uuid,
+        options: CommunityAuditLogOptions,
+    ) -> Result<Vec<CommunityAuditLogEntry>, Error> {
+        self.messaging_store()?
+            .get_community_audit_log(community_id, options)
+            .await
+    }
 
     async fn edit_community_channel_name(
         &mut self,
@@ -1965,6 +2353,41 @@ impl RayGunCommunity for WarpIpfs {
             .edit_community_channel_description(community_id, channel_id, description)
             .await
     }
+    async fn edit_community_channel_slow_mode(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .edit_community_channel_slow_mode(community_id, channel_id, slow_mode)
+            .await
+    }
+    async fn follow_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .follow_community_channel(
+                community_id,
+                channel_id,
+                source_community_id,
+                source_channel_id,
+            )
+            .await
+    }
+    async fn unfollow_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .unfollow_community_channel(community_id, channel_id)
+            .await
+    }
     async fn grant_community_channel_permission(
         &mut self,
         community_id: Uuid,
@@ -2008,6 +2431,67 @@ impl RayGunCommunity for WarpIpfs {
             .await
     }
 
+    async fn grant_community_channel_category_permission(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .grant_community_channel_category_permission(
+                community_id,
+                category_id,
+                permission,
+                role_id,
+            )
+            .await
+    }
+    async fn revoke_community_channel_category_permission(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .revoke_community_channel_category_permission(
+                community_id,
+                category_id,
+                permission,
+                role_id,
+            )
+            .await
+    }
+    async fn grant_community_channel_category_permission_for_all(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .grant_community_channel_category_permission_for_all(
+                community_id,
+                category_id,
+                permission,
+            )
+            .await
+    }
+    async fn revoke_community_channel_category_permission_for_all(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .revoke_community_channel_category_permission_for_all(
+                community_id,
+                category_id,
+                permission,
+            )
+            .await
+    }
+
     async fn get_community_channel_message(
         &self,
         community_id: Uuid,
@@ -2109,6 +2593,66 @@ impl RayGunCommunity for WarpIpfs {
             .delete_community_channel_message(community_id, channel_id, message_id)
             .await
     }
+    async fn list_quarantined_community_channel_messages(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        self.messaging_store()?
+            .list_quarantined_community_channel_messages(community_id, channel_id)
+            .await
+    }
+    async fn release_quarantined_community_channel_message(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .release_quarantined_community_channel_message(community_id, channel_id, message_id)
+            .await
+    }
+    async fn send_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        poll: Poll,
+    ) -> Result<Uuid, Error> {
+        self.messaging_store()?
+            .send_community_channel_poll(community_id, channel_id, poll)
+            .await
+    }
+    async fn vote_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .vote_community_channel_poll(community_id, channel_id, message_id, options)
+            .await
+    }
+    async fn close_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.messaging_store()?
+            .close_community_channel_poll(community_id, channel_id, message_id)
+            .await
+    }
+    async fn community_channel_poll_results(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        self.messaging_store()?
+            .community_channel_poll_results(community_id, channel_id, message_id)
+            .await
+    }
     async fn pin_community_channel_message(
         &mut self,
         community_id: Uuid,
@@ -2273,6 +2817,12 @@ impl RayGunEvents for WarpIpfs {
             .cancel_event(conversation_id, event)
             .await
     }
+
+    async fn typing_participants(&self, conversation_id: Uuid) -> Result<Vec<DID>, Error> {
+        self.messaging_store()?
+            .typing_participants(conversation_id)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
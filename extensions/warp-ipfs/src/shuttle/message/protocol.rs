@@ -6,6 +6,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use uuid::Uuid;
 use warp::crypto::DID;
 
+use crate::store::community::CommunityListingDocument;
 use crate::store::payload::{PayloadBuilder, PayloadMessage};
 
 pub fn payload_message_construct<T: Serialize + DeserializeOwned + Clone>(
@@ -33,6 +34,7 @@ pub enum Request {
     RegisterConversation(RegisterConversation),
     MessageUpdate(MessageUpdate),
     FetchMailBox { conversation_id: Uuid },
+    CommunityDirectory(CommunityDirectory),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -62,6 +64,22 @@ pub enum MessageUpdate {
     },
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommunityDirectory {
+    Publish {
+        listing: CommunityListingDocument,
+    },
+    Remove {
+        community_id: Uuid,
+    },
+    Search {
+        query: Option<String>,
+        skip: usize,
+        limit: usize,
+    },
+}
+
 impl From<RegisterConversation> for Request {
     fn from(request: RegisterConversation) -> Self {
         Self::RegisterConversation(request)
@@ -74,6 +92,12 @@ impl From<MessageUpdate> for Request {
     }
 }
 
+impl From<CommunityDirectory> for Request {
+    fn from(request: CommunityDirectory) -> Self {
+        Self::CommunityDirectory(request)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Response {
@@ -82,5 +106,8 @@ pub enum Response {
         conversation_id: Uuid,
         content: BTreeMap<String, Cid>,
     },
+    CommunityListings {
+        listings: Vec<CommunityListingDocument>,
+    },
     Error(String),
 }
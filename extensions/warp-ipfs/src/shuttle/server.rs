@@ -23,7 +23,7 @@ use super::{
     },
     message::{
         self,
-        protocol::{RegisterConversation, Response as MessageResponse},
+        protocol::{CommunityDirectory, RegisterConversation, Response as MessageResponse},
     },
     subscription_stream::Subscriptions,
 };
@@ -71,6 +71,7 @@ struct ShuttleTask {
     root_storage: super::store::root::RootStorage,
     identity_storage: super::store::identity::IdentityStorage,
     message_storage: super::store::messages::MessageStorage,
+    community_directory_storage: super::store::communities::CommunityDirectoryStorage,
     subscriptions: super::subscription_stream::Subscriptions,
     requests: FuturesUnordered<BoxFuture<'static, ()>>,
     identity_request_response: IdReqSt,
@@ -197,6 +198,9 @@ impl ShuttleServer {
         let root = super::store::root::RootStorage::new(&ipfs).await;
         let identity = super::store::identity::IdentityStorage::new(&ipfs, &root).await;
         let message = super::store::messages::MessageStorage::new(&ipfs, &root, &identity).await;
+        let community_directory =
+            super::store::communities::CommunityDirectoryStorage::new(&ipfs, &root, &identity)
+                .await;
 
         println!(
             "Identities Registered: {}",
@@ -235,6 +239,7 @@ impl ShuttleServer {
             root_storage: root,
             identity_storage: identity,
             message_storage: message,
+            community_directory_storage: community_directory,
             requests,
             identity_request_response,
             message_request_response,
@@ -934,6 +939,7 @@ impl ShuttleTask {
     ) {
         let ipfs = self.ipfs.clone();
         let message_storage = self.message_storage.clone();
+        let community_directory_storage = self.community_directory_storage.clone();
 
         let fut = async move {
             let keypair = ipfs.keypair();
@@ -1063,6 +1069,46 @@ impl ShuttleTask {
                         Err(e) => message::protocol::Response::Error(e.to_string()),
                     };
 
+                    let payload =
+                        message::protocol::payload_message_construct(keypair, None, message)
+                            .expect("Valid payload construction");
+
+                    let bytes = payload.to_bytes().expect("valid deserialization");
+                    _ = ipfs
+                        .send_response(sender_peer_id, id, (protocols::SHUTTLE_MESSAGE, bytes))
+                        .await;
+                }
+                message::protocol::Request::CommunityDirectory(request) => {
+                    let message = match request {
+                        CommunityDirectory::Publish { listing } => {
+                            let community_id = listing.community_id;
+                            tracing::info!(%community_id, %did, "publishing community listing");
+                            match community_directory_storage.publish(&did, listing).await {
+                                Ok(_) => MessageResponse::Ack,
+                                Err(e) => {
+                                    tracing::error!(%community_id, %did, error = %e, "unable to publish community listing");
+                                    MessageResponse::Error(e.to_string())
+                                }
+                            }
+                        }
+                        CommunityDirectory::Remove { community_id } => {
+                            tracing::info!(%community_id, %did, "removing community listing");
+                            match community_directory_storage.remove(&did, community_id).await {
+                                Ok(_) => MessageResponse::Ack,
+                                Err(e) => {
+                                    tracing::error!(%community_id, %did, error = %e, "unable to remove community listing");
+                                    MessageResponse::Error(e.to_string())
+                                }
+                            }
+                        }
+                        CommunityDirectory::Search { query, skip, limit } => {
+                            let listings = community_directory_storage
+                                .search(query.as_deref(), skip, limit)
+                                .await;
+                            MessageResponse::CommunityListings { listings }
+                        }
+                    };
+
                     let payload =
                         message::protocol::payload_message_construct(keypair, None, message)
                             .expect("Valid payload construction");
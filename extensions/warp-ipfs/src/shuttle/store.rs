@@ -1,3 +1,4 @@
+pub mod communities;
 pub mod identity;
 pub mod messages;
 pub mod root;
@@ -0,0 +1,223 @@
+// This module handles the public community directory. Owners publish a listing of their community which can then be
+// searched by anyone using the node
+use std::{collections::BTreeMap, sync::Arc};
+
+use ipld_core::cid::Cid;
+use rust_ipfs::Ipfs;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+use warp::{crypto::DID, error::Error};
+
+use crate::store::{
+    community::CommunityListingDocument, MAX_COMMUNITY_DESCRIPTION, MAX_COMMUNITY_NAME_LENGTH,
+};
+
+use super::{identity::IdentityStorage, root::RootStorage};
+
+const MAX_SEARCH_RESULTS: usize = 100;
+const MAX_LISTINGS_PER_OWNER: usize = 10;
+
+#[derive(Clone)]
+pub struct CommunityDirectoryStorage {
+    inner: Arc<RwLock<CommunityDirectoryStorageInner>>,
+}
+
+struct CommunityDirectoryStorageInner {
+    ipfs: Ipfs,
+    list: Option<Cid>,
+    listings: BTreeMap<String, CommunityListingDocument>,
+    identity: IdentityStorage,
+    root: RootStorage,
+}
+
+impl CommunityDirectoryStorage {
+    pub async fn new(ipfs: &Ipfs, root: &RootStorage, identity: &IdentityStorage) -> Self {
+        let root_dag = root.get_root().await;
+
+        let list = root_dag.community_directory;
+
+        let listings = match list {
+            Some(cid) => ipfs
+                .get_dag(cid)
+                .local()
+                .deserialized()
+                .await
+                .unwrap_or_default(),
+            None => BTreeMap::new(),
+        };
+
+        let inner = Arc::new(RwLock::new(CommunityDirectoryStorageInner {
+            ipfs: ipfs.clone(),
+            root: root.clone(),
+            identity: identity.clone(),
+            list,
+            listings,
+        }));
+
+        Self { inner }
+    }
+
+    pub async fn publish(
+        &self,
+        owner: &DID,
+        listing: CommunityListingDocument,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.publish(owner, listing).await
+    }
+
+    pub async fn remove(&self, owner: &DID, community_id: Uuid) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.remove(owner, community_id).await
+    }
+
+    pub async fn search(
+        &self,
+        query: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Vec<CommunityListingDocument> {
+        let inner = &*self.inner.read().await;
+        inner.search(query, skip, limit)
+    }
+}
+
+impl CommunityDirectoryStorageInner {
+    async fn publish(
+        &mut self,
+        owner: &DID,
+        listing: CommunityListingDocument,
+    ) -> Result<(), Error> {
+        if !self.identity.contains(owner).await {
+            return Err(Error::IdentityDoesntExist);
+        }
+
+        if &listing.owner != owner {
+            return Err(Error::Unauthorized);
+        }
+
+        // The listing is signed by the owner, proving that the community belongs to them and that
+        // they published its contents
+        listing.verify()?;
+
+        if listing.name.is_empty() || listing.name.len() > MAX_COMMUNITY_NAME_LENGTH {
+            return Err(Error::InvalidLength {
+                context: "name".into(),
+                minimum: Some(1),
+                maximum: Some(MAX_COMMUNITY_NAME_LENGTH),
+                current: listing.name.len(),
+            });
+        }
+
+        if let Some(description) = listing.description.as_ref() {
+            if description.is_empty() || description.len() > MAX_COMMUNITY_DESCRIPTION {
+                return Err(Error::InvalidLength {
+                    context: "description".into(),
+                    minimum: Some(1),
+                    maximum: Some(MAX_COMMUNITY_DESCRIPTION),
+                    current: description.len(),
+                });
+            }
+        }
+
+        let id = listing.community_id.to_string();
+
+        // Only the owner that originally listed the community is able to replace the listing
+        if self
+            .listings
+            .get(&id)
+            .is_some_and(|existing| &existing.owner != owner)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let listed = self
+            .listings
+            .iter()
+            .filter(|(listing_id, existing)| *listing_id != &id && &existing.owner == owner)
+            .count();
+
+        if listed >= MAX_LISTINGS_PER_OWNER {
+            return Err(Error::InvalidLength {
+                context: "listings".into(),
+                minimum: None,
+                maximum: Some(MAX_LISTINGS_PER_OWNER),
+                current: listed + 1,
+            });
+        }
+
+        tracing::info!(community_id = %listing.community_id, %owner, "publishing community listing");
+        self.listings.insert(id, listing);
+        self.save().await
+    }
+
+    async fn remove(&mut self, owner: &DID, community_id: Uuid) -> Result<(), Error> {
+        let id = community_id.to_string();
+
+        match self.listings.get(&id) {
+            Some(existing) if &existing.owner != owner => return Err(Error::Unauthorized),
+            Some(_) => {}
+            None => return Ok(()),
+        }
+
+        tracing::info!(%community_id, %owner, "removing community listing");
+        self.listings.remove(&id);
+        self.save().await
+    }
+
+    fn search(
+        &self,
+        query: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Vec<CommunityListingDocument> {
+        let query = query
+            .map(|query| query.trim().to_lowercase())
+            .filter(|query| !query.is_empty());
+
+        let mut listings =
+            self.listings
+                .values()
+                .filter(|listing| match &query {
+                    Some(query) => {
+                        listing.name.to_lowercase().contains(query)
+                            || listing.description.as_ref().is_some_and(|description| {
+                                description.to_lowercase().contains(query)
+                            })
+                    }
+                    None => true,
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+        // The member count is reported by the owner and cannot be verified, so listings are
+        // ranked by when they were last modified instead
+        listings.sort_by(|a, b| b.modified.cmp(&a.modified));
+
+        let limit = match limit {
+            0 => MAX_SEARCH_RESULTS,
+            limit => limit.min(MAX_SEARCH_RESULTS),
+        };
+
+        listings.into_iter().skip(skip).take(limit).collect()
+    }
+
+    async fn save(&mut self) -> Result<(), Error> {
+        let root_cid = self.ipfs.put_dag(&self.listings).await?;
+
+        if !self.ipfs.is_pinned(root_cid).await.unwrap_or_default() {
+            self.ipfs.insert_pin(root_cid).recursive().local().await?;
+        }
+
+        let mut old_cid = self.list.replace(root_cid);
+
+        if let Some(cid) = old_cid.take() {
+            if cid != root_cid {
+                self.ipfs.remove_pin(cid).recursive().await?;
+            }
+        }
+
+        self.root.set_community_directory(root_cid).await?;
+        Ok(())
+    }
+}
@@ -17,6 +17,8 @@ pub struct Root {
     pub mailbox: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conversation_mailbox: Option<Cid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub community_directory: Option<Cid>,
 }
 
 #[derive(Debug)]
@@ -81,6 +83,11 @@ impl RootStorage {
         inner.set_conversation_mailbox(&self.ipfs, cid).await
     }
 
+    pub async fn set_community_directory(&self, cid: Cid) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.set_community_directory(&self.ipfs, cid).await
+    }
+
     pub async fn get_root(&self) -> Root {
         let inner = &*self.inner.read().await;
         inner.root
@@ -111,6 +118,13 @@ impl RootInner {
         Ok(())
     }
 
+    async fn set_community_directory(&mut self, ipfs: &Ipfs, cid: Cid) -> Result<(), Error> {
+        self.root.community_directory.replace(cid);
+        tracing::debug!(%cid, "community directory set");
+        self.save(ipfs).await?;
+        Ok(())
+    }
+
     async fn save(&mut self, ipfs: &Ipfs) -> std::io::Result<()> {
         let cid = ipfs
             .put_dag(self.root)
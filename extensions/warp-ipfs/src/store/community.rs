@@ -22,8 +22,10 @@ use warp::{
     error::Error,
     raygun::{
         community::{
-            Community, CommunityChannel, CommunityChannelPermission, CommunityChannelPermissions,
-            CommunityChannelType, CommunityInvite, CommunityPermission, CommunityPermissions,
+            Community, CommunityAuditAction, CommunityAuditLogEntry, CommunityBan,
+            CommunityChannel, CommunityChannelCategory, CommunityChannelFollow,
+            CommunityChannelPermission, CommunityChannelPermissions, CommunityChannelType,
+            CommunityInvite, CommunityListing, CommunityPermission, CommunityPermissions,
             CommunityRole, RoleId,
         },
         Message, MessageOptions, MessagePage, MessageReference, Messages, MessagesType,
@@ -35,13 +37,25 @@ pub struct CommunityRoleDocument {
     pub id: RoleId,
     pub name: String,
     pub members: IndexSet<DID>,
+    #[serde(default)]
+    pub position: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(default)]
+    pub hoisted: bool,
+    #[serde(default)]
+    pub mentionable: bool,
 }
 impl CommunityRoleDocument {
-    pub fn new(name: String) -> Self {
+    pub fn new(name: String, position: u32) -> Self {
         Self {
             id: Uuid::new_v4(),
             name,
             members: IndexSet::new(),
+            position,
+            color: None,
+            hoisted: false,
+            mentionable: false,
         }
     }
 }
@@ -51,36 +65,333 @@ impl From<CommunityRoleDocument> for CommunityRole {
         role.set_id(value.id);
         role.set_name(value.name);
         role.set_members(value.members);
+        role.set_position(value.position);
+        role.set_color(value.color);
+        role.set_hoisted(value.hoisted);
+        role.set_mentionable(value.mentionable);
         role
     }
 }
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityListingDocument {
+    pub community_id: Uuid,
+    pub owner: DID,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Cid>,
+    pub member_count: usize,
+    pub invite_id: Uuid,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    /// Signature of the listing by the owner, proving that they own the community and published its contents
+    pub signature: String,
+}
+impl CommunityListingDocument {
+    fn construct(&self) -> Vec<u8> {
+        warp::crypto::hash::sha256_iter(
+            [
+                Some(self.community_id.into_bytes().to_vec()),
+                Some(self.owner.to_string().into_bytes()),
+                Some(self.name.as_bytes().to_vec()),
+                self.description
+                    .as_ref()
+                    .map(|description| description.as_bytes().to_vec()),
+                self.icon.map(|cid| cid.to_bytes()),
+                Some(self.member_count.to_be_bytes().to_vec()),
+                Some(self.invite_id.into_bytes().to_vec()),
+                Some(self.created.to_string().into_bytes()),
+                Some(self.modified.to_string().into_bytes()),
+            ]
+            .into_iter(),
+            None,
+        )
+    }
+
+    pub fn sign(&mut self, keypair: &Keypair) {
+        let signature = keypair.sign(&self.construct()).expect("not RSA");
+        self.signature = bs58::encode(signature).into_string();
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let owner_pk = self.owner.to_public_key()?;
+        let signature = bs58::decode(&self.signature).into_vec()?;
+        if !owner_pk.verify(&self.construct(), &signature) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+impl From<CommunityListingDocument> for CommunityListing {
+    fn from(value: CommunityListingDocument) -> Self {
+        let mut listing = CommunityListing::default();
+        listing.set_id(value.community_id);
+        listing.set_owner(value.owner);
+        listing.set_name(value.name);
+        listing.set_description(value.description);
+        listing.set_icon(value.icon.map(|cid| cid.to_string()));
+        listing.set_member_count(value.member_count);
+        listing.set_invite_id(value.invite_id);
+        listing.set_modified(value.modified);
+        listing
+    }
+}
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CommunityInviteDocument {
     pub id: Uuid,
     pub target_user: Option<DID>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<DID>,
     pub created: DateTime<Utc>,
     pub expiry: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    #[serde(default)]
+    pub uses: u32,
 }
 impl CommunityInviteDocument {
-    pub fn new(target_user: Option<DID>, expiry: Option<DateTime<Utc>>) -> Self {
+    pub fn new(creator: DID, target_user: Option<DID>, expiry: Option<DateTime<Utc>>) -> Self {
         Self {
             id: Uuid::new_v4(),
             target_user,
+            creator: Some(creator),
             created: Utc::now(),
             expiry,
+            max_uses: None,
+            uses: 0,
         }
     }
+    pub fn is_exhausted(&self) -> bool {
+        self.max_uses.is_some_and(|max_uses| self.uses >= max_uses)
+    }
+    pub fn is_valid_for(&self, user: &DID, now: DateTime<Utc>) -> bool {
+        self.expiry.is_none_or(|expiry| expiry > now)
+            && !self.is_exhausted()
+            && self
+                .target_user
+                .as_ref()
+                .is_none_or(|target| user == target)
+    }
 }
 impl From<CommunityInviteDocument> for CommunityInvite {
     fn from(value: CommunityInviteDocument) -> Self {
         let mut community_invite = CommunityInvite::default();
         community_invite.set_id(value.id);
         community_invite.set_target_user(value.target_user);
+        community_invite.set_creator(value.creator);
         community_invite.set_created(value.created);
         community_invite.set_expiry(value.expiry);
+        community_invite.set_max_uses(value.max_uses);
+        community_invite.set_uses(value.uses);
         community_invite
     }
 }
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityBanDocument {
+    pub user: DID,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub issuer: DID,
+    pub created: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<DateTime<Utc>>,
+}
+impl CommunityBanDocument {
+    pub fn new(
+        user: DID,
+        reason: Option<String>,
+        issuer: DID,
+        expiry: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            user,
+            reason,
+            issuer,
+            created: Utc::now(),
+            expiry,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.expiry.is_none_or(|expiry| expiry > Utc::now())
+    }
+}
+impl From<CommunityBanDocument> for CommunityBan {
+    fn from(value: CommunityBanDocument) -> Self {
+        let mut community_ban = CommunityBan::default();
+        community_ban.set_user(value.user);
+        community_ban.set_reason(value.reason);
+        community_ban.set_issuer(value.issuer);
+        community_ban.set_created(value.created);
+        community_ban.set_expiry(value.expiry);
+        community_ban
+    }
+}
+
+/// Entry within the audit log of a community. Each entry links to the one before it and is signed
+/// by the member who made the change, forming an append-only chain. Entries made concurrently by
+/// different members are joined again by the next entry, which links to all of them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityAuditLogEntryDocument {
+    pub id: Uuid,
+    pub actor: DID,
+    pub date: DateTime<Utc>,
+    pub action: CommunityAuditAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<Cid>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged: Vec<Cid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+impl CommunityAuditLogEntryDocument {
+    pub fn new(
+        keypair: &Keypair,
+        action: CommunityAuditAction,
+        previous: Option<Cid>,
+        merged: Vec<Cid>,
+    ) -> Result<Self, Error> {
+        let mut entry = Self {
+            id: Uuid::new_v4(),
+            actor: keypair.to_did()?,
+            date: Utc::now(),
+            action,
+            previous,
+            merged,
+            signature: None,
+        };
+        entry.sign(keypair)?;
+        Ok(entry)
+    }
+
+    fn construct(&self) -> Result<Vec<u8>, Error> {
+        let action = serde_json::to_vec(&self.action)?;
+        Ok(warp::crypto::hash::sha256_iter(
+            [
+                Some(self.id.into_bytes().to_vec()),
+                Some(self.actor.to_string().into_bytes()),
+                Some(self.date.to_string().into_bytes()),
+                Some(action),
+                self.previous.map(|cid| cid.to_bytes()),
+                (!self.merged.is_empty())
+                    .then(|| self.merged.iter().flat_map(|cid| cid.to_bytes()).collect()),
+            ]
+            .into_iter(),
+            None,
+        ))
+    }
+
+    /// Entries this entry directly follows
+    pub fn parents(&self) -> impl Iterator<Item = Cid> + '_ {
+        self.previous.into_iter().chain(self.merged.iter().copied())
+    }
+
+    pub fn sign(&mut self, keypair: &Keypair) -> Result<(), Error> {
+        let construct = self.construct()?;
+        let signature = keypair.sign(&construct).expect("not RSA");
+        self.signature = Some(bs58::encode(signature).into_string());
+        Ok(())
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let actor_pk = self.actor.to_public_key()?;
+
+        let Some(signature) = &self.signature else {
+            return Err(Error::InvalidSignature);
+        };
+
+        let signature = bs58::decode(signature).into_vec()?;
+
+        if !actor_pk.verify(&self.construct()?, &signature) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+impl From<CommunityAuditLogEntryDocument> for CommunityAuditLogEntry {
+    fn from(value: CommunityAuditLogEntryDocument) -> Self {
+        CommunityAuditLogEntry::new(value.id, value.actor, value.date, value.action)
+    }
+}
+
+/// Offer of ownership signed by the owner of the community, allowing every peer to verify the
+/// transfer even if they missed the offer being made
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityOwnershipOfferDocument {
+    pub community_id: Uuid,
+    pub owner: DID,
+    pub new_owner: DID,
+    pub date: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+impl CommunityOwnershipOfferDocument {
+    pub fn new(keypair: &Keypair, community_id: Uuid, new_owner: DID) -> Result<Self, Error> {
+        let mut offer = Self {
+            community_id,
+            owner: keypair.to_did()?,
+            new_owner,
+            date: Utc::now(),
+            signature: None,
+        };
+        offer.sign(keypair)?;
+        Ok(offer)
+    }
+
+    /// Whether the offer cancels earlier offers rather than offering ownership to a member
+    pub fn is_cancellation(&self) -> bool {
+        self.owner == self.new_owner
+    }
+
+    fn construct(&self) -> Vec<u8> {
+        warp::crypto::hash::sha256_iter(
+            [
+                Some(self.community_id.into_bytes().to_vec()),
+                Some(self.owner.to_string().into_bytes()),
+                Some(self.new_owner.to_string().into_bytes()),
+                Some(self.date.to_string().into_bytes()),
+            ]
+            .into_iter(),
+            None,
+        )
+    }
+
+    pub fn sign(&mut self, keypair: &Keypair) -> Result<(), Error> {
+        let signature = keypair.sign(&self.construct()).expect("not RSA");
+        self.signature = Some(bs58::encode(signature).into_string());
+        Ok(())
+    }
+
+    pub fn verify(&self) -> Result<(), Error> {
+        let owner_pk = self.owner.to_public_key()?;
+
+        let Some(signature) = &self.signature else {
+            return Err(Error::InvalidSignature);
+        };
+
+        let signature = bs58::decode(signature).into_vec()?;
+
+        if !owner_pk.verify(&self.construct(), &signature) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+/// Current version of the community permission set
+pub const COMMUNITY_PERMISSIONS_VERSION: u32 = 1;
+
+/// Permissions added since communities were first created, along with the version that added them.
+/// A missing permission is granted to every member, so older documents get these restricted to the
+/// owner instead
+const ADDED_COMMUNITY_PERMISSIONS: [(u32, CommunityPermission); 5] = [
+    (1, CommunityPermission::BanMembers),
+    (1, CommunityPermission::TimeoutMembers),
+    (1, CommunityPermission::ViewAuditLog),
+    (1, CommunityPermission::BypassSlowMode),
+    (1, CommunityPermission::PostAnnouncements),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq)]
 pub struct CommunityDocument {
@@ -93,16 +404,43 @@ pub struct CommunityDocument {
     pub modified: DateTime<Utc>,
     pub members: IndexSet<DID>,
     pub channels: IndexMap<String, CommunityChannelDocument>,
+    #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+    pub channel_order: IndexSet<Uuid>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub categories: IndexMap<String, CommunityChannelCategoryDocument>,
     pub roles: IndexMap<String, CommunityRoleDocument>,
     pub permissions: CommunityPermissions,
     pub invites: IndexMap<String, CommunityInviteDocument>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub invite_joins: IndexMap<String, Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_invite: Option<Uuid>,
+    /// Latest ownership offer made by the owner. An offer made to the owner themselves cancels
+    /// any earlier offer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ownership_offer: Option<CommunityOwnershipOfferDocument>,
+    /// Offer accepted by the current owner when ownership was last transferred
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ownership_transfer: Option<CommunityOwnershipOfferDocument>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub bans: IndexMap<String, CommunityBanDocument>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub timeouts: IndexMap<String, DateTime<Utc>>,
     #[serde(default)]
     pub deleted: bool,
+    /// Version of the permission set the document was created or last migrated with
+    #[serde(default)]
+    pub permissions_version: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub banner: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<Cid>,
+    /// Heads of audit log entries made concurrently with `audit_log`, merged by the next entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub concurrent_audit_logs: Vec<Cid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
 }
 
@@ -112,6 +450,19 @@ impl Hash for CommunityDocument {
     }
 }
 
+/// Content of the community that is signed by the owner
+fn community_header_construct(id: Uuid, owner: &DID, created: DateTime<Utc>) -> Vec<u8> {
+    warp::crypto::hash::sha256_iter(
+        [
+            Some(id.into_bytes().to_vec()),
+            Some(owner.to_string().into_bytes()),
+            Some(created.to_string().into_bytes()),
+        ]
+        .into_iter(),
+        None,
+    )
+}
+
 impl PartialEq for CommunityDocument {
     fn eq(&self, other: &Self) -> bool {
         self.id == other.id
@@ -144,15 +495,7 @@ impl CommunityDocument {
     }
 
     pub fn sign(&mut self, keypair: &Keypair) -> Result<(), Error> {
-        let construct = warp::crypto::hash::sha256_iter(
-            [
-                Some(self.id().into_bytes().to_vec()),
-                Some(self.owner.to_string().into_bytes()),
-                Some(self.created.to_string().into_bytes()),
-            ]
-            .into_iter(),
-            None,
-        );
+        let construct = community_header_construct(self.id, &self.owner, self.created);
 
         let signature = keypair.sign(&construct).expect("not RSA");
         self.signature = Some(bs58::encode(signature).into_string());
@@ -169,15 +512,7 @@ impl CommunityDocument {
 
         let signature = bs58::decode(signature).into_vec()?;
 
-        let construct = warp::crypto::hash::sha256_iter(
-            [
-                Some(self.id().into_bytes().to_vec()),
-                Some(self.owner.to_string().into_bytes()),
-                Some(self.created.to_string().into_bytes()),
-            ]
-            .into_iter(),
-            None,
-        );
+        let construct = community_header_construct(self.id, &self.owner, self.created);
 
         if !creator_pk.verify(&construct, &signature) {
             return Err(Error::InvalidSignature);
@@ -214,9 +549,16 @@ impl CommunityDocument {
         permissions.insert(CommunityPermission::DeleteInvites, IndexSet::new());
 
         permissions.insert(CommunityPermission::RemoveMembers, IndexSet::new());
+        permissions.insert(CommunityPermission::BanMembers, IndexSet::new());
+        permissions.insert(CommunityPermission::TimeoutMembers, IndexSet::new());
+
+        permissions.insert(CommunityPermission::ViewAuditLog, IndexSet::new());
 
         permissions.insert(CommunityPermission::DeleteMessages, IndexSet::new());
         permissions.insert(CommunityPermission::PinMessages, IndexSet::new());
+        permissions.insert(CommunityPermission::PostAnnouncements, IndexSet::new());
+
+        permissions.insert(CommunityPermission::BypassSlowMode, IndexSet::new());
 
         let mut members = IndexSet::new();
         members.insert(creator.clone());
@@ -230,20 +572,46 @@ impl CommunityDocument {
             modified: Utc::now(),
             members,
             channels: IndexMap::new(),
+            channel_order: IndexSet::new(),
+            categories: IndexMap::new(),
             roles: IndexMap::new(),
             permissions,
             invites: IndexMap::new(),
+            invite_joins: IndexMap::new(),
+            public_invite: None,
+            ownership_offer: None,
+            ownership_transfer: None,
+            bans: IndexMap::new(),
+            timeouts: IndexMap::new(),
             deleted: false,
+            permissions_version: COMMUNITY_PERMISSIONS_VERSION,
             icon: None,
             banner: None,
+            audit_log: None,
+            concurrent_audit_logs: vec![],
             signature: None,
         };
         document.sign(keypair)?;
         Ok(document)
     }
 }
+impl CommunityDocument {
+    /// Member that ownership of the community has been offered to, if any
+    pub fn pending_owner(&self) -> Option<&DID> {
+        self.ownership_offer
+            .as_ref()
+            .filter(|offer| self.is_valid_offer(offer) && !offer.is_cancellation())
+            .map(|offer| &offer.new_owner)
+    }
+
+    /// Checks that the offer was made for this community by its current owner
+    pub fn is_valid_offer(&self, offer: &CommunityOwnershipOfferDocument) -> bool {
+        offer.community_id == self.id && offer.owner == self.owner && offer.verify().is_ok()
+    }
+}
 impl From<CommunityDocument> for Community {
     fn from(value: CommunityDocument) -> Self {
+        let pending_owner = value.pending_owner().cloned();
         let mut community = Community::default();
         community.set_id(value.id);
         community.set_name(value.name);
@@ -252,13 +620,10 @@ impl From<CommunityDocument> for Community {
         community.set_created(value.created);
         community.set_modified(value.modified);
         community.set_members(value.members);
-        community.set_channels(
-            value
-                .channels
-                .iter()
-                .map(|(k, _)| RoleId::parse_str(k).expect("should be valid uuid"))
-                .collect(),
-        );
+        community.set_channels(value.ordered_channels());
+        let mut categories = value.categories.values().collect::<Vec<_>>();
+        categories.sort_by_key(|category| category.created);
+        community.set_categories(categories.iter().map(|category| category.id).collect());
         community.set_roles(
             value
                 .roles
@@ -274,30 +639,195 @@ impl From<CommunityDocument> for Community {
                 .map(|(k, _)| RoleId::parse_str(k).expect("should be valid uuid"))
                 .collect(),
         );
+        community.set_invite_joins(
+            value
+                .invite_joins
+                .iter()
+                .filter_map(|(member, invite_id)| {
+                    member
+                        .parse::<DID>()
+                        .ok()
+                        .map(|member| (member, *invite_id))
+                })
+                .collect(),
+        );
+        community.set_public_invite(value.public_invite);
+        community.set_pending_owner(pending_owner);
         community
     }
 }
 impl CommunityDocument {
+    /// Channels in the order set by the community. Any channel missing from the order, such as
+    /// those created before it was set, follows in the order it was added
+    pub fn ordered_channels(&self) -> IndexSet<Uuid> {
+        let mut channels = self
+            .channel_order
+            .iter()
+            .filter(|id| self.channels.contains_key(&id.to_string()))
+            .copied()
+            .collect::<IndexSet<_>>();
+        channels.extend(self.channels.values().map(|channel| channel.id));
+        channels
+    }
+    pub fn category_channels(&self, category_id: Uuid) -> IndexSet<Uuid> {
+        self.ordered_channels()
+            .into_iter()
+            .filter(|id| {
+                self.channels
+                    .get(&id.to_string())
+                    .is_some_and(|channel| channel.category == Some(category_id))
+            })
+            .collect()
+    }
     pub fn participants(&self) -> IndexSet<DID> {
         let mut participants = self.members.clone();
         participants.insert(self.owner.clone());
         participants
     }
+    /// Listing published to the public directory, if the community is public, signed by the owner
+    pub fn listing(&self, keypair: &Keypair) -> Option<CommunityListingDocument> {
+        if self.deleted {
+            return None;
+        }
+        let invite_id = self.public_invite?;
+        let mut listing = CommunityListingDocument {
+            community_id: self.id,
+            owner: self.owner.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            icon: self.icon,
+            member_count: self.participants().len(),
+            invite_id,
+            created: self.created,
+            modified: self.modified,
+            signature: String::new(),
+        };
+        listing.sign(keypair);
+        Some(listing)
+    }
     pub fn has_valid_invite(&self, user: &DID) -> bool {
-        for (_, invite) in &self.invites {
-            let is_expired = match &invite.expiry {
-                Some(expiry) => expiry < &Utc::now(),
-                None => false,
-            };
-            let is_valid_target = match &invite.target_user {
-                Some(target) => user == target,
-                None => true,
-            };
-            if !is_expired && is_valid_target {
-                return true;
+        self.valid_invite(user, Utc::now()).is_some()
+    }
+    /// Invite the user is able to join with, preferring one that targets the user directly
+    pub fn valid_invite(&self, user: &DID, now: DateTime<Utc>) -> Option<&CommunityInviteDocument> {
+        let mut invites = self
+            .invites
+            .values()
+            .filter(|invite| invite.is_valid_for(user, now));
+        let first = invites.next()?;
+        if first.target_user.is_some() {
+            return Some(first);
+        }
+        invites
+            .find(|invite| invite.target_user.is_some())
+            .or(Some(first))
+    }
+    pub fn is_banned(&self, user: &DID) -> bool {
+        self.bans
+            .get(&user.to_string())
+            .is_some_and(CommunityBanDocument::is_active)
+    }
+    /// Position of the highest role held by the user, if any. Lower positions rank higher
+    pub fn highest_role_position(&self, user: &DID) -> Option<u32> {
+        self.roles
+            .values()
+            .filter(|role| role.members.contains(user))
+            .map(|role| role.position)
+            .min()
+    }
+    /// Whether the user ranks above the given position within the role hierarchy
+    pub fn outranks_position(&self, user: &DID, position: u32) -> bool {
+        if &self.owner == user {
+            return true;
+        }
+        self.highest_role_position(user)
+            .is_some_and(|highest| highest < position)
+    }
+    /// Whether the user is able to manage the role based on the role hierarchy
+    pub fn can_manage_role(&self, user: &DID, role_id: RoleId) -> bool {
+        self.roles
+            .get(&role_id.to_string())
+            .is_some_and(|role| self.outranks_position(user, role.position))
+    }
+    /// Whether the user ranks above the other member within the role hierarchy
+    pub fn outranks(&self, user: &DID, other: &DID) -> bool {
+        if &self.owner == user {
+            return true;
+        }
+        if &self.owner == other {
+            return false;
+        }
+        match (
+            self.highest_role_position(user),
+            self.highest_role_position(other),
+        ) {
+            (Some(highest), Some(other_highest)) => highest < other_highest,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+    pub fn is_timed_out(&self, user: &DID) -> bool {
+        self.timeouts
+            .get(&user.to_string())
+            .is_some_and(|until| *until > Utc::now())
+    }
+    /// Slow mode the user is subject to in the channel, if any
+    pub fn slow_mode(&self, user: &DID, channel_id: Uuid) -> Option<Duration> {
+        if self.has_permission(user, &CommunityPermission::BypassSlowMode) {
+            return None;
+        }
+        self.channels
+            .get(&channel_id.to_string())
+            .and_then(|channel| channel.slow_mode)
+    }
+    /// Restricts permissions added after the document was created to the owner. Permissions later
+    /// granted to everyone are left alone since the document is then at the current version
+    pub fn migrate_permissions(&mut self) {
+        if self.permissions_version >= COMMUNITY_PERMISSIONS_VERSION {
+            return;
+        }
+        for (version, permission) in ADDED_COMMUNITY_PERMISSIONS {
+            if version > self.permissions_version {
+                self.permissions.entry(permission).or_default();
+            }
+        }
+        self.permissions_version = COMMUNITY_PERMISSIONS_VERSION;
+    }
+    /// Gives every role a unique position, keeping their current order. Roles created before
+    /// positions existed all share the same one, so they are ordered by when they were added
+    pub fn normalize_role_positions(&mut self) {
+        let positions = self
+            .roles
+            .values()
+            .map(|role| role.position)
+            .collect::<BTreeSet<_>>();
+        if positions.len() == self.roles.len() {
+            return;
+        }
+        let mut roles = self.roles.values_mut().collect::<Vec<_>>();
+        roles.sort_by_key(|role| role.position);
+        for (position, role) in roles.into_iter().enumerate() {
+            role.position = position as u32;
+        }
+    }
+    /// Moves the role to the position, shifting the roles in between so that positions stay unique
+    pub fn move_role(&mut self, role_id: RoleId, position: u32) {
+        let Some(current) = self
+            .roles
+            .get(&role_id.to_string())
+            .map(|role| role.position)
+        else {
+            return;
+        };
+        for role in self.roles.values_mut() {
+            if role.id == role_id {
+                role.position = position;
+            } else if position < current && (position..current).contains(&role.position) {
+                role.position += 1;
+            } else if position > current && (current + 1..=position).contains(&role.position) {
+                role.position -= 1;
             }
         }
-        false
     }
     pub fn has_permission(&self, user: &DID, has_permission: &CommunityPermission) -> bool {
         if &self.owner == user {
@@ -334,8 +864,25 @@ impl CommunityDocument {
         let Some(channel) = self.channels.get(&channel_id.to_string()) else {
             return false;
         };
-        let Some(authorized_roles) = channel.permissions.get(has_permission) else {
-            return true;
+        if has_permission == &CommunityChannelPermission::SendMessages
+            && channel.channel_type == CommunityChannelType::Announcement
+            && !self.has_permission(user, &CommunityPermission::PostAnnouncements)
+        {
+            return false;
+        }
+        // Permissions set on the channel take precedence over those of its category
+        let authorized_roles = match channel.permissions.get(has_permission) {
+            Some(authorized_roles) => authorized_roles,
+            None => {
+                let Some(authorized_roles) = channel
+                    .category
+                    .and_then(|category_id| self.categories.get(&category_id.to_string()))
+                    .and_then(|category| category.permissions.get(has_permission))
+                else {
+                    return true;
+                };
+                authorized_roles
+            }
         };
         for authorized_role in authorized_roles {
             if let Some(role) = self.roles.get(&authorized_role.to_string()) {
@@ -358,7 +905,13 @@ pub struct CommunityChannelDocument {
     pub channel_type: CommunityChannelType,
     pub permissions: CommunityChannelPermissions,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub messages: Option<Cid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_mode: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub following: Option<CommunityChannelFollowDocument>,
 }
 impl CommunityChannelDocument {
     pub fn new(
@@ -374,7 +927,10 @@ impl CommunityChannelDocument {
             modified: Utc::now(),
             channel_type,
             permissions: CommunityChannelPermissions::new(),
+            category: None,
             messages: None,
+            slow_mode: None,
+            following: None,
         }
     }
 }
@@ -707,6 +1263,177 @@ impl From<CommunityChannelDocument> for CommunityChannel {
         community_channel.set_modified(value.modified);
         community_channel.set_channel_type(value.channel_type);
         community_channel.set_permissions(value.permissions);
+        community_channel.set_category(value.category);
+        community_channel.set_slow_mode(value.slow_mode);
+        community_channel.set_following(value.following.map(Into::into));
         community_channel
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityChannelFollowDocument {
+    pub community_id: Uuid,
+    pub channel_id: Uuid,
+    pub follower: DID,
+}
+impl From<CommunityChannelFollowDocument> for CommunityChannelFollow {
+    fn from(value: CommunityChannelFollowDocument) -> Self {
+        let mut follow = CommunityChannelFollow::default();
+        follow.set_community_id(value.community_id);
+        follow.set_channel_id(value.channel_id);
+        follow.set_follower(value.follower);
+        follow
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityChannelCategoryDocument {
+    pub id: Uuid,
+    pub name: String,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    pub permissions: CommunityChannelPermissions,
+}
+impl CommunityChannelCategoryDocument {
+    pub fn new(name: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            created: Utc::now(),
+            modified: Utc::now(),
+            permissions: CommunityChannelPermissions::new(),
+        }
+    }
+}
+impl From<CommunityChannelCategoryDocument> for CommunityChannelCategory {
+    fn from(value: CommunityChannelCategoryDocument) -> Self {
+        let mut category = CommunityChannelCategory::default();
+        category.set_id(value.id);
+        category.set_name(value.name);
+        category.set_created(value.created);
+        category.set_modified(value.modified);
+        category.set_permissions(value.permissions);
+        category
+    }
+}
+
+/// Date a message received from a peer counts against slow mode. The date the sender signed is
+/// used so messages delivered together, such as when reconnecting, are not rejected for arriving
+/// at once, but it is never later than when the message was received
+pub fn slow_mode_date(sent: DateTime<Utc>, received: DateTime<Utc>) -> DateTime<Utc> {
+    sent.min(received)
+}
+
+/// Whether enough time has passed since the last message for a message dated `date`
+pub fn slow_mode_elapsed(
+    slow_mode: Duration,
+    last_sent: DateTime<Utc>,
+    date: DateTime<Utc>,
+) -> bool {
+    (date - last_sent)
+        .to_std()
+        .is_ok_and(|elapsed| elapsed >= slow_mode)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use rust_ipfs::Keypair;
+    use std::time::Duration;
+    use warp::raygun::community::CommunityPermission;
+
+    use super::{slow_mode_date, slow_mode_elapsed, CommunityDocument, CommunityRoleDocument};
+    use crate::store::{PeerIdExt, SLOW_MODE_SKEW};
+
+    #[test]
+    fn missing_permission_is_restricted_after_migration() -> anyhow::Result<()> {
+        let owner = Keypair::generate_ed25519();
+        let member = Keypair::generate_ed25519().to_did()?;
+
+        let mut document = CommunityDocument::new(&owner, "Community0".into())?;
+        document.members.insert(member.clone());
+
+        // Documents created before moderation permissions existed have no entry for them
+        document
+            .permissions
+            .swap_remove(&CommunityPermission::BanMembers);
+        document.permissions_version = 0;
+        assert!(document.has_permission(&member, &CommunityPermission::BanMembers));
+
+        document.migrate_permissions();
+        assert!(!document.has_permission(&member, &CommunityPermission::BanMembers));
+        assert!(document.has_permission(&owner.to_did()?, &CommunityPermission::BanMembers));
+
+        // Granting the permission to everyone afterwards is kept
+        document
+            .permissions
+            .swap_remove(&CommunityPermission::BanMembers);
+        document.migrate_permissions();
+        assert!(document.has_permission(&member, &CommunityPermission::BanMembers));
+        Ok(())
+    }
+
+    #[test]
+    fn role_positions_are_unique() -> anyhow::Result<()> {
+        let owner = Keypair::generate_ed25519();
+        let mut document = CommunityDocument::new(&owner, "Community0".into())?;
+
+        // Roles created before positions existed all load at the same position
+        let roles = (0..3)
+            .map(|i| CommunityRoleDocument::new(format!("Role{i}"), 0))
+            .collect::<Vec<_>>();
+        for role in &roles {
+            document.roles.insert(role.id.to_string(), role.clone());
+        }
+
+        document.normalize_role_positions();
+        let positions = |document: &CommunityDocument| {
+            roles
+                .iter()
+                .map(|role| document.roles[&role.id.to_string()].position)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&document), [0, 1, 2]);
+
+        document.move_role(roles[2].id, 0);
+        assert_eq!(positions(&document), [1, 2, 0]);
+
+        document.move_role(roles[2].id, 2);
+        assert_eq!(positions(&document), [0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn delayed_messages_within_slow_mode_are_accepted() {
+        let slow_mode = Duration::from_secs(10);
+        let skew = chrono::Duration::milliseconds(SLOW_MODE_SKEW);
+        let start = Utc::now() - chrono::Duration::minutes(5);
+
+        // Receives a batch of messages at once, returning how many were accepted
+        let receive = |dates: &[chrono::Duration]| {
+            let received = Utc::now();
+            let mut last_sent = None;
+            let mut accepted = 0;
+            for offset in dates {
+                let date = slow_mode_date(start + *offset, received);
+                if last_sent.is_some_and(|last| !slow_mode_elapsed(slow_mode, last, date + skew)) {
+                    continue;
+                }
+                last_sent = Some(date);
+                accepted += 1;
+            }
+            accepted
+        };
+
+        // Sent within the limit, but delivered together after a delay
+        let spaced = [0, 10, 20, 30].map(chrono::Duration::seconds);
+        assert_eq!(receive(&spaced), 4);
+
+        let burst = [0, 1, 2, 3].map(chrono::Duration::seconds);
+        assert_eq!(receive(&burst), 1);
+
+        // Dates ahead of the time the messages were received count as received
+        let future = [10, 20].map(chrono::Duration::minutes);
+        assert_eq!(receive(&future), 1);
+    }
+}
@@ -1,11 +1,16 @@
+pub mod crdt;
 pub mod message;
 pub mod reference;
 
-use super::{keystore::Keystore, topics::ConversationTopic, verify_serde_sig, PeerIdExt};
+use super::{
+    keystore::Keystore, topics::ConversationTopic, verify_serde_sig, PeerIdExt, MAX_CLOCK_DRIFT,
+};
 use crate::store::DidExt;
 
+use crate::store::conversation::crdt::{ConversationCrdt, Tag};
 use crate::store::conversation::message::MessageDocument;
 use crate::store::conversation::reference::MessageReferenceList;
+use crate::store::document::FileAttachmentDocument;
 use chrono::{DateTime, Utc};
 use core::hash::Hash;
 use either::Either;
@@ -17,7 +22,7 @@ use ipld_core::cid::Cid;
 use rust_ipfs::{Ipfs, Keypair};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeSet, HashMap, HashSet},
     time::Duration,
 };
 use uuid::Uuid;
@@ -25,11 +30,14 @@ use warp::{
     crypto::DID,
     error::Error,
     raygun::{
-        Conversation, ConversationType, GroupPermissions, Message, MessageOptions, MessagePage,
-        MessageReference, Messages, MessagesType,
+        Conversation, ConversationType, GroupPermissions, HybridTimestamp, Message, MessageOptions,
+        MessagePage, MessageReference, MessageType, Messages, MessagesType, PinnedMessage,
     },
 };
 
+/// Maximum number of references stored within a single block of the attachment index
+const ATTACHMENT_INDEX_BLOCK_SIZE: usize = 128;
+
 #[derive(Default, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ConversationVersion {
@@ -55,6 +63,8 @@ pub struct ConversationDocument {
     pub favorite: bool,
     #[serde(default)]
     pub archived: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_read: Option<DateTime<Utc>>,
     pub excluded: HashMap<DID, String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub restrict: Vec<DID>,
@@ -62,16 +72,64 @@ pub struct ConversationDocument {
     pub deleted: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub messages: Option<Cid>,
+    /// Index of files attached to messages, resolving to a list of blocks of `Vec<AttachmentReference>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub icon: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub banner: Option<Cid>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Index of pinned messages. This is `None` for conversations with messages pinned before the
+    /// index existed, until it is rebuilt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<Vec<PinnedMessageDocument>>,
+    /// Local hybrid logical clock for messages within the conversation
+    #[serde(default)]
+    pub clock: HybridTimestamp,
+    /// Replicated state backing `name`, `description`, `recipients` and `restrict`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crdt: Option<ConversationCrdt>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PinnedMessageDocument {
+    pub message_id: Uuid,
+    pub pinned_by: DID,
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AttachmentReference {
+    pub message_id: Uuid,
+    pub sender: DID,
+    pub date: DateTime<Utc>,
+    pub file: FileAttachmentDocument,
+}
+
+impl AttachmentReference {
+    fn from_message(message: &MessageDocument) -> Vec<Self> {
+        if message.message_type != MessageType::Attachment {
+            return vec![];
+        }
+
+        let sender = message.sender.to_did();
+
+        message
+            .attachments()
+            .map(|file| AttachmentReference {
+                message_id: message.id,
+                sender: sender.clone(),
+                date: message.date,
+                file: file.clone(),
+            })
+            .collect()
+    }
+}
+
 impl Hash for ConversationDocument {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.id.hash(state)
@@ -172,16 +230,21 @@ impl ConversationDocument {
             modified,
             favorite: false,
             archived: false,
+            last_read: None,
             conversation_type,
             permissions,
             excluded,
             messages,
+            attachments: None,
             signature,
             restrict,
             deleted: false,
             icon: None,
             banner: None,
             description: None,
+            pinned: Some(Vec::new()),
+            clock: HybridTimestamp::default(),
+            crdt: None,
         };
 
         if document.signature.is_some() {
@@ -324,6 +387,80 @@ impl ConversationDocument {
         Ok(())
     }
 
+    pub fn is_pinned(&self, message_id: Uuid) -> bool {
+        self.pinned
+            .iter()
+            .flatten()
+            .any(|pinned| pinned.message_id == message_id)
+    }
+
+    pub fn pinned_count(&self) -> usize {
+        self.pinned.as_ref().map(Vec::len).unwrap_or_default()
+    }
+
+    /// Insert the message into the pinned index, returning false if it already exist
+    pub fn pin_message(&mut self, message_id: Uuid, pinned_by: DID, date: DateTime<Utc>) -> bool {
+        if self.is_pinned(message_id) {
+            return false;
+        }
+
+        self.pinned
+            .get_or_insert_with(Vec::new)
+            .push(PinnedMessageDocument {
+                message_id,
+                pinned_by,
+                date,
+            });
+        true
+    }
+
+    /// Remove the message from the pinned index, returning false if it does not exist
+    pub fn unpin_message(&mut self, message_id: Uuid) -> bool {
+        let Some(pinned) = self.pinned.as_mut() else {
+            return false;
+        };
+        let len = pinned.len();
+        pinned.retain(|pinned| pinned.message_id != message_id);
+        pinned.len() != len
+    }
+
+    /// Build the pinned index from the messages within the conversation. Used for conversations
+    /// with messages pinned before the index existed. Who pinned those messages was not recorded,
+    /// so they are attributed to the sender of the message
+    pub async fn rebuild_pinned_index(&mut self, ipfs: &Ipfs) -> Result<(), Error> {
+        let pinned = self
+            .get_message_list(ipfs)
+            .await?
+            .into_iter()
+            .filter(MessageDocument::pinned)
+            .map(|message| PinnedMessageDocument {
+                message_id: message.id(),
+                pinned_by: message.sender(),
+                date: message.modified().unwrap_or(message.date()),
+            })
+            .collect();
+
+        self.pinned = Some(pinned);
+        Ok(())
+    }
+
+    pub fn pinned_messages(&self) -> Vec<PinnedMessage> {
+        self.pinned
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(position, pinned)| {
+                PinnedMessage::new(
+                    self.id,
+                    pinned.message_id,
+                    pinned.pinned_by.clone(),
+                    pinned.date,
+                    position,
+                )
+            })
+            .collect()
+    }
+
     pub async fn message_reference_list(&self, ipfs: &Ipfs) -> Result<MessageReferenceList, Error> {
         let refs = match self.messages {
             Some(cid) => {
@@ -343,6 +480,18 @@ impl ConversationDocument {
         Ok(list.contains(ipfs, message_id).await)
     }
 
+    /// Ids of messages known to the conversation, including those that were deleted
+    pub async fn message_ids(&self, ipfs: &Ipfs) -> Result<HashSet<Uuid>, Error> {
+        let list = self.message_reference_list(ipfs).await?;
+        Ok(HashSet::from_iter(list.ids(ipfs, true).await))
+    }
+
+    /// Ids of messages stored in the conversation, in the order of the reference list
+    pub async fn stored_message_ids(&self, ipfs: &Ipfs) -> Result<Vec<Uuid>, Error> {
+        let list = self.message_reference_list(ipfs).await?;
+        Ok(list.ids(ipfs, false).await)
+    }
+
     pub async fn set_message_reference_list(
         &mut self,
         ipfs: &Ipfs,
@@ -359,12 +508,290 @@ impl ConversationDocument {
         ipfs: &Ipfs,
         message_document: &MessageDocument,
     ) -> Result<Cid, Error> {
+        // The index is only maintained once it exists, or for a conversation without any messages yet.
+        // Otherwise it is built from the existing messages when first requested
+        let indexed = self.attachments.is_some() || self.messages.is_none();
+
         let mut list = self.message_reference_list(ipfs).await?;
         let cid = list.insert(ipfs, message_document).await?;
         self.set_message_reference_list(ipfs, list).await?;
+        self.observe_clock(message_document.clock());
+
+        let references = AttachmentReference::from_message(message_document);
+        if indexed && (self.attachments.is_none() || !references.is_empty()) {
+            self.insert_attachment_references(ipfs, references).await?;
+        }
+
         Ok(cid)
     }
 
+    async fn attachment_index_blocks(&self, ipfs: &Ipfs) -> Result<Vec<Cid>, Error> {
+        let blocks = match self.attachments {
+            Some(cid) => ipfs.get_dag(cid).local().deserialized().await?,
+            None => Vec::new(),
+        };
+
+        Ok(blocks)
+    }
+
+    async fn set_attachment_index_blocks(
+        &mut self,
+        ipfs: &Ipfs,
+        blocks: Vec<Cid>,
+    ) -> Result<(), Error> {
+        let cid = ipfs.put_dag(blocks).await?;
+        self.attachments.replace(cid);
+        Ok(())
+    }
+
+    /// Insert references into the attachment index, keeping the blocks ordered by date. A full block
+    /// is split in half, unless the reference is newer than everything indexed, in which case a new
+    /// block is started
+    async fn insert_attachment_references(
+        &mut self,
+        ipfs: &Ipfs,
+        mut references: Vec<AttachmentReference>,
+    ) -> Result<(), Error> {
+        let mut blocks = self.attachment_index_blocks(ipfs).await?;
+        references.sort_by_key(|reference| reference.date);
+
+        if blocks.is_empty() {
+            for chunk in references.chunks(ATTACHMENT_INDEX_BLOCK_SIZE) {
+                blocks.push(ipfs.put_dag(chunk.to_vec()).await?);
+            }
+            return self.set_attachment_index_blocks(ipfs, blocks).await;
+        }
+
+        for reference in references {
+            let last = blocks.len() - 1;
+
+            // References are usually newer than everything indexed, so the blocks are searched
+            // starting from the most recent
+            let mut position = last;
+            let mut block: Vec<AttachmentReference> = ipfs
+                .get_dag(blocks[position])
+                .local()
+                .deserialized()
+                .await?;
+            while position > 0
+                && block
+                    .first()
+                    .is_some_and(|first| first.date > reference.date)
+            {
+                position -= 1;
+                block = ipfs
+                    .get_dag(blocks[position])
+                    .local()
+                    .deserialized()
+                    .await?;
+            }
+
+            let index = block.partition_point(|existing| existing.date <= reference.date);
+
+            if position == last
+                && index == block.len()
+                && block.len() >= ATTACHMENT_INDEX_BLOCK_SIZE
+            {
+                blocks.push(ipfs.put_dag(vec![reference]).await?);
+                continue;
+            }
+
+            block.insert(index, reference);
+
+            if block.len() > ATTACHMENT_INDEX_BLOCK_SIZE {
+                let tail = block.split_off(block.len() / 2);
+                blocks.insert(position + 1, ipfs.put_dag(tail).await?);
+            }
+
+            blocks[position] = ipfs.put_dag(block).await?;
+        }
+
+        self.set_attachment_index_blocks(ipfs, blocks).await
+    }
+
+    /// References within the attachment index, starting from the most recent. Blocks are only
+    /// loaded as the stream is polled
+    pub async fn attachment_references(
+        &self,
+        ipfs: &Ipfs,
+    ) -> Result<BoxStream<'static, AttachmentReference>, Error> {
+        let blocks = self.attachment_index_blocks(ipfs).await?;
+        let ipfs = ipfs.clone();
+
+        let stream = stream::iter(blocks.into_iter().rev())
+            .then(move |cid| {
+                let ipfs = ipfs.clone();
+                async move {
+                    ipfs.get_dag(cid)
+                        .local()
+                        .deserialized::<Vec<AttachmentReference>>()
+                        .await
+                        .unwrap_or_default()
+                }
+            })
+            .flat_map(|block| stream::iter(block.into_iter().rev()));
+
+        Ok(stream.boxed())
+    }
+
+    /// Build the attachment index from the messages within the conversation. Used for conversations
+    /// with messages inserted before the index existed
+    pub async fn rebuild_attachment_index(&mut self, ipfs: &Ipfs) -> Result<(), Error> {
+        let references = self
+            .get_message_list(ipfs)
+            .await?
+            .iter()
+            .flat_map(AttachmentReference::from_message)
+            .collect::<Vec<_>>();
+
+        self.attachments.take();
+        self.insert_attachment_references(ipfs, references).await
+    }
+
+    /// Advance the clock for a message that is about to be sent
+    pub fn tick_clock(&mut self) -> HybridTimestamp {
+        let now = HybridTimestamp::from(Utc::now());
+        self.clock = match now.physical() > self.clock.physical() {
+            true => now,
+            false => HybridTimestamp::new(
+                self.clock.physical(),
+                self.clock.logical().saturating_add(1),
+            ),
+        };
+        self.clock
+    }
+
+    /// Merge the clock of a stored message so that any message sent afterward is ordered after it
+    fn observe_clock(&mut self, clock: HybridTimestamp) {
+        let now = HybridTimestamp::from(Utc::now());
+        if clock.physical().saturating_sub(now.physical()) > MAX_CLOCK_DRIFT {
+            tracing::warn!(conversation_id = %self.id, physical = clock.physical(), "message clock is too far ahead. ignoring");
+            return;
+        }
+        self.clock = self.clock.max(clock);
+    }
+
+    /// Replicated state of the document, constructing it from the fields if it predates the crdt
+    pub fn crdt(&self) -> ConversationCrdt {
+        self.crdt.clone().unwrap_or_else(|| {
+            ConversationCrdt::from_fields(
+                self.id,
+                self.name.clone(),
+                self.description.clone(),
+                &self.recipients,
+                &self.restrict,
+            )
+        })
+    }
+
+    fn crdt_mut(&mut self) -> &mut ConversationCrdt {
+        if self.crdt.is_none() {
+            self.crdt = Some(self.crdt());
+        }
+        self.crdt.as_mut().expect("crdt is set")
+    }
+
+    /// Update the fields from the replicated state
+    fn materialize(&mut self) {
+        let crdt = self.crdt();
+        self.name = crdt.name.value().clone();
+        self.description = crdt.description.value().clone();
+        self.recipients = crdt.recipients.values();
+        self.restrict = crdt.restrict.values();
+    }
+
+    pub fn set_name(&mut self, name: Option<String>, author: &DID) {
+        let clock = self.tick_clock();
+        self.crdt_mut().name.set(name, clock, author);
+        self.materialize();
+    }
+
+    pub fn set_description(&mut self, description: Option<String>, author: &DID) {
+        let clock = self.tick_clock();
+        self.crdt_mut().description.set(description, clock, author);
+        self.materialize();
+    }
+
+    pub fn add_recipient(&mut self, did: &DID) {
+        let tag = Tag::new(self.tick_clock());
+        self.crdt_mut().recipients.insert(did.clone(), tag);
+        self.materialize();
+    }
+
+    pub fn remove_recipient(&mut self, did: &DID) {
+        self.crdt_mut().recipients.remove(did);
+        self.materialize();
+    }
+
+    pub fn add_restricted(&mut self, did: &DID) {
+        let tag = Tag::new(self.tick_clock());
+        self.crdt_mut().restrict.insert(did.clone(), tag);
+        self.materialize();
+    }
+
+    pub fn remove_restricted(&mut self, did: &DID) {
+        self.crdt_mut().restrict.remove(did);
+        self.materialize();
+    }
+
+    /// Latest physical time accepted from another replica. Anything beyond it would win every
+    /// future merge, so it is rejected
+    fn max_merge_physical() -> u64 {
+        HybridTimestamp::from(Utc::now())
+            .physical()
+            .saturating_add(MAX_CLOCK_DRIFT)
+    }
+
+    /// Merge the name from another replica
+    pub fn merge_name(&mut self, other: &ConversationDocument) {
+        let other = other.crdt();
+        if other.name.is_after(Self::max_merge_physical()) {
+            tracing::warn!(conversation_id = %self.id, "name clock is too far ahead. ignoring");
+            return;
+        }
+        self.observe_clock(other.name.clock);
+        if self.crdt_mut().name.merge(&other.name) {
+            self.materialize();
+        }
+    }
+
+    /// Merge the description from another replica
+    pub fn merge_description(&mut self, other: &ConversationDocument) {
+        let other = other.crdt();
+        if other.description.is_after(Self::max_merge_physical()) {
+            tracing::warn!(conversation_id = %self.id, "description clock is too far ahead. ignoring");
+            return;
+        }
+        self.observe_clock(other.description.clock);
+        if self.crdt_mut().description.merge(&other.description) {
+            self.materialize();
+        }
+    }
+
+    /// Merge the recipients from another replica
+    pub fn merge_recipients(&mut self, other: &ConversationDocument) {
+        let mut other = other.crdt();
+        other.recipients.discard_after(Self::max_merge_physical());
+        self.observe_clock(other.recipients.clock());
+        self.crdt_mut().recipients.merge(&other.recipients);
+        self.materialize();
+    }
+
+    /// Merge the restricted list from another replica. As the list is covered by the signature
+    /// of the creator, the signature of the other replica is used if the lists are the same
+    pub fn merge_restrict(&mut self, other: &ConversationDocument) {
+        let mut other_state = other.crdt();
+        other_state
+            .restrict
+            .discard_after(Self::max_merge_physical());
+        self.observe_clock(other_state.restrict.clock());
+        self.crdt_mut().restrict.merge(&other_state.restrict);
+        self.materialize();
+        if self.restrict == other.restrict {
+            self.signature.clone_from(&other.signature);
+        }
+    }
+
     pub async fn update_message_document(
         &mut self,
         ipfs: &Ipfs,
@@ -639,6 +1066,34 @@ impl ConversationDocument {
         let mut list = self.message_reference_list(ipfs).await?;
         list.remove(ipfs, message_id).await?;
         self.set_message_reference_list(ipfs, list).await?;
+        self.unpin_message(message_id);
+
+        if self.attachments.is_some() {
+            let mut blocks = self.attachment_index_blocks(ipfs).await?;
+            let mut changed = false;
+
+            for cid in std::mem::take(&mut blocks) {
+                let mut block: Vec<AttachmentReference> =
+                    ipfs.get_dag(cid).local().deserialized().await?;
+                let len = block.len();
+                block.retain(|reference| reference.message_id != message_id);
+
+                if block.len() == len {
+                    blocks.push(cid);
+                    continue;
+                }
+
+                changed = true;
+                if !block.is_empty() {
+                    blocks.push(ipfs.put_dag(block).await?);
+                }
+            }
+
+            if changed {
+                self.set_attachment_index_blocks(ipfs, blocks).await?;
+            }
+        }
+
         Ok(())
     }
 }
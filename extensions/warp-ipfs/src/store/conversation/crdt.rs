@@ -0,0 +1,404 @@
+use std::collections::BTreeSet;
+use std::hash::Hash;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use warp::crypto::DID;
+use warp::raygun::HybridTimestamp;
+
+/// Last-writer-wins register ordered by a [`HybridTimestamp`], with the author as a tiebreaker
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LwwRegister<T> {
+    pub value: T,
+    pub clock: HybridTimestamp,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<DID>,
+}
+
+impl<T> LwwRegister<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            value,
+            clock: HybridTimestamp::default(),
+            author: None,
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn set(&mut self, value: T, clock: HybridTimestamp, author: &DID) {
+        self.value = value;
+        self.clock = clock;
+        self.author = Some(author.clone());
+    }
+
+    /// Check if the register was written at a time beyond the physical time given
+    pub fn is_after(&self, physical: u64) -> bool {
+        self.clock.physical() > physical
+    }
+
+    fn key(&self) -> (HybridTimestamp, String) {
+        (
+            self.clock,
+            self.author
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+impl<T: Clone> LwwRegister<T> {
+    /// Merge another replica of the register, returning true if the value was replaced
+    pub fn merge(&mut self, other: &Self) -> bool {
+        if other.key() <= self.key() {
+            return false;
+        }
+        *self = other.clone();
+        true
+    }
+}
+
+/// Unique tag identifying a single addition to an [`OrSet`]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag {
+    pub clock: HybridTimestamp,
+    pub id: Uuid,
+}
+
+impl Tag {
+    pub fn new(clock: HybridTimestamp) -> Self {
+        Self {
+            clock,
+            id: Uuid::new_v4(),
+        }
+    }
+}
+
+/// Observed-remove set. Removing an element only removes the additions that were observed,
+/// so a concurrent addition will win over a removal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct OrSet<T: Hash + Eq> {
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    adds: IndexMap<T, BTreeSet<Tag>>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    removed: BTreeSet<Tag>,
+}
+
+impl<T: Hash + Eq> Default for OrSet<T> {
+    fn default() -> Self {
+        Self {
+            adds: IndexMap::new(),
+            removed: BTreeSet::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq + Clone> OrSet<T> {
+    pub fn insert(&mut self, value: T, tag: Tag) {
+        if self.removed.contains(&tag) {
+            return;
+        }
+        self.adds.entry(value).or_default().insert(tag);
+    }
+
+    /// Remove every observed addition of the value, returning true if it was present
+    pub fn remove(&mut self, value: &T) -> bool {
+        let Some(tags) = self.adds.shift_remove(value) else {
+            return false;
+        };
+        let present = !tags.is_empty();
+        self.removed.extend(tags);
+        present
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.adds.get(value).is_some_and(|tags| !tags.is_empty())
+    }
+
+    /// Elements within the set, ordered by when they were first added
+    pub fn values(&self) -> Vec<T> {
+        let mut list = self
+            .adds
+            .iter()
+            .filter_map(|(value, tags)| tags.first().map(|tag| (*tag, value)))
+            .collect::<Vec<_>>();
+        list.sort_by_key(|(tag, _)| *tag);
+        list.into_iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    /// Latest clock observed from any addition or removal
+    pub fn clock(&self) -> HybridTimestamp {
+        self.adds
+            .values()
+            .flatten()
+            .chain(self.removed.iter())
+            .map(|tag| tag.clock)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Discard any addition or removal with a clock beyond the physical time given
+    pub fn discard_after(&mut self, physical: u64) {
+        self.removed.retain(|tag| tag.clock.physical() <= physical);
+        self.adds.retain(|_, tags| {
+            tags.retain(|tag| tag.clock.physical() <= physical);
+            !tags.is_empty()
+        });
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.removed.extend(other.removed.iter().copied());
+
+        for (value, tags) in &other.adds {
+            self.adds
+                .entry(value.clone())
+                .or_default()
+                .extend(tags.iter().copied());
+        }
+
+        let removed = &self.removed;
+        self.adds.retain(|_, tags| {
+            tags.retain(|tag| !removed.contains(tag));
+            !tags.is_empty()
+        });
+    }
+}
+
+/// Replicated state of the fields of a conversation that can be edited concurrently
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationCrdt {
+    pub name: LwwRegister<Option<String>>,
+    pub description: LwwRegister<Option<String>>,
+    pub recipients: OrSet<DID>,
+    pub restrict: OrSet<DID>,
+}
+
+impl ConversationCrdt {
+    /// Construct the state from a document that predates the crdt. Tags are derived from the conversation
+    /// so that every replica produces the same state and the existing order is kept
+    pub fn from_fields(
+        conversation_id: Uuid,
+        name: Option<String>,
+        description: Option<String>,
+        recipients: &[DID],
+        restrict: &[DID],
+    ) -> Self {
+        let legacy_set = |list: &[DID]| {
+            let mut set = OrSet::default();
+            for (index, did) in list.iter().enumerate() {
+                let tag = Tag {
+                    clock: HybridTimestamp::new(0, index as u32),
+                    id: Uuid::new_v5(&conversation_id, did.to_string().as_bytes()),
+                };
+                set.insert(did.clone(), tag);
+            }
+            set
+        };
+
+        Self {
+            name: LwwRegister::new(name),
+            description: LwwRegister::new(description),
+            recipients: legacy_set(recipients),
+            restrict: legacy_set(restrict),
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.name.merge(&other.name);
+        self.description.merge(&other.description);
+        self.recipients.merge(&other.recipients);
+        self.restrict.merge(&other.restrict);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConversationCrdt, Tag};
+    use uuid::Uuid;
+    use warp::crypto::{
+        rand::{self, rngs::StdRng, seq::SliceRandom, Rng, SeedableRng},
+        DID,
+    };
+    use warp::raygun::HybridTimestamp;
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        SetName(Option<String>),
+        SetDescription(Option<String>),
+        AddRecipient(usize),
+        RemoveRecipient(usize),
+        AddRestricted(usize),
+        RemoveRestricted(usize),
+    }
+
+    fn apply(
+        state: &mut ConversationCrdt,
+        op: &Op,
+        clock: HybridTimestamp,
+        author: &DID,
+        dids: &[DID],
+    ) {
+        match op {
+            Op::SetName(name) => state.name.set(name.clone(), clock, author),
+            Op::SetDescription(desc) => state.description.set(desc.clone(), clock, author),
+            Op::AddRecipient(i) => state.recipients.insert(dids[*i].clone(), Tag::new(clock)),
+            Op::RemoveRecipient(i) => {
+                state.recipients.remove(&dids[*i]);
+            }
+            Op::AddRestricted(i) => state.restrict.insert(dids[*i].clone(), Tag::new(clock)),
+            Op::RemoveRestricted(i) => {
+                state.restrict.remove(&dids[*i]);
+            }
+        }
+    }
+
+    fn random_op(rng: &mut impl Rng, members: usize) -> Op {
+        let index = rng.gen_range(0..members);
+        match rng.gen_range(0..6) {
+            0 => Op::SetName(
+                rng.gen_bool(0.8)
+                    .then(|| format!("name-{}", rng.gen::<u16>())),
+            ),
+            1 => Op::SetDescription(
+                rng.gen_bool(0.8)
+                    .then(|| format!("desc-{}", rng.gen::<u16>())),
+            ),
+            2 => Op::AddRecipient(index),
+            3 => Op::RemoveRecipient(index),
+            4 => Op::AddRestricted(index),
+            _ => Op::RemoveRestricted(index),
+        }
+    }
+
+    #[test]
+    fn concurrent_register_writes_converge() {
+        let a = DID::default();
+        let b = DID::default();
+        let mut replica_a = ConversationCrdt::default();
+        let mut replica_b = ConversationCrdt::default();
+
+        let clock = HybridTimestamp::new(10, 0);
+        replica_a.name.set(Some("a".into()), clock, &a);
+        replica_b.name.set(Some("b".into()), clock, &b);
+
+        let snapshot_a = replica_a.clone();
+        replica_a.merge(&replica_b);
+        replica_b.merge(&snapshot_a);
+
+        assert_eq!(replica_a, replica_b);
+    }
+
+    #[test]
+    fn concurrent_add_wins_over_remove() {
+        let did = DID::default();
+        let mut replica_a = ConversationCrdt::default();
+        replica_a
+            .recipients
+            .insert(did.clone(), Tag::new(HybridTimestamp::new(1, 0)));
+        let mut replica_b = replica_a.clone();
+
+        replica_a.recipients.remove(&did);
+        replica_b
+            .recipients
+            .insert(did.clone(), Tag::new(HybridTimestamp::new(2, 0)));
+
+        let snapshot_a = replica_a.clone();
+        replica_a.merge(&replica_b);
+        replica_b.merge(&snapshot_a);
+
+        assert!(replica_a.recipients.contains(&did));
+        assert_eq!(replica_a.recipients.values(), replica_b.recipients.values());
+    }
+
+    #[test]
+    fn tags_beyond_limit_are_discarded() {
+        let a = DID::default();
+        let b = DID::default();
+        let mut replica = ConversationCrdt::default();
+        replica
+            .recipients
+            .insert(a.clone(), Tag::new(HybridTimestamp::new(10, 0)));
+        replica
+            .recipients
+            .insert(b.clone(), Tag::new(HybridTimestamp::new(20, 0)));
+
+        replica.recipients.discard_after(15);
+
+        assert!(replica.recipients.contains(&a));
+        assert!(!replica.recipients.contains(&b));
+    }
+
+    #[test]
+    fn legacy_state_is_deterministic() {
+        let id = Uuid::new_v4();
+        let dids = (0..4).map(|_| DID::default()).collect::<Vec<_>>();
+        let a = ConversationCrdt::from_fields(id, Some("name".into()), None, &dids, &[]);
+        let b = ConversationCrdt::from_fields(id, Some("name".into()), None, &dids, &[]);
+        assert_eq!(a, b);
+        assert_eq!(a.recipients.values(), dids);
+    }
+
+    #[test]
+    fn random_interleavings_converge() {
+        // A different seed can be set to explore other interleavings
+        let seed = std::env::var("CRDT_TEST_SEED")
+            .ok()
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or(0x5eed_c0de);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let dids = (0..5).map(|_| DID::default()).collect::<Vec<_>>();
+        let authors = (0..3).map(|_| DID::default()).collect::<Vec<_>>();
+
+        for _ in 0..50 {
+            let mut replicas = vec![ConversationCrdt::default(); authors.len()];
+            let mut physical = 0;
+
+            for step in 0..40 {
+                // clocks only move forward, as the local clock observes every merged state
+                physical += rng.gen_range(0..2);
+                let replica = rng.gen_range(0..replicas.len());
+                let op = random_op(&mut rng, dids.len());
+                let clock = HybridTimestamp::new(physical, step);
+                apply(&mut replicas[replica], &op, clock, &authors[replica], &dids);
+
+                // deliver the state of a random replica to another
+                if rng.gen_bool(0.3) {
+                    let from = rng.gen_range(0..replicas.len());
+                    let to = rng.gen_range(0..replicas.len());
+                    let state = replicas[from].clone();
+                    replicas[to].merge(&state);
+                }
+            }
+
+            // deliver every state to every replica in a random order
+            let states = replicas.clone();
+            for replica in replicas.iter_mut() {
+                let mut order = states.iter().collect::<Vec<_>>();
+                order.shuffle(&mut rng);
+                for state in order {
+                    replica.merge(state);
+                }
+            }
+
+            let first = &replicas[0];
+            for replica in &replicas[1..] {
+                assert_eq!(first.name, replica.name, "seed: {seed}");
+                assert_eq!(first.description, replica.description, "seed: {seed}");
+                assert_eq!(
+                    first.recipients.values(),
+                    replica.recipients.values(),
+                    "seed: {seed}"
+                );
+                assert_eq!(
+                    first.restrict.values(),
+                    replica.restrict.values(),
+                    "seed: {seed}"
+                );
+            }
+        }
+    }
+}
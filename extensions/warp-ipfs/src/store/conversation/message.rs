@@ -1,9 +1,12 @@
 use crate::store::document::files::FileDocument;
+use crate::store::document::identity::IdentityDocument;
 use crate::store::document::FileAttachmentDocument;
 use crate::store::keystore::Keystore;
 use crate::store::{
     ecdh_decrypt, ecdh_encrypt, ecdh_encrypt_with_nonce, extract_data_slice, DidExt, PeerIdExt,
-    MAX_ATTACHMENT, MAX_MESSAGE_SIZE, MAX_REACTIONS, MIN_MESSAGE_SIZE,
+    MAX_ATTACHMENT, MAX_CLOCK_DRIFT, MAX_MESSAGE_SIZE, MAX_METADATA_ENTRIES,
+    MAX_METADATA_KEY_LENGTH, MAX_METADATA_VALUE_LENGTH, MAX_POLL_OPTIONS, MAX_POLL_OPTION_LENGTH,
+    MAX_REACTIONS, MIN_MESSAGE_SIZE, VOICE_NOTE_WAVEFORM_LENGTH,
 };
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
@@ -19,13 +22,22 @@ use warp::crypto::cipher::Cipher;
 use warp::crypto::hash::sha256_iter;
 use warp::crypto::{DIDKey, Ed25519KeyPair, KeyMaterial, DID};
 use warp::error::Error;
-use warp::raygun::{Message, MessageReference, MessageType};
+use warp::raygun::{
+    HybridTimestamp, Message, MessageReference, MessageType, Poll, PollResults, SignatureState,
+    VoiceNote,
+};
 
 #[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageVersion {
     #[default]
     V0,
+    /// Includes a signed hybrid logical clock used for ordering.
+    ///
+    /// Note: This changes the wire format. Peers that predate this version fail to deserialize
+    ///       these messages, so they will not receive messages from updated peers until they
+    ///       update as well
+    V1,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +48,8 @@ pub struct MessageDocument {
     pub version: MessageVersion,
     pub sender: DIDEd25519Reference,
     pub date: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<HybridTimestamp>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     pub reactions: IndexMap<String, IndexSet<DID>>,
     #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
@@ -48,10 +62,95 @@ pub struct MessageDocument {
     pub replied: Option<Uuid>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub message: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "IndexSet::is_empty")]
+    pub mentions: IndexSet<DID>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub poll_votes: IndexMap<DID, PollVoteDocument>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_closed: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice_note: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_card: Option<Bytes>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub signature: Option<MessageSignature>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PollVoteDocument {
+    pub voter: DID,
+    pub options: IndexSet<usize>,
+    pub date: DateTime<Utc>,
+    pub signature: MessageSignature,
+}
+
+impl PollVoteDocument {
+    pub fn new(
+        keypair: &Keypair,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        options: IndexSet<usize>,
+    ) -> Result<Self, Error> {
+        let voter = keypair.to_did()?;
+        let date = Utc::now();
+        let hash = Self::hash(conversation_id, message_id, &voter, &options, date);
+        let signature = keypair.sign(&hash).expect("not RSA");
+        Ok(Self {
+            voter,
+            options,
+            date,
+            signature: MessageSignature::try_from(signature)?,
+        })
+    }
+
+    pub fn verify(&self, conversation_id: Uuid, message_id: Uuid) -> Result<(), Error> {
+        let voter_pk = self
+            .voter
+            .to_public_key()
+            .map_err(|_| Error::PublicKeyInvalid)?;
+        let hash = Self::hash(
+            conversation_id,
+            message_id,
+            &self.voter,
+            &self.options,
+            self.date,
+        );
+        if !voter_pk.verify(&hash, self.signature.as_ref()) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    fn hash(
+        conversation_id: Uuid,
+        message_id: Uuid,
+        voter: &DID,
+        options: &IndexSet<usize>,
+        date: DateTime<Utc>,
+    ) -> Vec<u8> {
+        sha256_iter(
+            [
+                Some(conversation_id.as_bytes().to_vec()),
+                Some(message_id.as_bytes().to_vec()),
+                Some(voter.public_key_bytes()),
+                Some(
+                    options
+                        .iter()
+                        .flat_map(|option| (*option as u64).to_le_bytes())
+                        .collect(),
+                ),
+                Some(date.to_string().into_bytes()),
+            ]
+            .into_iter(),
+            None,
+        )
+    }
+}
+
 impl MessageDocument {
     pub fn id(&self) -> Uuid {
         self.id
@@ -77,6 +176,15 @@ impl MessageDocument {
         self.date
     }
 
+    /// Logical timestamp used for ordering. Documents prior to [`MessageVersion::V1`]
+    /// fall back to the date of the message
+    pub fn clock(&self) -> HybridTimestamp {
+        match (self.version, self.clock) {
+            (MessageVersion::V1, Some(clock)) => clock,
+            _ => HybridTimestamp::from(self.date),
+        }
+    }
+
     pub fn reactions(&self) -> &IndexMap<String, IndexSet<DID>> {
         &self.reactions
     }
@@ -92,6 +200,10 @@ impl MessageDocument {
     pub fn replied(&self) -> Option<Uuid> {
         self.replied
     }
+
+    pub fn mentions(&self) -> &IndexSet<DID> {
+        &self.mentions
+    }
 }
 
 impl PartialEq for MessageDocument {
@@ -118,12 +230,20 @@ impl MessageDocument {
             version: MessageVersion::V0,
             sender: DIDEd25519Reference([0; 32]),
             date: Utc::now(),
+            clock: None,
             reactions: IndexMap::new(),
             attachments: IndexSet::new(),
             modified: None,
             pinned: false,
             replied: None,
             message: None,
+            mentions: IndexSet::new(),
+            metadata: None,
+            poll: None,
+            poll_votes: IndexMap::new(),
+            poll_closed: None,
+            voice_note: None,
+            contact_card: None,
             signature: None,
         }
     }
@@ -141,6 +261,7 @@ impl From<&MessageDocument> for MessageReference {
         reference.set_id(document.id);
         reference.set_conversation_id(document.conversation_id);
         reference.set_date(document.date);
+        reference.set_clock(document.clock());
         if let Some(modified) = document.modified {
             reference.set_modified(modified);
         }
@@ -160,7 +281,9 @@ impl PartialOrd for MessageDocument {
 
 impl Ord for MessageDocument {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.date.cmp(&other.date)
+        self.clock()
+            .cmp(&other.clock())
+            .then_with(|| self.id.cmp(&other.id))
     }
 }
 
@@ -219,6 +342,12 @@ impl<'a> MessageDocumentBuilder<'a> {
         self
     }
 
+    pub fn set_clock(mut self, clock: HybridTimestamp) -> Self {
+        self.message_document.version = MessageVersion::V1;
+        self.message_document.clock = Some(clock);
+        self
+    }
+
     pub fn add_attachment(mut self, attachment: impl Into<FileDocument>) -> Result<Self, Error> {
         let amount = self.message_document.attachments.len();
         if amount > MAX_ATTACHMENT {
@@ -269,6 +398,95 @@ impl<'a> MessageDocumentBuilder<'a> {
         Ok(self)
     }
 
+    pub fn set_mentions(mut self, mentions: impl IntoIterator<Item = DID>) -> Self {
+        self.message_document.mentions = IndexSet::from_iter(mentions);
+        self
+    }
+
+    pub fn set_metadata(mut self, metadata: IndexMap<String, String>) -> Result<Self, Error> {
+        if metadata.is_empty() {
+            self.message_document.metadata = None;
+            return Ok(self);
+        }
+
+        validate_metadata(&metadata)?;
+
+        let sender = self.message_document.sender.to_did();
+
+        let bytes = serde_json::to_vec(&metadata)?;
+
+        let data = match self.keystore {
+            Either::Right(keystore) => {
+                let key = keystore.get_latest(self.keypair, &sender)?;
+                Cipher::direct_encrypt(&bytes, &key)?.into()
+            }
+            Either::Left(key) => ecdh_encrypt(self.keypair, Some(key), &bytes)?.into(),
+        };
+
+        self.message_document.metadata = Some(data);
+        Ok(self)
+    }
+
+    pub fn set_poll(mut self, poll: Poll) -> Result<Self, Error> {
+        validate_poll(&poll)?;
+
+        let sender = self.message_document.sender.to_did();
+
+        let bytes = serde_json::to_vec(&poll)?;
+
+        let data = match self.keystore {
+            Either::Right(keystore) => {
+                let key = keystore.get_latest(self.keypair, &sender)?;
+                Cipher::direct_encrypt(&bytes, &key)?.into()
+            }
+            Either::Left(key) => ecdh_encrypt(self.keypair, Some(key), &bytes)?.into(),
+        };
+
+        self.message_document.message_type = MessageType::Poll;
+        self.message_document.poll = Some(data);
+        Ok(self)
+    }
+
+    pub fn set_contact_card(mut self, document: &IdentityDocument) -> Result<Self, Error> {
+        document.verify()?;
+
+        let sender = self.message_document.sender.to_did();
+
+        let bytes = serde_json::to_vec(document)?;
+
+        let data = match self.keystore {
+            Either::Right(keystore) => {
+                let key = keystore.get_latest(self.keypair, &sender)?;
+                Cipher::direct_encrypt(&bytes, &key)?.into()
+            }
+            Either::Left(key) => ecdh_encrypt(self.keypair, Some(key), &bytes)?.into(),
+        };
+
+        self.message_document.message_type = MessageType::ContactCard;
+        self.message_document.contact_card = Some(data);
+        Ok(self)
+    }
+
+    pub fn set_voice_note(mut self, voice_note: VoiceNote) -> Result<Self, Error> {
+        validate_voice_note(&voice_note)?;
+
+        let sender = self.message_document.sender.to_did();
+
+        let bytes = serde_json::to_vec(&voice_note)?;
+
+        let data = match self.keystore {
+            Either::Right(keystore) => {
+                let key = keystore.get_latest(self.keypair, &sender)?;
+                Cipher::direct_encrypt(&bytes, &key)?.into()
+            }
+            Either::Left(key) => ecdh_encrypt(self.keypair, Some(key), &bytes)?.into(),
+        };
+
+        self.message_document.message_type = MessageType::VoiceNote;
+        self.message_document.voice_note = Some(data);
+        Ok(self)
+    }
+
     pub fn build(self) -> Result<MessageDocument, Error> {
         self.message_document.sign(self.keypair)
     }
@@ -338,6 +556,52 @@ impl MessageDocument {
         Ok(())
     }
 
+    /// Apply a vote to the poll, replacing any previous vote by the same voter.
+    /// A vote with no options will remove the previous vote.
+    /// Whether the poll has closed is checked against the time the vote was received, as the date
+    /// of the vote is set by the voter
+    pub fn apply_poll_vote(
+        &mut self,
+        poll: &Poll,
+        vote: PollVoteDocument,
+        received: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        vote.verify(self.conversation_id, self.id)?;
+
+        if self.poll_closed.is_some() || poll.close_date().is_some_and(|date| received > date) {
+            return Err(Error::PollClosed);
+        }
+
+        validate_poll_vote(poll, &vote)?;
+
+        if self
+            .poll_votes
+            .get(&vote.voter)
+            .is_some_and(|current| current.date >= vote.date)
+        {
+            return Err(Error::InvalidPollVote);
+        }
+
+        if vote.options.is_empty() {
+            self.poll_votes.shift_remove(&vote.voter);
+        } else {
+            self.poll_votes.insert(vote.voter.clone(), vote);
+        }
+
+        Ok(())
+    }
+
+    pub fn close_poll(&mut self, date: DateTime<Utc>) -> Result<(), Error> {
+        if self.message_type != MessageType::Poll {
+            return Err(Error::MessageNotPoll);
+        }
+        if self.poll_closed.is_some() {
+            return Err(Error::PollClosed);
+        }
+        self.poll_closed = Some(date);
+        Ok(())
+    }
+
     pub fn remove_reaction(&mut self, emoji: impl Into<String>, reactor: DID) -> Result<(), Error> {
         let emoji = emoji.into();
         if !self.reactions.contains_key(&emoji) {
@@ -476,6 +740,36 @@ impl MessageDocument {
 
 impl MessageDocument {
     pub fn verify(&self) -> Result<(), Error> {
+        self.verify_signature()?;
+        self.verify_clock(Utc::now())?;
+        self.verify_limits()
+    }
+
+    /// Rejects a clock further ahead than the allowed drift, as the message would otherwise be
+    /// ordered after every message sent until that time
+    fn verify_clock(&self, now: DateTime<Utc>) -> Result<(), Error> {
+        if self.version == MessageVersion::V0 {
+            return Ok(());
+        }
+
+        let now = HybridTimestamp::from(now);
+        if self.clock().physical().saturating_sub(now.physical()) > MAX_CLOCK_DRIFT {
+            return Err(Error::InvalidMessage);
+        }
+
+        Ok(())
+    }
+
+    /// Provides the state of the signature without validating the rest of the document
+    pub fn signature_state(&self) -> SignatureState {
+        match self.verify_signature() {
+            Ok(_) => SignatureState::Verified,
+            Err(Error::InvalidSignature) if self.signature.is_none() => SignatureState::Unsigned,
+            Err(_) => SignatureState::Invalid,
+        }
+    }
+
+    fn verify_signature(&self) -> Result<(), Error> {
         let Some(signature) = self.signature else {
             return Err(Error::InvalidSignature);
         };
@@ -496,27 +790,52 @@ impl MessageDocument {
         );
         let attachments_hash = (!attachments_hash.is_empty()).then_some(attachments_hash);
 
-        let hash = match self.version {
-            MessageVersion::V0 => sha256_iter(
-                [
-                    Some(self.conversation_id.as_bytes().to_vec()),
-                    Some(self.id.as_bytes().to_vec()),
-                    Some(sender.public_key_bytes()),
-                    Some(self.date.to_string().into_bytes()),
-                    self.modified.map(|time| time.to_string().into_bytes()),
-                    self.replied.map(|id| id.as_bytes().to_vec()),
-                    attachments_hash,
-                    self.message.as_ref().map(|m| m.to_vec()),
-                ]
-                .into_iter(),
+        let mentions_hash = (!self.mentions.is_empty()).then(|| {
+            sha256_iter(
+                self.mentions
+                    .iter()
+                    .map(|did| did.public_key_bytes())
+                    .map(Option::Some),
                 None,
-            ),
+            )
+        });
+
+        let clock = match self.version {
+            MessageVersion::V0 => None,
+            MessageVersion::V1 => Some(clock_bytes(
+                self.clock.as_ref().ok_or(Error::InvalidMessage)?,
+            )),
         };
 
+        let hash = sha256_iter(
+            [
+                Some(self.conversation_id.as_bytes().to_vec()),
+                Some(self.id.as_bytes().to_vec()),
+                Some(sender.public_key_bytes()),
+                Some(self.date.to_string().into_bytes()),
+                self.modified.map(|time| time.to_string().into_bytes()),
+                self.replied.map(|id| id.as_bytes().to_vec()),
+                attachments_hash,
+                self.message.as_ref().map(|m| m.to_vec()),
+                mentions_hash,
+                self.metadata.as_ref().map(|m| m.to_vec()),
+                self.poll.as_ref().map(|p| p.to_vec()),
+                self.voice_note.as_ref().map(|v| v.to_vec()),
+                self.contact_card.as_ref().map(|c| c.to_vec()),
+                clock,
+            ]
+            .into_iter(),
+            None,
+        );
+
         if !sender_pk.verify(&hash, signature.as_ref()) {
             return Err(Error::InvalidMessage);
         }
 
+        Ok(())
+    }
+
+    fn verify_limits(&self) -> Result<(), Error> {
         if self.reactions.len() > MAX_REACTIONS {
             return Err(Error::InvalidLength {
                 context: "reactions".into(),
@@ -535,6 +854,19 @@ impl MessageDocument {
             });
         }
 
+        if self.message_type != MessageType::Poll
+            && (!self.poll_votes.is_empty() || self.poll_closed.is_some())
+        {
+            return Err(Error::MessageNotPoll);
+        }
+
+        for (voter, vote) in &self.poll_votes {
+            if *voter != vote.voter || vote.options.is_empty() {
+                return Err(Error::InvalidPollVote);
+            }
+            vote.verify(self.conversation_id, self.id)?;
+        }
+
         Ok(())
     }
 
@@ -588,6 +920,120 @@ impl MessageDocument {
         Ok(lines)
     }
 
+    pub fn metadata(
+        &self,
+        keypair: &Keypair,
+        keystore: Either<&DID, &Keystore>,
+    ) -> Result<IndexMap<String, String>, Error> {
+        let Some(metadata_cipher) = self.metadata.as_ref() else {
+            return Ok(IndexMap::new());
+        };
+
+        let data = match keystore {
+            Either::Left(exchange) => ecdh_decrypt(keypair, Some(exchange), metadata_cipher)?,
+            Either::Right(keystore) => {
+                keystore.try_decrypt(keypair, &self.sender(), metadata_cipher)?
+            }
+        };
+
+        let metadata: IndexMap<String, String> = serde_json::from_slice(&data)?;
+
+        validate_metadata(&metadata)?;
+
+        Ok(metadata)
+    }
+
+    pub fn poll(
+        &self,
+        keypair: &Keypair,
+        keystore: Either<&DID, &Keystore>,
+    ) -> Result<Poll, Error> {
+        let poll_cipher = self.poll.as_ref().ok_or(Error::MessageNotPoll)?;
+
+        let data = match keystore {
+            Either::Left(exchange) => ecdh_decrypt(keypair, Some(exchange), poll_cipher)?,
+            Either::Right(keystore) => {
+                keystore.try_decrypt(keypair, &self.sender(), poll_cipher)?
+            }
+        };
+
+        let poll: Poll = serde_json::from_slice(&data)?;
+
+        validate_poll(&poll)?;
+
+        Ok(poll)
+    }
+
+    pub fn voice_note(
+        &self,
+        keypair: &Keypair,
+        keystore: Either<&DID, &Keystore>,
+    ) -> Result<Option<VoiceNote>, Error> {
+        let Some(voice_note_cipher) = self.voice_note.as_ref() else {
+            return Ok(None);
+        };
+
+        let data = match keystore {
+            Either::Left(exchange) => ecdh_decrypt(keypair, Some(exchange), voice_note_cipher)?,
+            Either::Right(keystore) => {
+                keystore.try_decrypt(keypair, &self.sender(), voice_note_cipher)?
+            }
+        };
+
+        let voice_note: VoiceNote = serde_json::from_slice(&data)?;
+
+        validate_voice_note(&voice_note)?;
+
+        Ok(Some(voice_note))
+    }
+
+    /// Identity document shared in the message. The signature of the document is verified
+    pub fn contact_card(
+        &self,
+        keypair: &Keypair,
+        keystore: Either<&DID, &Keystore>,
+    ) -> Result<IdentityDocument, Error> {
+        let card_cipher = self
+            .contact_card
+            .as_ref()
+            .ok_or(Error::MessageNotContactCard)?;
+
+        let data = match keystore {
+            Either::Left(exchange) => ecdh_decrypt(keypair, Some(exchange), card_cipher)?,
+            Either::Right(keystore) => {
+                keystore.try_decrypt(keypair, &self.sender(), card_cipher)?
+            }
+        };
+
+        let document: IdentityDocument = serde_json::from_slice(&data)?;
+
+        document.verify()?;
+
+        Ok(document)
+    }
+
+    /// Check the options of each vote against the poll. The signature of the votes are checked
+    /// when the document is verified
+    pub fn validate_poll_votes(&self, poll: &Poll) -> Result<(), Error> {
+        self.poll_votes
+            .values()
+            .try_for_each(|vote| validate_poll_vote(poll, vote))
+    }
+
+    pub fn poll_results(
+        &self,
+        keypair: &Keypair,
+        keystore: Either<&DID, &Keystore>,
+    ) -> Result<PollResults, Error> {
+        let poll = self.poll(keypair, keystore)?;
+        let votes = self
+            .poll_votes
+            .iter()
+            .map(|(voter, vote)| (voter.clone(), vote.options.clone()))
+            .collect();
+        Ok(PollResults::new(poll, votes, self.poll_closed))
+    }
+
     pub async fn resolve(
         &self,
         ipfs: &Ipfs,
@@ -595,7 +1041,7 @@ impl MessageDocument {
         local: bool,
         key: Either<&DID, &Keystore>,
     ) -> Result<Message, Error> {
-        self.verify()?;
+        self.verify_limits()?;
 
         let mut message = Message::default();
         message.set_id(self.id);
@@ -603,11 +1049,14 @@ impl MessageDocument {
         message.set_conversation_id(self.conversation_id);
         message.set_sender(self.sender.to_did());
         message.set_date(self.date);
+        message.set_clock(self.clock());
         if let Some(date) = self.modified {
             message.set_modified(date);
         }
         message.set_pinned(self.pinned);
         message.set_replied(self.replied);
+        message.set_mentions(self.mentions.iter().cloned().collect());
+        message.set_signature_state(self.signature_state());
 
         let attachments = self.attachments();
 
@@ -634,6 +1083,24 @@ impl MessageDocument {
             Err(e) => return Err(e),
         }
 
+        if let Ok(metadata) = self.metadata(keypair, key) {
+            message.set_metadata(metadata);
+        }
+
+        if self.message_type == MessageType::Poll {
+            let poll = self.poll(keypair, key)?;
+            self.validate_poll_votes(&poll)?;
+            message.set_poll(Some(poll));
+        }
+
+        if self.message_type == MessageType::VoiceNote {
+            message.set_voice_note(self.voice_note(keypair, key)?);
+        }
+
+        if self.message_type == MessageType::ContactCard {
+            message.set_contact_card(Some(self.contact_card(keypair, key)?.resolve()?));
+        }
+
         Ok(message)
     }
 
@@ -661,6 +1128,23 @@ impl MessageDocument {
         );
         let attachments_hash = (!attachments_hash.is_empty()).then_some(attachments_hash);
 
+        let mentions_hash = (!self.mentions.is_empty()).then(|| {
+            sha256_iter(
+                self.mentions
+                    .iter()
+                    .map(|did| did.public_key_bytes())
+                    .map(Option::Some),
+                None,
+            )
+        });
+
+        let clock = match self.version {
+            MessageVersion::V0 => None,
+            MessageVersion::V1 => Some(clock_bytes(
+                self.clock.as_ref().ok_or(Error::InvalidMessage)?,
+            )),
+        };
+
         let hash = sha256_iter(
             [
                 Some(self.conversation_id.as_bytes().to_vec()),
@@ -671,6 +1155,12 @@ impl MessageDocument {
                 self.replied.map(|id| id.as_bytes().to_vec()),
                 attachments_hash,
                 self.message.as_ref().map(|m| m.to_vec()),
+                mentions_hash,
+                self.metadata.as_ref().map(|m| m.to_vec()),
+                self.poll.as_ref().map(|p| p.to_vec()),
+                self.voice_note.as_ref().map(|v| v.to_vec()),
+                self.contact_card.as_ref().map(|c| c.to_vec()),
+                clock,
             ]
             .into_iter(),
             None,
@@ -683,6 +1173,107 @@ impl MessageDocument {
     }
 }
 
+fn validate_poll(poll: &Poll) -> Result<(), Error> {
+    let options = poll.options();
+
+    if !(2..=MAX_POLL_OPTIONS).contains(&options.len()) {
+        return Err(Error::InvalidLength {
+            context: "poll options".into(),
+            current: options.len(),
+            minimum: Some(2),
+            maximum: Some(MAX_POLL_OPTIONS),
+        });
+    }
+
+    let question_length = poll.question().trim().chars().count();
+    if !(MIN_MESSAGE_SIZE..=MAX_MESSAGE_SIZE).contains(&question_length) {
+        return Err(Error::InvalidLength {
+            context: "poll question".into(),
+            current: question_length,
+            minimum: Some(MIN_MESSAGE_SIZE),
+            maximum: Some(MAX_MESSAGE_SIZE),
+        });
+    }
+
+    for option in options {
+        let length = option.trim().chars().count();
+        if !(1..=MAX_POLL_OPTION_LENGTH).contains(&length) {
+            return Err(Error::InvalidLength {
+                context: "poll option".into(),
+                current: length,
+                minimum: Some(1),
+                maximum: Some(MAX_POLL_OPTION_LENGTH),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_poll_vote(poll: &Poll, vote: &PollVoteDocument) -> Result<(), Error> {
+    if (!poll.multiple_choice() && vote.options.len() > 1)
+        || vote
+            .options
+            .iter()
+            .any(|option| *option >= poll.options().len())
+    {
+        return Err(Error::InvalidPollVote);
+    }
+    Ok(())
+}
+
+fn validate_voice_note(voice_note: &VoiceNote) -> Result<(), Error> {
+    let length = voice_note.waveform().len();
+    if length > VOICE_NOTE_WAVEFORM_LENGTH {
+        return Err(Error::InvalidLength {
+            context: "waveform".into(),
+            current: length,
+            minimum: None,
+            maximum: Some(VOICE_NOTE_WAVEFORM_LENGTH),
+        });
+    }
+    Ok(())
+}
+
+fn clock_bytes(clock: &HybridTimestamp) -> Vec<u8> {
+    let mut bytes = clock.physical().to_le_bytes().to_vec();
+    bytes.extend_from_slice(&clock.logical().to_le_bytes());
+    bytes
+}
+
+fn validate_metadata(metadata: &IndexMap<String, String>) -> Result<(), Error> {
+    if metadata.len() > MAX_METADATA_ENTRIES {
+        return Err(Error::InvalidLength {
+            context: "metadata".into(),
+            current: metadata.len(),
+            minimum: None,
+            maximum: Some(MAX_METADATA_ENTRIES),
+        });
+    }
+
+    for (key, val) in metadata {
+        if key.is_empty() || key.len() > MAX_METADATA_KEY_LENGTH {
+            return Err(Error::InvalidLength {
+                context: "metadata key".into(),
+                current: key.len(),
+                minimum: Some(1),
+                maximum: Some(MAX_METADATA_KEY_LENGTH),
+            });
+        }
+
+        if val.len() > MAX_METADATA_VALUE_LENGTH {
+            return Err(Error::InvalidLength {
+                context: "metadata value".into(),
+                current: val.len(),
+                minimum: None,
+                maximum: Some(MAX_METADATA_VALUE_LENGTH),
+            });
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DIDEd25519Reference([u8; 32]);
 
@@ -789,3 +1380,146 @@ impl<'d> Deserialize<'d> for MessageSignature {
         Self::try_from(bytes).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::store::{DidExt, MAX_CLOCK_DRIFT};
+
+    use super::{MessageDocumentBuilder, MessageVersion, PollVoteDocument};
+    use chrono::{Duration, Utc};
+    use either::Either;
+    use indexmap::IndexSet;
+    use rust_ipfs::Keypair;
+    use std::collections::BTreeSet;
+    use uuid::Uuid;
+    use warp::raygun::{HybridTimestamp, Poll};
+
+    #[test]
+    fn clock_orders_over_date() -> anyhow::Result<()> {
+        let keypair_a = Keypair::generate_ed25519();
+        let keypair_b = Keypair::generate_ed25519();
+        let did_a = keypair_a.to_did()?;
+        let did_b = keypair_b.to_did()?;
+        let conversation_id = Uuid::new_v4();
+
+        // sender of the first message has a clock that is an hour ahead
+        let first = MessageDocumentBuilder::new(&keypair_a, Either::Left(&did_b))
+            .set_conversation_id(conversation_id)
+            .set_sender(did_a.clone())
+            .set_date(Utc::now() + Duration::hours(1))
+            .set_clock(HybridTimestamp::new(1, 0))
+            .set_message(vec!["first".into()])?
+            .build()?;
+
+        let second = MessageDocumentBuilder::new(&keypair_b, Either::Left(&did_a))
+            .set_conversation_id(conversation_id)
+            .set_sender(did_b)
+            .set_clock(HybridTimestamp::new(1, 1))
+            .set_message(vec!["second".into()])?
+            .build()?;
+
+        assert_eq!(first.version(), MessageVersion::V1);
+        first.verify()?;
+        second.verify()?;
+
+        let list = BTreeSet::from_iter([second.clone(), first.clone()]);
+        assert_eq!(Vec::from_iter(list), vec![first, second]);
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_clock_fails_verification() -> anyhow::Result<()> {
+        let keypair = Keypair::generate_ed25519();
+        let did = keypair.to_did()?;
+        let recipient = Keypair::generate_ed25519().to_did()?;
+
+        let mut document = MessageDocumentBuilder::new(&keypair, Either::Left(&recipient))
+            .set_conversation_id(Uuid::new_v4())
+            .set_sender(did)
+            .set_clock(HybridTimestamp::new(10, 0))
+            .set_message(vec!["message".into()])?
+            .build()?;
+
+        document.verify()?;
+
+        document.clock = Some(HybridTimestamp::new(0, 0));
+
+        assert!(document.verify().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn forged_poll_votes_fail_verification() -> anyhow::Result<()> {
+        let keypair_a = Keypair::generate_ed25519();
+        let keypair_b = Keypair::generate_ed25519();
+        let keypair_c = Keypair::generate_ed25519();
+        let did_a = keypair_a.to_did()?;
+        let did_b = keypair_b.to_did()?;
+        let did_c = keypair_c.to_did()?;
+
+        let document = MessageDocumentBuilder::new(&keypair_a, Either::Left(&did_b))
+            .set_conversation_id(Uuid::new_v4())
+            .set_sender(did_a.clone())
+            .set_poll(Poll::new("question", vec!["yes".into(), "no".into()]))?
+            .build()?;
+        let poll = document.poll(&keypair_b, Either::Left(&did_a))?;
+
+        let vote = |keypair: &Keypair, options: &[usize]| {
+            PollVoteDocument::new(
+                keypair,
+                document.conversation_id,
+                document.id,
+                IndexSet::from_iter(options.iter().copied()),
+            )
+        };
+
+        let mut valid = document.clone();
+        valid
+            .poll_votes
+            .insert(did_b.clone(), vote(&keypair_b, &[0])?);
+        valid.verify()?;
+        valid.validate_poll_votes(&poll)?;
+
+        // vote recorded under another member
+        let mut forged = document.clone();
+        forged
+            .poll_votes
+            .insert(did_c.clone(), vote(&keypair_b, &[0])?);
+        assert!(forged.verify().is_err());
+
+        // options changed after the vote was signed
+        let mut forged = document.clone();
+        let mut tampered = vote(&keypair_c, &[0])?;
+        tampered.options = IndexSet::from_iter([1]);
+        forged.poll_votes.insert(did_c.clone(), tampered);
+        assert!(forged.verify().is_err());
+
+        // signed vote for an option the poll does not have
+        let mut forged = document;
+        forged.poll_votes.insert(did_c, vote(&keypair_c, &[5])?);
+        forged.verify()?;
+        assert!(forged.validate_poll_votes(&poll).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn clock_beyond_drift_fails_verification() -> anyhow::Result<()> {
+        let keypair = Keypair::generate_ed25519();
+        let did = keypair.to_did()?;
+        let recipient = Keypair::generate_ed25519().to_did()?;
+        let now = HybridTimestamp::from(Utc::now()).physical();
+
+        let build = |physical: u64| {
+            MessageDocumentBuilder::new(&keypair, Either::Left(&recipient))
+                .set_conversation_id(Uuid::new_v4())
+                .set_sender(did.clone())
+                .set_clock(HybridTimestamp::new(physical, 0))
+                .set_message(vec!["message".into()])?
+                .build()
+        };
+
+        build(now + MAX_CLOCK_DRIFT / 2)?.verify()?;
+        assert!(build(now + MAX_CLOCK_DRIFT + 60 * 1000)?.verify().is_err());
+        Ok(())
+    }
+}
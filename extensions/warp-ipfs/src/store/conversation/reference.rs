@@ -5,6 +5,7 @@ use indexmap::IndexMap;
 use ipld_core::cid::Cid;
 use rust_ipfs::{Ipfs, IpfsPath};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::time::Duration;
 use uuid::Uuid;
 use warp::error::Error;
@@ -221,6 +222,45 @@ impl MessageReferenceList {
         refs_list.contains(ipfs, message_id).await
     }
 
+    /// Ids of every message referenced by the list, optionally including messages that have been removed
+    #[async_recursion::async_recursion]
+    pub async fn ids(&self, ipfs: &Ipfs, removed: bool) -> Vec<Uuid> {
+        let Some(cid) = self.messages else {
+            return vec![];
+        };
+
+        let Ok(list) = ipfs
+            .get_dag(cid)
+            .timeout(Duration::from_secs(10))
+            .deserialized::<IndexMap<String, Option<Cid>>>()
+            .await
+        else {
+            return vec![];
+        };
+
+        let mut ids = list
+            .iter()
+            .filter(|(_, cid)| removed || cid.is_some())
+            .filter_map(|(id, _)| Uuid::from_str(id).ok())
+            .collect::<Vec<_>>();
+
+        let Some(next) = self.next else {
+            return ids;
+        };
+
+        let Ok(refs_list) = ipfs
+            .get_dag(next)
+            .timeout(Duration::from_secs(10))
+            .deserialized::<MessageReferenceList>()
+            .await
+        else {
+            return ids;
+        };
+
+        ids.extend(refs_list.ids(ipfs, removed).await);
+        ids
+    }
+
     #[async_recursion::async_recursion]
     pub async fn count(&self, ipfs: &Ipfs) -> usize {
         let Some(cid) = self.messages else {
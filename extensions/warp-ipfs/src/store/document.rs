@@ -98,6 +98,18 @@ pub struct RootDocument {
     /// map of keystore for group chat conversations and communities
     #[serde(skip_serializing_if = "Option::is_none")]
     pub keystore: Option<Cid>,
+    /// map of user defined labels to conversations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_labels: Option<Cid>,
+    /// map of encrypted drafts for conversations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drafts: Option<Cid>,
+    /// map of encrypted messages held back by the message filter for conversations and communities
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quarantine: Option<Cid>,
+    /// encrypted map of saved messages
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub saved_messages: Option<Cid>,
     /// index to constellation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub file_index: Option<Cid>,
@@ -347,12 +359,63 @@ impl RootDocument {
             },
         );
 
+        let fut_drafts =
+            futures::future::ready(self.drafts.ok_or(Error::Other)).and_then(|document| {
+                let ipfs = ipfs.clone();
+                async move {
+                    let map: BTreeMap<String, Cid> = ipfs.get_dag(document).deserialized().await?;
+                    let fut_drafts = map.into_values().map(|cid| ipfs.get_dag(cid).into_future());
+                    let _ = futures::future::join_all(fut_drafts).await;
+                    Ok::<_, Error>(())
+                }
+            });
+
+        let fut_quarantine =
+            futures::future::ready(self.quarantine.ok_or(Error::Other)).and_then(|document| {
+                let ipfs = ipfs.clone();
+                async move {
+                    let map: BTreeMap<String, Cid> = ipfs.get_dag(document).deserialized().await?;
+                    let fut_quarantine =
+                        map.into_values().map(|cid| ipfs.get_dag(cid).into_future());
+                    let _ = futures::future::join_all(fut_quarantine).await;
+                    Ok::<_, Error>(())
+                }
+            });
+
+        let fut_saved_messages = futures::future::ready(self.saved_messages.ok_or(Error::Other))
+            .and_then(|document| {
+                let ipfs = ipfs.clone();
+                async move {
+                    ipfs.get_dag(document)
+                        .await
+                        .map_err(anyhow::Error::from)
+                        .map_err(Error::from)
+                }
+            });
+
+        let fut_conversation_labels = futures::future::ready(
+            self.conversation_labels.ok_or(Error::Other),
+        )
+        .and_then(|document| {
+            let ipfs = ipfs.clone();
+            async move {
+                ipfs.get_dag(document)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .map_err(Error::from)
+            }
+        });
+
         let _ = tokio::join!(
             fut_friends,
             fut_block_list,
             fut_blocked_by_list,
             fut_requests_list,
-            fut_keystore
+            fut_keystore,
+            fut_drafts,
+            fut_quarantine,
+            fut_saved_messages,
+            fut_conversation_labels
         );
 
         self.verify(&ipfs).await
@@ -387,6 +450,10 @@ impl RootDocument {
             request: None,
             conversations: None,
             keystore: None,
+            conversation_labels: None,
+            drafts: None,
+            quarantine: None,
+            saved_messages: None,
             communities: None,
             file_index: None,
             status: None,
@@ -516,11 +583,19 @@ impl FileAttachmentDocument {
         file_document.to_attachment()
     }
 
-    pub async fn resolve_to_file(&self, ipfs: &Ipfs, local: bool) -> Result<File, Error> {
+    /// File without its thumbnail, used when the thumbnail is not needed or cannot be resolved
+    pub fn to_file(&self) -> File {
         let file = File::new(&self.name);
         file.set_id(self.id);
         file.set_size(self.size);
         file.set_file_type(self.file_type.clone());
+        file.set_creation(self.creation);
+        file.set_modified(Some(self.creation));
+        file
+    }
+
+    pub async fn resolve_to_file(&self, ipfs: &Ipfs, local: bool) -> Result<File, Error> {
+        let file = self.to_file();
 
         if let Some(cid) = self.thumbnail {
             let image: ImageDag = ipfs
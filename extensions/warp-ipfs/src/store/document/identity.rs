@@ -58,6 +58,9 @@ pub struct IdentityMetadata {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub arb_data: Option<Cid>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bot: bool,
 }
 
 impl From<Identity> for IdentityDocument {
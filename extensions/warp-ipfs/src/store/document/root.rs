@@ -6,16 +6,25 @@ use futures::{
 use indexmap::IndexMap;
 use ipld_core::cid::Cid;
 use rust_ipfs::{Ipfs, IpfsPath, Keypair};
+use serde::{de::DeserializeOwned, Serialize};
 use std::borrow::Borrow;
-use std::{collections::BTreeMap, future::IntoFuture, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::IntoFuture,
+    sync::Arc,
+};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use warp::{
-    constellation::directory::Directory, crypto::DID, error::Error,
+    constellation::directory::Directory,
+    crypto::DID,
+    error::Error,
     multipass::identity::IdentityStatus,
+    raygun::{Draft, SavedMessage},
 };
 
+use crate::filter::Quarantine;
 use crate::store::{
     community::CommunityDocument, conversation::ConversationDocument, ds_key::DataStoreKey,
     ecdh_decrypt, ecdh_encrypt, identity::Request, keystore::Keystore, VecExt,
@@ -221,6 +230,59 @@ impl RootDocumentMap {
         inner.set_keystore(document).await
     }
 
+    pub async fn get_conversation_labels(&self) -> Result<BTreeMap<String, BTreeSet<Uuid>>, Error> {
+        let inner = &*self.inner.read().await;
+        inner.get_conversation_labels().await
+    }
+
+    pub async fn set_conversation_labels(
+        &self,
+        labels: BTreeMap<String, BTreeSet<Uuid>>,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.set_conversation_labels(labels).await
+    }
+
+    pub async fn get_draft(&self, id: Uuid) -> Result<Option<Draft>, Error> {
+        let inner = &*self.inner.read().await;
+        inner.get_draft(id).await
+    }
+
+    pub async fn set_draft(&self, id: Uuid, draft: Option<Draft>) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.set_draft(id, draft).await
+    }
+
+    pub async fn get_quarantine<E: DeserializeOwned>(
+        &self,
+        id: Uuid,
+    ) -> Result<Quarantine<E>, Error> {
+        let inner = &*self.inner.read().await;
+        inner.get_quarantine(id).await
+    }
+
+    pub async fn set_quarantine<E: Serialize>(
+        &self,
+        id: Uuid,
+        quarantine: &Quarantine<E>,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.set_quarantine(id, quarantine).await
+    }
+
+    pub async fn get_saved_messages(&self) -> Result<IndexMap<Uuid, SavedMessage>, Error> {
+        let inner = &*self.inner.read().await;
+        inner.get_saved_messages().await
+    }
+
+    pub async fn set_saved_messages(
+        &self,
+        saved: IndexMap<Uuid, SavedMessage>,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.set_saved_messages(saved).await
+    }
+
     pub async fn get_directory_index(&self) -> Result<Directory, Error> {
         let inner = &*self.inner.read().await;
         inner.get_root_index().await
@@ -908,6 +970,164 @@ impl RootDocumentInner {
             .map_err(Error::from)
     }
 
+    async fn get_conversation_labels(&self) -> Result<BTreeMap<String, BTreeSet<Uuid>>, Error> {
+        let document = self.get_root_document().await?;
+
+        let cid = match document.conversation_labels {
+            Some(cid) => cid,
+            None => return Ok(BTreeMap::new()),
+        };
+
+        self.ipfs
+            .get_dag(cid)
+            .local()
+            .deserialized()
+            .await
+            .map_err(Error::from)
+    }
+
+    async fn set_conversation_labels(
+        &mut self,
+        labels: BTreeMap<String, BTreeSet<Uuid>>,
+    ) -> Result<(), Error> {
+        let mut document = self.get_root_document().await?;
+        document.conversation_labels = match labels.is_empty() {
+            true => None,
+            false => Some(self.ipfs.put_dag(labels).await?),
+        };
+        self.set_root_document(document).await
+    }
+
+    async fn get_draft(&self, id: Uuid) -> Result<Option<Draft>, Error> {
+        let document = self.get_root_document().await?;
+
+        let cid = match document.drafts {
+            Some(cid) => cid,
+            None => return Ok(None),
+        };
+
+        let path = IpfsPath::from(cid).sub_path(&id.to_string())?;
+
+        let bytes: Vec<u8> = match self.ipfs.get_dag(path).local().deserialized().await {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(None),
+        };
+
+        let bytes = ecdh_decrypt(self.keypair(), None, bytes)?;
+        let draft = serde_json::from_slice(&bytes)?;
+        Ok(Some(draft))
+    }
+
+    async fn set_draft(&mut self, id: Uuid, draft: Option<Draft>) -> Result<(), Error> {
+        let mut document = self.get_root_document().await?;
+
+        let mut map: BTreeMap<String, Cid> = match document.drafts {
+            Some(cid) => self.ipfs.get_dag(cid).local().deserialized().await?,
+            None => BTreeMap::new(),
+        };
+
+        match draft {
+            Some(draft) => {
+                let bytes = ecdh_encrypt(self.keypair(), None, serde_json::to_vec(&draft)?)?;
+                let cid = self.ipfs.put_dag(bytes).await?;
+                map.insert(id.to_string(), cid);
+            }
+            None => {
+                if map.remove(&id.to_string()).is_none() {
+                    return Ok(());
+                }
+            }
+        }
+
+        document.drafts = match map.is_empty() {
+            true => None,
+            false => Some(self.ipfs.put_dag(map).await?),
+        };
+
+        self.set_root_document(document).await
+    }
+
+    async fn get_quarantine<E: DeserializeOwned>(&self, id: Uuid) -> Result<Quarantine<E>, Error> {
+        let document = self.get_root_document().await?;
+
+        let cid = match document.quarantine {
+            Some(cid) => cid,
+            None => return Ok(Quarantine::new()),
+        };
+
+        let path = IpfsPath::from(cid).sub_path(&id.to_string())?;
+
+        let bytes: Vec<u8> = match self.ipfs.get_dag(path).local().deserialized().await {
+            Ok(bytes) => bytes,
+            Err(_) => return Ok(Quarantine::new()),
+        };
+
+        let bytes = ecdh_decrypt(self.keypair(), None, bytes)?;
+        let quarantine = serde_json::from_slice(&bytes)?;
+        Ok(quarantine)
+    }
+
+    async fn set_quarantine<E: Serialize>(
+        &mut self,
+        id: Uuid,
+        quarantine: &Quarantine<E>,
+    ) -> Result<(), Error> {
+        let mut document = self.get_root_document().await?;
+
+        let mut map: BTreeMap<String, Cid> = match document.quarantine {
+            Some(cid) => self.ipfs.get_dag(cid).local().deserialized().await?,
+            None => BTreeMap::new(),
+        };
+
+        match quarantine.is_empty() {
+            false => {
+                let bytes = ecdh_encrypt(self.keypair(), None, serde_json::to_vec(quarantine)?)?;
+                let cid = self.ipfs.put_dag(bytes).await?;
+                map.insert(id.to_string(), cid);
+            }
+            true => {
+                if map.remove(&id.to_string()).is_none() {
+                    return Ok(());
+                }
+            }
+        }
+
+        document.quarantine = match map.is_empty() {
+            true => None,
+            false => Some(self.ipfs.put_dag(map).await?),
+        };
+
+        self.set_root_document(document).await
+    }
+
+    async fn get_saved_messages(&self) -> Result<IndexMap<Uuid, SavedMessage>, Error> {
+        let document = self.get_root_document().await?;
+
+        let cid = match document.saved_messages {
+            Some(cid) => cid,
+            None => return Ok(IndexMap::new()),
+        };
+
+        let bytes: Vec<u8> = self.ipfs.get_dag(cid).local().deserialized().await?;
+        let bytes = ecdh_decrypt(self.keypair(), None, bytes)?;
+        serde_json::from_slice(&bytes).map_err(Error::from)
+    }
+
+    async fn set_saved_messages(
+        &mut self,
+        saved: IndexMap<Uuid, SavedMessage>,
+    ) -> Result<(), Error> {
+        let mut document = self.get_root_document().await?;
+        document.saved_messages = match saved.is_empty() {
+            true => None,
+            false => {
+                let bytes = ecdh_encrypt(self.keypair(), None, serde_json::to_vec(&saved)?)?;
+                Some(self.ipfs.put_dag(bytes).await?)
+            }
+        };
+        self.set_root_document(document).await
+    }
+
     async fn get_keystore(&self, id: Uuid) -> Result<Keystore, Error> {
         let document = self.get_root_document().await?;
 
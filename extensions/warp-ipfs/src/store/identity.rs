@@ -952,6 +952,7 @@ impl IdentityStore {
         let is_blocked_by = self.is_blocked_by(out_did).await.unwrap_or_default();
 
         identity.metadata.platform = Some(self.own_platform());
+        identity.metadata.bot = self.config.store_setting().bot;
 
         let metadata = identity.metadata;
         identity.metadata = Default::default();
@@ -2404,6 +2405,64 @@ impl IdentityStore {
             .ok_or(Error::IdentityDoesntExist)
     }
 
+    pub async fn identity_is_bot(&self, did: &DID) -> Result<bool, Error> {
+        let own_did = self
+            .own_identity()
+            .await
+            .map(|identity| identity.did_key().to_owned())
+            .map_err(|_| Error::OtherWithContext("Identity store may not be initialized".into()))?;
+
+        if own_did.eq(did) {
+            return Ok(self.config.store_setting().bot);
+        }
+
+        self.identity_cache
+            .get(did)
+            .await
+            .map(|cache| cache.metadata.bot)
+            .map_err(|_| Error::IdentityDoesntExist)
+    }
+
+    /// Signed identity document of a known identity, including our own
+    pub async fn identity_document(&self, did: &DID) -> Result<IdentityDocument, Error> {
+        let own_document = self.own_identity_document().await?;
+
+        if own_document.did.eq(did) {
+            return Ok(own_document);
+        }
+
+        self.identity_cache
+            .get(did)
+            .await
+            .map_err(|_| Error::IdentityDoesntExist)
+    }
+
+    /// Store an identity document that was shared with us, such as through a contact card.
+    /// The document is ignored if we already have a newer copy of it
+    pub async fn import_identity_document(&self, document: &IdentityDocument) -> Result<(), Error> {
+        document.verify()?;
+
+        if self.did_key().eq(&document.did) || self.is_blocked(&document.did).await? {
+            return Ok(());
+        }
+
+        if let Ok(cached) = self.identity_cache.get(&document.did).await {
+            if cached.modified >= document.modified {
+                return Ok(());
+            }
+        }
+
+        self.identity_cache.insert(document).await?;
+
+        if !self.discovery.contains(&document.did).await {
+            if let Err(e) = self.discovery.insert(&document.did).await {
+                tracing::warn!("Error inserting into discovery service: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn get_raw_keypair(&self) -> anyhow::Result<ipfs::libp2p::identity::ed25519::Keypair> {
         self.root_document
             .keypair()
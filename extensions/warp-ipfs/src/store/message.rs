@@ -2,7 +2,7 @@ mod attachment;
 mod community_task;
 mod task;
 
-use community_task::CommunityTaskCommand;
+use community_task::{CommunityCrossPost, CommunityTaskCommand};
 use futures_timer::Delay;
 use task::ConversationTaskCommand;
 
@@ -35,6 +35,9 @@ use uuid::Uuid;
 use super::community::CommunityInviteDocument;
 use super::topics::ConversationTopic;
 use super::{document::root::RootDocumentMap, ds_key::DataStoreKey, PeerIdExt};
+use crate::config::{self, Config};
+use crate::filter::MessageFilterHandle;
+use crate::shuttle::message::protocol::{CommunityDirectory, Response as ShuttleMessageResponse};
 use crate::store::CommunityJoinEvents;
 use crate::store::{
     conversation::ConversationDocument,
@@ -48,24 +51,29 @@ use crate::store::{
     sign_serde,
     topics::PeerTopic,
     ConversationEvents, ConversationRequestKind, ConversationRequestResponse, DidExt,
+    MAX_COMMUNITY_NAME_LENGTH, MAX_CONVERSATION_LABELS, MAX_CONVERSATION_LABEL_LENGTH,
+    MAX_SAVED_MESSAGES, MAX_SAVED_MESSAGE_NOTE_LENGTH,
 };
 
 use crate::store::community::CommunityDocument;
 use chrono::{DateTime, Utc};
 use warp::raygun::community::{
-    Community, CommunityChannel, CommunityChannelPermission, CommunityChannelType, CommunityInvite,
-    CommunityPermission, CommunityRole, RoleId,
+    Community, CommunityAuditLogEntry, CommunityAuditLogOptions, CommunityBan, CommunityChannel,
+    CommunityChannelCategory, CommunityChannelPermission, CommunityChannelType, CommunityInvite,
+    CommunityListing, CommunityPermission, CommunityRole, RoleId,
 };
-use warp::raygun::{ConversationImage, GroupPermissionOpt, Message};
+use warp::raygun::{ConversationImage, GroupPermissionOpt, Message, MessageBuilder};
 use warp::{
     constellation::ConstellationProgressStream,
     crypto::DID,
     error::Error,
     multipass::MultiPassEventKind,
     raygun::{
-        AttachmentEventStream, Conversation, ConversationType, Location, MessageEvent,
+        AttachmentEventStream, AttachmentFilter, Conversation, ConversationAttachment,
+        ConversationQuery, ConversationSummary, ConversationType, Draft, Location, MessageEvent,
         MessageEventKind, MessageOptions, MessageReference, MessageStatus, Messages, PinState,
-        RayGunEventKind, ReactionState,
+        PinnedMessage, Poll, PollResults, QuarantinedMessage, RayGunEventKind, ReactionState,
+        SavedMessage, SyncStatus,
     },
 };
 
@@ -82,6 +90,8 @@ pub struct MessageStore {
 impl MessageStore {
     pub async fn new(
         ipfs: &Ipfs,
+        config: &Config,
+        message_filter: Option<MessageFilterHandle>,
         discovery: Discovery,
         file: &FileStore,
         event: EventSubscription<RayGunEventKind>,
@@ -91,11 +101,16 @@ impl MessageStore {
 
         let root = identity.root_document().clone();
 
+        let (crosspost_tx, crosspost_rx) = mpsc::channel(256);
+
         let mut inner = ConversationInner {
             ipfs: ipfs.clone(),
+            config: config.clone(),
+            message_filter,
             conversation_task: HashMap::new(),
             community_task: HashMap::new(),
             community_invites: vec![],
+            crosspost_tx,
             identity: identity.clone(),
             root,
             discovery,
@@ -116,6 +131,7 @@ impl MessageStore {
             inner: inner.clone(),
             ipfs: ipfs.clone(),
             identity: identity.clone(),
+            crosspost_rx,
         };
 
         let _handle = async_rt::task::spawn_abortable(task.run());
@@ -130,10 +146,190 @@ impl MessageStore {
         Ok(document.into())
     }
 
-    pub async fn list_conversations(&self) -> Result<Vec<Conversation>, Error> {
-        self.list()
-            .await
-            .map(|list| list.into_iter().map(|document| document.into()).collect())
+    pub async fn list_conversations(
+        &self,
+        query: ConversationQuery,
+    ) -> Result<Vec<ConversationSummary>, Error> {
+        let (list, labels, own_did) = {
+            let inner = &*self.inner.read().await;
+            // Labels may not be available locally yet, such as right after importing the root document
+            let labels = inner
+                .root
+                .get_conversation_labels()
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "unable to get conversation labels");
+                    Default::default()
+                });
+            (inner.list().await, labels, inner.identity.did_key())
+        };
+
+        let name_query = query.name().map(|name| name.to_lowercase());
+
+        let mut summaries = Vec::with_capacity(list.len());
+
+        for document in list {
+            let conversation_id = document.id();
+
+            if let Some(label) = query.label() {
+                if !labels
+                    .get(label)
+                    .is_some_and(|ids| ids.contains(&conversation_id))
+                {
+                    continue;
+                }
+            }
+
+            let recipients = document.recipients();
+
+            if !query
+                .participants()
+                .iter()
+                .all(|did| recipients.contains(did))
+            {
+                continue;
+            }
+
+            if let Some(name_query) = name_query.as_ref() {
+                if !document
+                    .name
+                    .as_ref()
+                    .is_some_and(|name| name.to_lowercase().contains(name_query))
+                {
+                    continue;
+                }
+            }
+
+            let last_message = match self
+                .get_messages(
+                    conversation_id,
+                    MessageOptions::default().set_last_message(),
+                )
+                .await
+            {
+                Ok(Messages::List(mut list)) => list.pop(),
+                _ => None,
+            };
+
+            let unread = last_message.as_ref().is_some_and(|message| {
+                message.sender().ne(&own_did)
+                    && document
+                        .last_read
+                        .map_or(true, |last_read| message.date() > last_read)
+            });
+
+            if query.unread() && !unread {
+                continue;
+            }
+
+            let conversation_labels = labels
+                .iter()
+                .filter(|(_, ids)| ids.contains(&conversation_id))
+                .map(|(label, _)| label.clone())
+                .collect::<Vec<_>>();
+
+            summaries.push(ConversationSummary::new(
+                Conversation::from(&document),
+                conversation_labels,
+                last_message,
+                unread,
+            ));
+        }
+
+        if query.sort_by_last_activity() {
+            summaries.sort_by_key(|summary| std::cmp::Reverse(summary.last_activity()));
+        }
+
+        Ok(summaries)
+    }
+
+    pub async fn add_conversation_label(
+        &self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+
+        if !inner.contains(conversation_id).await {
+            return Err(Error::InvalidConversation);
+        }
+
+        let label = label.trim();
+
+        if label.is_empty() || label.chars().count() > MAX_CONVERSATION_LABEL_LENGTH {
+            return Err(Error::InvalidLength {
+                context: "label".into(),
+                current: label.chars().count(),
+                minimum: Some(1),
+                maximum: Some(MAX_CONVERSATION_LABEL_LENGTH),
+            });
+        }
+
+        let mut labels = inner.root.get_conversation_labels().await?;
+
+        if !labels.contains_key(label) && labels.len() >= MAX_CONVERSATION_LABELS {
+            return Err(Error::InvalidLength {
+                context: "labels".into(),
+                current: labels.len(),
+                minimum: None,
+                maximum: Some(MAX_CONVERSATION_LABELS),
+            });
+        }
+
+        if !labels
+            .entry(label.to_string())
+            .or_default()
+            .insert(conversation_id)
+        {
+            return Ok(());
+        }
+
+        inner.root.set_conversation_labels(labels).await?;
+        inner.identity.export_root_document().await
+    }
+
+    pub async fn remove_conversation_label(
+        &self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+
+        let mut labels = inner.root.get_conversation_labels().await?;
+
+        let Some(ids) = labels.get_mut(label.trim()) else {
+            return Ok(());
+        };
+
+        if !ids.remove(&conversation_id) {
+            return Ok(());
+        }
+
+        labels.retain(|_, ids| !ids.is_empty());
+
+        inner.root.set_conversation_labels(labels).await?;
+        inner.identity.export_root_document().await
+    }
+
+    pub async fn list_conversation_labels(&self) -> Result<Vec<String>, Error> {
+        let inner = &*self.inner.read().await;
+        let labels = inner.root.get_conversation_labels().await?;
+        Ok(labels.into_keys().collect())
+    }
+
+    pub async fn mark_conversation_read(&self, conversation_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::MarkRead { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
 
     pub async fn get_conversation_stream(
@@ -486,6 +682,28 @@ impl MessageStore {
         rx.await.map_err(anyhow::Error::from)?
     }
 
+    pub async fn send_message_with(
+        &self,
+        conversation_id: Uuid,
+        message: MessageBuilder,
+    ) -> Result<Uuid, Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::SendMessageWith {
+                message,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
     pub async fn edit_message(
         &self,
         conversation_id: Uuid,
@@ -580,13 +798,7 @@ impl MessageStore {
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn react(
-        &self,
-        conversation_id: Uuid,
-        message_id: Uuid,
-        state: ReactionState,
-        emoji: String,
-    ) -> Result<(), Error> {
+    pub async fn send_contact_card(&self, conversation_id: Uuid, did: &DID) -> Result<Uuid, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -596,23 +808,15 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::ReactMessage {
-                message_id,
-                state,
-                emoji,
+            .send(ConversationTaskCommand::SendContactCard {
+                did: did.clone(),
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn attach(
-        &self,
-        conversation_id: Uuid,
-        message_id: Option<Uuid>,
-        locations: Vec<Location>,
-        messages: Vec<String>,
-    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+    pub async fn send_poll(&self, conversation_id: Uuid, poll: Poll) -> Result<Uuid, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -622,24 +826,17 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::AttachMessage {
-                message_id,
-                locations,
-                lines: messages,
-                response: tx,
-            })
+            .send(ConversationTaskCommand::SendPoll { poll, response: tx })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn download<P: AsRef<Path>>(
+    pub async fn vote_poll(
         &self,
         conversation_id: Uuid,
         message_id: Uuid,
-        file: &str,
-        path: P,
-    ) -> Result<ConstellationProgressStream, Error> {
-        let path = path.as_ref().to_path_buf();
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -649,22 +846,16 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::DownloadAttachment {
+            .send(ConversationTaskCommand::VotePoll {
                 message_id,
-                file: file.to_owned(),
-                path,
+                options,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn download_stream(
-        &self,
-        conversation_id: Uuid,
-        message_id: Uuid,
-        file: &str,
-    ) -> Result<DownloadStream, Error> {
+    pub async fn close_poll(&self, conversation_id: Uuid, message_id: Uuid) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -674,20 +865,19 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::DownloadAttachmentStream {
+            .send(ConversationTaskCommand::ClosePoll {
                 message_id,
-                file: file.to_owned(),
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn send_event(
+    pub async fn poll_results(
         &self,
         conversation_id: Uuid,
-        event: MessageEvent,
-    ) -> Result<(), Error> {
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -697,18 +887,24 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::SendEvent {
-                event,
+            .send(ConversationTaskCommand::PollResults {
+                message_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn cancel_event(
+    pub async fn list_pinned(&self, conversation_id: Uuid) -> Result<Vec<PinnedMessage>, Error> {
+        let document = self.get(conversation_id).await?;
+        Ok(document.pinned_messages())
+    }
+
+    pub async fn set_draft(
         &self,
         conversation_id: Uuid,
-        event: MessageEvent,
+        lines: Vec<String>,
+        attachments: Vec<PathBuf>,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
@@ -719,19 +915,16 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::CancelEvent {
-                event,
+            .send(ConversationTaskCommand::SetDraft {
+                lines,
+                attachments,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn update_conversation_icon(
-        &self,
-        conversation_id: Uuid,
-        location: Location,
-    ) -> Result<(), Error> {
+    pub async fn get_draft(&self, conversation_id: Uuid) -> Result<Option<Draft>, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -741,19 +934,12 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::UpdateIcon {
-                location,
-                response: tx,
-            })
+            .send(ConversationTaskCommand::GetDraft { response: tx })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn update_conversation_banner(
-        &self,
-        conversation_id: Uuid,
-        location: Location,
-    ) -> Result<(), Error> {
+    pub async fn sync_status(&self, conversation_id: Uuid) -> Result<SyncStatus, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -763,18 +949,12 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::UpdateBanner {
-                location,
-                response: tx,
-            })
+            .send(ConversationTaskCommand::SyncStatus { response: tx })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn conversation_icon(
-        &mut self,
-        conversation_id: Uuid,
-    ) -> Result<ConversationImage, Error> {
+    pub async fn force_resync(&self, conversation_id: Uuid, peer: &DID) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -784,30 +964,95 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::GetIcon { response: tx })
+            .send(ConversationTaskCommand::ForceResync {
+                peer: peer.clone(),
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn conversation_banner(
-        &mut self,
+    pub async fn save_message(
+        &self,
         conversation_id: Uuid,
-    ) -> Result<ConversationImage, Error> {
+        message_id: Uuid,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        let note = note
+            .map(|note| note.trim().to_string())
+            .filter(|note| !note.is_empty());
+
+        if let Some(note) = note.as_ref() {
+            if note.chars().count() > MAX_SAVED_MESSAGE_NOTE_LENGTH {
+                return Err(Error::InvalidLength {
+                    context: "note".into(),
+                    current: note.chars().count(),
+                    minimum: None,
+                    maximum: Some(MAX_SAVED_MESSAGE_NOTE_LENGTH),
+                });
+            }
+        }
+
+        let message = self.get_message(conversation_id, message_id).await?;
+
+        let inner = &mut *self.inner.write().await;
+
+        let mut saved = inner.root.get_saved_messages().await?;
+
+        match saved.get_mut(&message_id) {
+            Some(entry) => {
+                *entry = SavedMessage::new(message, note, entry.saved());
+            }
+            None => {
+                if saved.len() >= MAX_SAVED_MESSAGES {
+                    return Err(Error::InvalidLength {
+                        context: "saved messages".into(),
+                        current: saved.len(),
+                        minimum: None,
+                        maximum: Some(MAX_SAVED_MESSAGES),
+                    });
+                }
+                saved.insert(message_id, SavedMessage::new(message, note, Utc::now()));
+            }
+        }
+
+        inner.root.set_saved_messages(saved).await?;
+        inner.identity.export_root_document().await
+    }
+
+    pub async fn unsave_message(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+
+        let mut saved = inner.root.get_saved_messages().await?;
+
+        if !saved
+            .get(&message_id)
+            .is_some_and(|entry| entry.conversation_id() == conversation_id)
+        {
+            return Err(Error::MessageNotFound);
+        }
+
+        saved.shift_remove(&message_id);
+
+        inner.root.set_saved_messages(saved).await?;
+        inner.identity.export_root_document().await
+    }
+
+    pub async fn list_saved_messages(&self) -> Result<Vec<SavedMessage>, Error> {
         let inner = &*self.inner.read().await;
-        let conversation_meta = inner
-            .conversation_task
-            .get(&conversation_id)
-            .ok_or(Error::InvalidConversation)?;
-        let (tx, rx) = oneshot::channel();
-        let _ = conversation_meta
-            .command_tx
-            .clone()
-            .send(ConversationTaskCommand::GetBanner { response: tx })
-            .await;
-        rx.await.map_err(anyhow::Error::from)?
+        let saved = inner.root.get_saved_messages().await?;
+        Ok(saved.into_values().rev().collect())
     }
 
-    pub async fn remove_conversation_icon(&self, conversation_id: Uuid) -> Result<(), Error> {
+    pub async fn list_attachments(
+        &self,
+        conversation_id: Uuid,
+        filter: AttachmentFilter,
+    ) -> Result<Vec<ConversationAttachment>, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -817,12 +1062,18 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::RemoveIcon { response: tx })
+            .send(ConversationTaskCommand::ListAttachments {
+                filter,
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn remove_conversation_banner(&self, conversation_id: Uuid) -> Result<(), Error> {
+    pub async fn list_quarantined_messages(
+        &self,
+        conversation_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -832,14 +1083,15 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::RemoveBanner { response: tx })
+            .send(ConversationTaskCommand::ListQuarantined { response: tx })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn set_description(
+
+    pub async fn release_quarantined_message(
         &self,
         conversation_id: Uuid,
-        desc: Option<&str>,
+        message_id: Uuid,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
@@ -850,14 +1102,21 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::SetDescription {
-                desc: desc.map(|s| s.to_string()),
+            .send(ConversationTaskCommand::ReleaseQuarantined {
+                message_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn archived_conversation(&self, conversation_id: Uuid) -> Result<(), Error> {
+
+    pub async fn react(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        state: ReactionState,
+        emoji: String,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -867,12 +1126,23 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::ArchivedConversation { response: tx })
+            .send(ConversationTaskCommand::ReactMessage {
+                message_id,
+                state,
+                emoji,
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn unarchived_conversation(&self, conversation_id: Uuid) -> Result<(), Error> {
+    pub async fn attach(
+        &self,
+        conversation_id: Uuid,
+        message_id: Option<Uuid>,
+        locations: Vec<Location>,
+        messages: Vec<String>,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
         let inner = &*self.inner.read().await;
         let conversation_meta = inner
             .conversation_task
@@ -882,39 +1152,899 @@ impl MessageStore {
         let _ = conversation_meta
             .command_tx
             .clone()
-            .send(ConversationTaskCommand::UnarchivedConversation { response: tx })
+            .send(ConversationTaskCommand::AttachMessage {
+                message_id,
+                locations,
+                lines: messages,
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-}
 
-impl MessageStore {
-    pub async fn create_community(&mut self, name: &str) -> Result<Community, Error> {
-        let inner = &mut *self.inner.write().await;
-        inner.create_community(name).await
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    pub async fn send_voice_note(
+        &self,
+        conversation_id: Uuid,
+        pcm: BoxStream<'static, Vec<f32>>,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+        let (data, voice_note) = crate::voice_note::encode(pcm).await?;
+
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::AttachVoiceNote {
+                data,
+                voice_note,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn delete_community(&mut self, community_id: Uuid) -> Result<(), Error> {
-        let inner = &mut *self.inner.write().await;
-        inner.delete_community(community_id).await
+
+    pub async fn download<P: AsRef<Path>>(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        file: &str,
+        path: P,
+    ) -> Result<ConstellationProgressStream, Error> {
+        let path = path.as_ref().to_path_buf();
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::DownloadAttachment {
+                message_id,
+                file: file.to_owned(),
+                path,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn get_community(&mut self, community_id: Uuid) -> Result<Community, Error> {
-        let inner = &mut *self.inner.write().await;
-        inner.get_community(community_id).await
+
+    pub async fn download_stream(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        file: &str,
+    ) -> Result<DownloadStream, Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::DownloadAttachmentStream {
+                message_id,
+                file: file.to_owned(),
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn list_communities_joined(&self) -> Result<IndexSet<Uuid>, Error> {
-        let inner = &mut *self.inner.write().await;
-        inner.list_communities_joined().await
+    pub async fn send_event(
+        &self,
+        conversation_id: Uuid,
+        event: MessageEvent,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::SendEvent {
+                event,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn list_communities_invited_to(&self) -> Result<Vec<(Uuid, CommunityInvite)>, Error> {
+
+    pub async fn cancel_event(
+        &self,
+        conversation_id: Uuid,
+        event: MessageEvent,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
-        Ok(inner
-            .community_invites
-            .iter()
-            .map(|(community_id, i)| (*community_id, CommunityInvite::from(i.clone())))
-            .collect())
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::CancelEvent {
+                event,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn leave_community(&mut self, community_id: Uuid) -> Result<(), Error> {
+
+    pub async fn typing_participants(&self, conversation_id: Uuid) -> Result<Vec<DID>, Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::TypingParticipants { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn update_conversation_icon(
+        &self,
+        conversation_id: Uuid,
+        location: Location,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::UpdateIcon {
+                location,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn update_conversation_banner(
+        &self,
+        conversation_id: Uuid,
+        location: Location,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::UpdateBanner {
+                location,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn conversation_icon(
+        &mut self,
+        conversation_id: Uuid,
+    ) -> Result<ConversationImage, Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::GetIcon { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn conversation_banner(
+        &mut self,
+        conversation_id: Uuid,
+    ) -> Result<ConversationImage, Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::GetBanner { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn remove_conversation_icon(&self, conversation_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::RemoveIcon { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn remove_conversation_banner(&self, conversation_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::RemoveBanner { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn set_description(
+        &self,
+        conversation_id: Uuid,
+        desc: Option<&str>,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::SetDescription {
+                desc: desc.map(|s| s.to_string()),
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn archived_conversation(&self, conversation_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::ArchivedConversation { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn unarchived_conversation(&self, conversation_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let conversation_meta = inner
+            .conversation_task
+            .get(&conversation_id)
+            .ok_or(Error::InvalidConversation)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = conversation_meta
+            .command_tx
+            .clone()
+            .send(ConversationTaskCommand::UnarchivedConversation { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+}
+
+impl MessageStore {
+    pub async fn create_community(&mut self, name: &str) -> Result<Community, Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.create_community(name).await
+    }
+    pub async fn delete_community(&mut self, community_id: Uuid) -> Result<(), Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.delete_community(community_id).await
+    }
+    pub async fn get_community(&mut self, community_id: Uuid) -> Result<Community, Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.get_community(community_id).await
+    }
+
+    pub async fn list_communities_joined(&self) -> Result<IndexSet<Uuid>, Error> {
+        let inner = &mut *self.inner.write().await;
+        inner.list_communities_joined().await
+    }
+    pub async fn list_communities_invited_to(&self) -> Result<Vec<(Uuid, CommunityInvite)>, Error> {
+        let inner = &*self.inner.read().await;
+        Ok(inner
+            .community_invites
+            .iter()
+            .map(|(community_id, i)| (*community_id, CommunityInvite::from(i.clone())))
+            .collect())
+    }
+    pub async fn search_public_communities(
+        &self,
+        query: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommunityListing>, Error> {
+        let inner = &*self.inner.read().await;
+        let request = CommunityDirectory::Search {
+            query: query.map(str::to_string),
+            skip,
+            limit,
+        };
+        match community_task::community_directory_request(
+            &inner.ipfs,
+            inner.root.keypair(),
+            &inner.discovery,
+            request,
+        )
+        .await?
+        {
+            ShuttleMessageResponse::CommunityListings { listings } => {
+                // Listings that are not signed by the owner of the community are skipped
+                Ok(listings
+                    .into_iter()
+                    .filter(|listing| listing.verify().is_ok())
+                    .map(CommunityListing::from)
+                    .collect())
+            }
+            _ => Err(Error::OtherWithContext(
+                "response from shuttle node was invalid".into(),
+            )),
+        }
+    }
+    pub async fn leave_community(&mut self, community_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::LeaveCommunity { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn get_community_icon(&self, community_id: Uuid) -> Result<ConversationImage, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::GetCommunityIcon { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn get_community_banner(
+        &self,
+        community_id: Uuid,
+    ) -> Result<ConversationImage, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::GetCommunityBanner { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_icon(
+        &mut self,
+        community_id: Uuid,
+        location: Location,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityIcon {
+                location,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_banner(
+        &mut self,
+        community_id: Uuid,
+        location: Location,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityBanner {
+                location,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn set_community_listing(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::SetCommunityListing {
+                invite_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn create_community_invite(
+        &mut self,
+        community_id: Uuid,
+        target_user: Option<DID>,
+        expiry: Option<DateTime<Utc>>,
+    ) -> Result<CommunityInvite, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::CreateCommunityInvite {
+                target_user,
+                expiry,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn delete_community_invite(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Uuid,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        match inner.community_task.get(&community_id) {
+            None => {
+                let keypair = inner.root.keypair();
+
+                let event = CommunityJoinEvents::DeleteInvite { invite_id };
+                let payload = PayloadBuilder::new(keypair, event)
+                    .from_ipfs(&inner.ipfs)
+                    .await?;
+                let bytes = payload.to_bytes()?;
+
+                if let Err(e) = inner
+                    .ipfs
+                    .pubsub_publish(community_id.join_topic(), bytes)
+                    .await
+                {
+                    tracing::error!(id=%community_id, "Unable to send event: {e}");
+                }
+            }
+            Some(community_meta) => {
+                let (tx, rx) = oneshot::channel();
+                let _ = community_meta
+                    .command_tx
+                    .clone()
+                    .send(CommunityTaskCommand::DeleteCommunityInvite {
+                        invite_id,
+                        response: tx,
+                    })
+                    .await;
+                return rx.await.map_err(anyhow::Error::from)?;
+            }
+        }
+        Ok(())
+    }
+    pub async fn get_community_invite(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Uuid,
+    ) -> Result<CommunityInvite, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::GetCommunityInvite {
+                invite_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn list_community_invites(
+        &mut self,
+        community_id: Uuid,
+    ) -> Result<Vec<CommunityInvite>, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::ListCommunityInvites { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn request_join_community(&mut self, community_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let keypair = inner.root.keypair();
+
+        let event = CommunityJoinEvents::Join;
+        let payload = PayloadBuilder::new(keypair, event)
+            .from_ipfs(&inner.ipfs)
+            .await?;
+        let bytes = payload.to_bytes()?;
+
+        if let Err(e) = inner
+            .ipfs
+            .pubsub_publish(community_id.join_topic(), bytes)
+            .await
+        {
+            tracing::error!(id=%community_id, "Unable to send event: {e}");
+        }
+        Ok(())
+    }
+    pub async fn edit_community_invite(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Uuid,
+        invite: CommunityInvite,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityInvite {
+                invite_id,
+                invite,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn create_community_role(
+        &mut self,
+        community_id: Uuid,
+        name: &str,
+    ) -> Result<CommunityRole, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::CreateCommunityRole {
+                name: name.to_string(),
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn delete_community_role(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::DeleteCommunityRole {
+                role_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn get_community_role(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+    ) -> Result<CommunityRole, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::GetCommunityRole {
+                role_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_role_name(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        new_name: String,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityRoleName {
+                role_id,
+                new_name,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_role_position(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        position: u32,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityRolePosition {
+                role_id,
+                position,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_role_color(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        color: Option<u32>,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityRoleColor {
+                role_id,
+                color,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_role_hoisted(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        hoisted: bool,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityRoleHoisted {
+                role_id,
+                hoisted,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn edit_community_role_mentionable(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        mentionable: bool,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityRoleMentionable {
+                role_id,
+                mentionable,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn grant_community_role(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        user: DID,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::GrantCommunityRole {
+                role_id,
+                user,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn revoke_community_role(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        user: DID,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::RevokeCommunityRole {
+                role_id,
+                user,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn create_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_name: &str,
+        channel_type: CommunityChannelType,
+    ) -> Result<CommunityChannel, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::CreateCommunityChannel {
+                channel_name: channel_name.to_string(),
+                channel_type,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn delete_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::DeleteCommunityChannel {
+                channel_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn get_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<CommunityChannel, Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -924,12 +2054,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::LeaveCommunity { response: tx })
+            .send(CommunityTaskCommand::GetCommunityChannel {
+                channel_id,
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-
-    pub async fn get_community_icon(&self, community_id: Uuid) -> Result<ConversationImage, Error> {
+    pub async fn reorder_community_channels(
+        &mut self,
+        community_id: Uuid,
+        channels: Vec<Uuid>,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -939,14 +2075,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GetCommunityIcon { response: tx })
+            .send(CommunityTaskCommand::ReorderCommunityChannels {
+                channels,
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn get_community_banner(
-        &self,
+    pub async fn create_community_channel_category(
+        &mut self,
         community_id: Uuid,
-    ) -> Result<ConversationImage, Error> {
+        name: &str,
+    ) -> Result<CommunityChannelCategory, Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -956,14 +2096,17 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GetCommunityBanner { response: tx })
+            .send(CommunityTaskCommand::CreateCommunityChannelCategory {
+                name: name.to_string(),
+                response: tx,
+            })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn edit_community_icon(
+    pub async fn delete_community_channel_category(
         &mut self,
         community_id: Uuid,
-        location: Location,
+        category_id: Uuid,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -974,18 +2117,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityIcon {
-                location,
+            .send(CommunityTaskCommand::DeleteCommunityChannelCategory {
+                category_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn edit_community_banner(
-        &mut self,
+    pub async fn get_community_channel_category(
+        &self,
         community_id: Uuid,
-        location: Location,
-    ) -> Result<(), Error> {
+        category_id: Uuid,
+    ) -> Result<CommunityChannelCategory, Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -995,20 +2138,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityBanner {
-                location,
+            .send(CommunityTaskCommand::GetCommunityChannelCategory {
+                category_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-
-    pub async fn create_community_invite(
+    pub async fn move_channel_to_category(
         &mut self,
         community_id: Uuid,
-        target_user: Option<DID>,
-        expiry: Option<DateTime<Utc>>,
-    ) -> Result<CommunityInvite, Error> {
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1018,58 +2160,41 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::CreateCommunityInvite {
-                target_user,
-                expiry,
+            .send(CommunityTaskCommand::MoveChannelToCategory {
+                channel_id,
+                category_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn delete_community_invite(
+
+    pub async fn edit_community_name(
         &mut self,
         community_id: Uuid,
-        invite_id: Uuid,
+        name: &str,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
-        match inner.community_task.get(&community_id) {
-            None => {
-                let keypair = inner.root.keypair();
-
-                let event = CommunityJoinEvents::DeleteInvite { invite_id };
-                let payload = PayloadBuilder::new(keypair, event)
-                    .from_ipfs(&inner.ipfs)
-                    .await?;
-                let bytes = payload.to_bytes()?;
-
-                if let Err(e) = inner
-                    .ipfs
-                    .pubsub_publish(community_id.join_topic(), bytes)
-                    .await
-                {
-                    tracing::error!(id=%community_id, "Unable to send event: {e}");
-                }
-            }
-            Some(community_meta) => {
-                let (tx, rx) = oneshot::channel();
-                let _ = community_meta
-                    .command_tx
-                    .clone()
-                    .send(CommunityTaskCommand::DeleteCommunityInvite {
-                        invite_id,
-                        response: tx,
-                    })
-                    .await;
-                return rx.await.map_err(anyhow::Error::from)?;
-            }
-        }
-        Ok(())
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::EditCommunityName {
+                name: name.to_string(),
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn get_community_invite(
+    pub async fn edit_community_description(
         &mut self,
         community_id: Uuid,
-        invite_id: Uuid,
-    ) -> Result<CommunityInvite, Error> {
+        description: Option<String>,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1079,37 +2204,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GetCommunityInvite {
-                invite_id,
+            .send(CommunityTaskCommand::EditCommunityDescription {
+                description,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn request_join_community(&mut self, community_id: Uuid) -> Result<(), Error> {
-        let inner = &*self.inner.read().await;
-        let keypair = inner.root.keypair();
-
-        let event = CommunityJoinEvents::Join;
-        let payload = PayloadBuilder::new(keypair, event)
-            .from_ipfs(&inner.ipfs)
-            .await?;
-        let bytes = payload.to_bytes()?;
-
-        if let Err(e) = inner
-            .ipfs
-            .pubsub_publish(community_id.join_topic(), bytes)
-            .await
-        {
-            tracing::error!(id=%community_id, "Unable to send event: {e}");
-        }
-        Ok(())
-    }
-    pub async fn edit_community_invite(
+    pub async fn grant_community_permission(
         &mut self,
         community_id: Uuid,
-        invite_id: Uuid,
-        invite: CommunityInvite,
+        permission: CommunityPermission,
+        role_id: RoleId,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1120,20 +2226,20 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityInvite {
-                invite_id,
-                invite,
+            .send(CommunityTaskCommand::GrantCommunityPermission {
+                permission,
+                role_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-
-    pub async fn create_community_role(
+    pub async fn revoke_community_permission(
         &mut self,
         community_id: Uuid,
-        name: &str,
-    ) -> Result<CommunityRole, Error> {
+        permission: CommunityPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1143,17 +2249,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::CreateCommunityRole {
-                name: name.to_string(),
+            .send(CommunityTaskCommand::RevokeCommunityPermission {
+                permission,
+                role_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn delete_community_role(
+    pub async fn grant_community_permission_for_all(
         &mut self,
         community_id: Uuid,
-        role_id: RoleId,
+        permission: CommunityPermission,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1164,18 +2271,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::DeleteCommunityRole {
-                role_id,
+            .send(CommunityTaskCommand::GrantCommunityPermissionForAll {
+                permission,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn get_community_role(
+    pub async fn revoke_community_permission_for_all(
         &mut self,
         community_id: Uuid,
-        role_id: RoleId,
-    ) -> Result<CommunityRole, Error> {
+        permission: CommunityPermission,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1185,18 +2292,17 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GetCommunityRole {
-                role_id,
+            .send(CommunityTaskCommand::RevokeCommunityPermissionForAll {
+                permission,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn edit_community_role_name(
+    pub async fn remove_community_member(
         &mut self,
         community_id: Uuid,
-        role_id: RoleId,
-        new_name: String,
+        member: DID,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1207,19 +2313,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityRoleName {
-                role_id,
-                new_name,
+            .send(CommunityTaskCommand::RemoveCommunityMember {
+                member,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn grant_community_role(
+    pub async fn ban_community_member(
         &mut self,
         community_id: Uuid,
-        role_id: RoleId,
-        user: DID,
+        member: DID,
+        reason: Option<String>,
+        expiry: Option<DateTime<Utc>>,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1230,19 +2336,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GrantCommunityRole {
-                role_id,
-                user,
+            .send(CommunityTaskCommand::BanCommunityMember {
+                member,
+                reason,
+                expiry,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn revoke_community_role(
+    pub async fn unban_community_member(
         &mut self,
         community_id: Uuid,
-        role_id: RoleId,
-        user: DID,
+        member: DID,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1253,21 +2359,36 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::RevokeCommunityRole {
-                role_id,
-                user,
+            .send(CommunityTaskCommand::UnbanCommunityMember {
+                member,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-
-    pub async fn create_community_channel(
+    pub async fn list_community_bans(
+        &self,
+        community_id: Uuid,
+    ) -> Result<Vec<CommunityBan>, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::ListCommunityBans { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn timeout_community_member(
         &mut self,
         community_id: Uuid,
-        channel_name: &str,
-        channel_type: CommunityChannelType,
-    ) -> Result<CommunityChannel, Error> {
+        member: DID,
+        until: DateTime<Utc>,
+    ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1277,18 +2398,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::CreateCommunityChannel {
-                channel_name: channel_name.to_string(),
-                channel_type,
+            .send(CommunityTaskCommand::TimeoutCommunityMember {
+                member,
+                until,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn delete_community_channel(
+
+    pub async fn transfer_community_ownership(
         &mut self,
         community_id: Uuid,
-        channel_id: Uuid,
+        new_owner: DID,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1299,18 +2421,33 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::DeleteCommunityChannel {
-                channel_id,
+            .send(CommunityTaskCommand::TransferCommunityOwnership {
+                new_owner,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn get_community_channel(
-        &mut self,
+    pub async fn accept_community_ownership(&mut self, community_id: Uuid) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::AcceptCommunityOwnership { response: tx })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+
+    pub async fn get_community_audit_log(
+        &self,
         community_id: Uuid,
-        channel_id: Uuid,
-    ) -> Result<CommunityChannel, Error> {
+        options: CommunityAuditLogOptions,
+    ) -> Result<Vec<CommunityAuditLogEntry>, Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1320,17 +2457,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GetCommunityChannel {
-                channel_id,
+            .send(CommunityTaskCommand::GetCommunityAuditLog {
+                options,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
 
-    pub async fn edit_community_name(
+    pub async fn edit_community_channel_name(
         &mut self,
         community_id: Uuid,
+        channel_id: Uuid,
         name: &str,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
@@ -1342,16 +2480,18 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityName {
+            .send(CommunityTaskCommand::EditCommunityChannelName {
+                channel_id,
                 name: name.to_string(),
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn edit_community_description(
+    pub async fn edit_community_channel_description(
         &mut self,
         community_id: Uuid,
+        channel_id: Uuid,
         description: Option<String>,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
@@ -1363,18 +2503,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityDescription {
+            .send(CommunityTaskCommand::EditCommunityChannelDescription {
+                channel_id,
                 description,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn grant_community_permission(
+    pub async fn edit_community_channel_slow_mode(
         &mut self,
         community_id: Uuid,
-        permission: CommunityPermission,
-        role_id: RoleId,
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1385,20 +2526,30 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GrantCommunityPermission {
-                permission,
-                role_id,
+            .send(CommunityTaskCommand::EditCommunityChannelSlowMode {
+                channel_id,
+                slow_mode,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn revoke_community_permission(
+    pub async fn follow_community_channel(
         &mut self,
         community_id: Uuid,
-        permission: CommunityPermission,
-        role_id: RoleId,
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
     ) -> Result<(), Error> {
+        // Copies are only cross-posted from announcement channels we receive, so we have to be a
+        // member of the source community that is able to view the channel
+        let source_channel = self
+            .get_community_channel(source_community_id, source_channel_id)
+            .await?;
+        if source_channel.channel_type() != CommunityChannelType::Announcement {
+            return Err(Error::InvalidCommunityChannelType);
+        }
+
         let inner = &*self.inner.read().await;
         let community_meta = inner
             .community_task
@@ -1408,18 +2559,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::RevokeCommunityPermission {
-                permission,
-                role_id,
+            .send(CommunityTaskCommand::FollowCommunityChannel {
+                channel_id,
+                source_community_id,
+                source_channel_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn grant_community_permission_for_all(
+    pub async fn unfollow_community_channel(
         &mut self,
         community_id: Uuid,
-        permission: CommunityPermission,
+        channel_id: Uuid,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1430,17 +2582,19 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GrantCommunityPermissionForAll {
-                permission,
+            .send(CommunityTaskCommand::UnfollowCommunityChannel {
+                channel_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn revoke_community_permission_for_all(
+    pub async fn grant_community_channel_permission(
         &mut self,
         community_id: Uuid,
-        permission: CommunityPermission,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1451,17 +2605,21 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::RevokeCommunityPermissionForAll {
+            .send(CommunityTaskCommand::GrantCommunityChannelPermission {
+                channel_id,
                 permission,
+                role_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn remove_community_member(
+    pub async fn revoke_community_channel_permission(
         &mut self,
         community_id: Uuid,
-        member: DID,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1472,19 +2630,20 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::RemoveCommunityMember {
-                member,
+            .send(CommunityTaskCommand::RevokeCommunityChannelPermission {
+                channel_id,
+                permission,
+                role_id,
                 response: tx,
             })
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-
-    pub async fn edit_community_channel_name(
+    pub async fn grant_community_channel_permission_for_all(
         &mut self,
         community_id: Uuid,
         channel_id: Uuid,
-        name: &str,
+        permission: CommunityChannelPermission,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1495,19 +2654,21 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityChannelName {
-                channel_id,
-                name: name.to_string(),
-                response: tx,
-            })
+            .send(
+                CommunityTaskCommand::GrantCommunityChannelPermissionForAll {
+                    channel_id,
+                    permission,
+                    response: tx,
+                },
+            )
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn edit_community_channel_description(
+    pub async fn revoke_community_channel_permission_for_all(
         &mut self,
         community_id: Uuid,
         channel_id: Uuid,
-        description: Option<String>,
+        permission: CommunityChannelPermission,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
         let community_meta = inner
@@ -1518,18 +2679,21 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::EditCommunityChannelDescription {
-                channel_id,
-                description,
-                response: tx,
-            })
+            .send(
+                CommunityTaskCommand::RevokeCommunityChannelPermissionForAll {
+                    channel_id,
+                    permission,
+                    response: tx,
+                },
+            )
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn grant_community_channel_permission(
+
+    pub async fn grant_community_channel_category_permission(
         &mut self,
         community_id: Uuid,
-        channel_id: Uuid,
+        category_id: Uuid,
         permission: CommunityChannelPermission,
         role_id: RoleId,
     ) -> Result<(), Error> {
@@ -1542,19 +2706,21 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::GrantCommunityChannelPermission {
-                channel_id,
-                permission,
-                role_id,
-                response: tx,
-            })
+            .send(
+                CommunityTaskCommand::GrantCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                    response: tx,
+                },
+            )
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn revoke_community_channel_permission(
+    pub async fn revoke_community_channel_category_permission(
         &mut self,
         community_id: Uuid,
-        channel_id: Uuid,
+        category_id: Uuid,
         permission: CommunityChannelPermission,
         role_id: RoleId,
     ) -> Result<(), Error> {
@@ -1567,19 +2733,21 @@ impl MessageStore {
         let _ = community_meta
             .command_tx
             .clone()
-            .send(CommunityTaskCommand::RevokeCommunityChannelPermission {
-                channel_id,
-                permission,
-                role_id,
-                response: tx,
-            })
+            .send(
+                CommunityTaskCommand::RevokeCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                    response: tx,
+                },
+            )
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn grant_community_channel_permission_for_all(
+    pub async fn grant_community_channel_category_permission_for_all(
         &mut self,
         community_id: Uuid,
-        channel_id: Uuid,
+        category_id: Uuid,
         permission: CommunityChannelPermission,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
@@ -1592,8 +2760,8 @@ impl MessageStore {
             .command_tx
             .clone()
             .send(
-                CommunityTaskCommand::GrantCommunityChannelPermissionForAll {
-                    channel_id,
+                CommunityTaskCommand::GrantCommunityChannelCategoryPermissionForAll {
+                    category_id,
                     permission,
                     response: tx,
                 },
@@ -1601,10 +2769,10 @@ impl MessageStore {
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
-    pub async fn revoke_community_channel_permission_for_all(
+    pub async fn revoke_community_channel_category_permission_for_all(
         &mut self,
         community_id: Uuid,
-        channel_id: Uuid,
+        category_id: Uuid,
         permission: CommunityChannelPermission,
     ) -> Result<(), Error> {
         let inner = &*self.inner.read().await;
@@ -1617,8 +2785,8 @@ impl MessageStore {
             .command_tx
             .clone()
             .send(
-                CommunityTaskCommand::RevokeCommunityChannelPermissionForAll {
-                    channel_id,
+                CommunityTaskCommand::RevokeCommunityChannelCategoryPermissionForAll {
+                    category_id,
                     permission,
                     response: tx,
                 },
@@ -1859,6 +3027,54 @@ impl MessageStore {
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
+    pub async fn list_quarantined_community_channel_messages(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(
+                CommunityTaskCommand::ListQuarantinedCommunityChannelMessages {
+                    channel_id,
+                    response: tx,
+                },
+            )
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn release_quarantined_community_channel_message(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(
+                CommunityTaskCommand::ReleaseQuarantinedCommunityChannelMessage {
+                    channel_id,
+                    message_id,
+                    response: tx,
+                },
+            )
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
     pub async fn pin_community_channel_message(
         &mut self,
         community_id: Uuid,
@@ -1911,6 +3127,100 @@ impl MessageStore {
             .await;
         rx.await.map_err(anyhow::Error::from)?
     }
+    pub async fn send_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        poll: Poll,
+    ) -> Result<Uuid, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::SendCommunityChannelPoll {
+                channel_id,
+                poll,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn vote_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::VoteCommunityChannelPoll {
+                channel_id,
+                message_id,
+                options,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn close_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::CloseCommunityChannelPoll {
+                channel_id,
+                message_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
+    pub async fn community_channel_poll_results(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        let inner = &*self.inner.read().await;
+        let community_meta = inner
+            .community_task
+            .get(&community_id)
+            .ok_or(Error::InvalidCommunity)?;
+        let (tx, rx) = oneshot::channel();
+        let _ = community_meta
+            .command_tx
+            .clone()
+            .send(CommunityTaskCommand::CommunityChannelPollResults {
+                channel_id,
+                message_id,
+                response: tx,
+            })
+            .await;
+        rx.await.map_err(anyhow::Error::from)?
+    }
     pub async fn send_community_channel_messsage_event(
         &mut self,
         community_id: Uuid,
@@ -2044,10 +3354,11 @@ struct ConversationTask {
     inner: Arc<tokio::sync::RwLock<ConversationInner>>,
     ipfs: Ipfs,
     identity: IdentityStore,
+    crosspost_rx: mpsc::Receiver<CommunityCrossPost>,
 }
 
 impl ConversationTask {
-    async fn run(self) {
+    async fn run(mut self) {
         let mut identity_stream = self
             .identity
             .subscribe()
@@ -2103,6 +3414,22 @@ impl ConversationTask {
                         tracing::error!(%sender, error = %e, "error processing conversation");
                     }
                 }
+                Some(crosspost) = self.crosspost_rx.next() => {
+                    // Every community task is notified, since a channel in any community, including the
+                    // announcement channel's own, may be following it
+                    let inner = &*self.inner.read().await;
+                    for community_meta in inner.community_task.values() {
+                        let _ = community_meta
+                            .command_tx
+                            .clone()
+                            .send(CommunityTaskCommand::CrossPostCommunityMessage {
+                                source_community_id: crosspost.community_id,
+                                source_channel_id: crosspost.channel_id,
+                                lines: crosspost.lines.clone(),
+                            })
+                            .await;
+                    }
+                }
                 _ = &mut queue_timer => {
                     let _ = _process_queue(&mut *self.inner.write().await).await;
                     queue_timer.reset(Duration::from_secs(5));
@@ -2127,9 +3454,12 @@ struct CommunityInnerMeta {
 
 struct ConversationInner {
     ipfs: Ipfs,
+    config: config::Config,
+    message_filter: Option<MessageFilterHandle>,
     conversation_task: HashMap<Uuid, ConversationInnerMeta>,
     community_task: HashMap<Uuid, CommunityInnerMeta>,
     community_invites: Vec<(Uuid, CommunityInviteDocument)>,
+    crosspost_tx: mpsc::Sender<CommunityCrossPost>,
     root: RootDocumentMap,
     file: FileStore,
     event: EventSubscription<RayGunEventKind>,
@@ -2193,6 +3523,8 @@ impl ConversationInner {
         let task = task::ConversationTask::new(
             conversation_id,
             &self.ipfs,
+            &self.config,
+            self.message_filter.clone(),
             &self.root,
             &self.identity,
             &self.file,
@@ -2448,6 +3780,19 @@ impl ConversationInner {
         meta.command_tx.close_channel();
         meta.handle.abort();
 
+        if let Ok(mut labels) = self.root.get_conversation_labels().await {
+            let len = labels.len();
+            labels.iter_mut().for_each(|(_, ids)| {
+                ids.remove(&id);
+            });
+            labels.retain(|_, ids| !ids.is_empty());
+            if labels.len() != len {
+                if let Err(e) = self.root.set_conversation_labels(labels).await {
+                    tracing::warn!(conversation_id = %id, error = %e, "unable to remove conversation from labels");
+                }
+            }
+        }
+
         Ok(conversation)
     }
 
@@ -2880,11 +4225,13 @@ impl ConversationInner {
         let task = community_task::CommunityTask::new(
             community_id,
             &self.ipfs,
+            self.message_filter.clone(),
             &self.root,
             &self.identity,
             &self.file,
             &self.discovery,
             crx,
+            self.crosspost_tx.clone(),
             self.event.clone(),
         )
         .await?;
@@ -2942,12 +4289,12 @@ impl ConversationInner {
 impl ConversationInner {
     pub async fn create_community(&mut self, name: &str) -> Result<Community, Error> {
         let name = name.trim();
-        if name.is_empty() || name.len() > 255 {
+        if name.is_empty() || name.len() > MAX_COMMUNITY_NAME_LENGTH {
             return Err(Error::InvalidLength {
                 context: "name".into(),
                 current: name.len(),
                 minimum: Some(1),
-                maximum: Some(255),
+                maximum: Some(MAX_COMMUNITY_NAME_LENGTH),
             });
         }
 
@@ -3130,8 +4477,11 @@ async fn process_conversation(
 
             //TODO: Resolve message list
             conversation.messages = None;
+            conversation.attachments = None;
             conversation.archived = false;
             conversation.favorite = false;
+            conversation.last_read = None;
+            conversation.clock = Default::default();
 
             this.set_document(conversation).await?;
 
@@ -19,7 +19,9 @@ use warp::constellation::file::File;
 use warp::constellation::{ConstellationProgressStream, Progression};
 use warp::crypto::DID;
 use warp::error::Error;
-use warp::raygun::{AttachmentKind, Location, LocationKind, MessageType};
+use warp::raygun::{
+    AttachmentKind, HybridTimestamp, Location, LocationKind, MessageType, VoiceNote,
+};
 
 type AOneShot = (MessageDocument, oneshot::Sender<Result<(), Error>>);
 type ProgressedStream = BoxStream<'static, (LocationKind, Progression, Option<File>)>;
@@ -33,6 +35,8 @@ pub struct AttachmentStream {
     locations: Vec<Location>,
     directory: Directory,
     lines: Option<Vec<String>>,
+    voice_note: Option<VoiceNote>,
+    clock: Option<HybridTimestamp>,
     keystore: Either<DID, Keystore>,
     file_store: FileStore,
     state: AttachmentState,
@@ -70,6 +74,8 @@ impl AttachmentStream {
             reply_to: None,
             locations: Vec::new(),
             lines: None,
+            voice_note: None,
+            clock: None,
             state: AttachmentState::Initialize,
             progressed: Some(SelectAll::new()),
             successful_attachment: Vec::new(),
@@ -162,6 +168,17 @@ impl AttachmentStream {
         self.reply_to = message_id.into();
         self
     }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    pub fn set_voice_note(mut self, voice_note: VoiceNote) -> Self {
+        self.voice_note = Some(voice_note);
+        self
+    }
+
+    pub fn set_clock(mut self, clock: HybridTimestamp) -> Self {
+        self.clock = Some(clock);
+        self
+    }
 }
 
 impl Stream for AttachmentStream {
@@ -449,6 +466,8 @@ impl Stream for AttachmentStream {
                             }
                             let attachments = std::mem::take(&mut this.successful_attachment);
                             let messages = std::mem::take(&mut this.lines);
+                            let voice_note = this.voice_note.take();
+                            let clock = this.clock;
                             let reply_id = this.reply_to;
                             let message_id = this.message_id;
                             let local_did = this.local_did.clone();
@@ -468,6 +487,14 @@ impl Stream for AttachmentStream {
                                     message_builder = message_builder.set_message(messages)?;
                                 }
 
+                                if let Some(voice_note) = voice_note {
+                                    message_builder = message_builder.set_voice_note(voice_note)?;
+                                }
+
+                                if let Some(clock) = clock {
+                                    message_builder = message_builder.set_clock(clock);
+                                }
+
                                 for file in attachments {
                                     message_builder = message_builder.add_attachment(file)?;
                                 }
@@ -8,11 +8,11 @@ use futures_timer::Delay;
 use indexmap::{IndexMap, IndexSet};
 use ipld_core::cid::Cid;
 use rust_ipfs::libp2p::gossipsub::Message;
-use rust_ipfs::{Ipfs, IpfsPath};
+use rust_ipfs::{Ipfs, IpfsPath, Keypair};
 use rust_ipfs::{PeerId, SubscriptionStream};
 use serde::{Deserialize, Serialize};
 use std::borrow::BorrowMut;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -23,21 +23,34 @@ use uuid::Uuid;
 use warp::constellation::ConstellationProgressStream;
 use warp::crypto::DID;
 use warp::raygun::community::{
-    CommunityChannel, CommunityChannelPermission, CommunityChannelType, CommunityInvite,
-    CommunityPermission, CommunityRole, RoleId,
+    CommunityAuditAction, CommunityAuditLogEntry, CommunityAuditLogOptions, CommunityBan,
+    CommunityChannel, CommunityChannelCategory, CommunityChannelPermission, CommunityChannelType,
+    CommunityInvite, CommunityPermission, CommunityRole, RoleId,
 };
 use warp::raygun::{
     AttachmentEventStream, ConversationImage, Location, MessageEvent, MessageOptions,
-    MessageReference, MessageStatus, MessageType, Messages, MessagesType, PinState,
-    RayGunEventKind, ReactionState,
+    MessageReference, MessageStatus, MessageType, Messages, MessagesType, PinState, PollResults,
+    QuarantinedMessage, RayGunEventKind, ReactionState,
 };
 use warp::{crypto::generate, error::Error, raygun::MessageEventKind};
 use web_time::Instant;
 
+use crate::config;
+use crate::filter::{
+    self, FilterKind, FilterSource, MessageFilterHandle, Quarantine, QuarantineEntry,
+};
+use crate::shuttle::message::protocol::{
+    CommunityDirectory, Request as ShuttleMessageRequest, Response as ShuttleMessageResponse,
+};
 use crate::store::community::{
-    CommunityChannelDocument, CommunityDocument, CommunityInviteDocument, CommunityRoleDocument,
+    slow_mode_date, slow_mode_elapsed, CommunityAuditLogEntryDocument, CommunityBanDocument,
+    CommunityChannelCategoryDocument, CommunityChannelDocument, CommunityChannelFollowDocument,
+    CommunityDocument, CommunityInviteDocument, CommunityListingDocument,
+    CommunityOwnershipOfferDocument, CommunityRoleDocument,
+};
+use crate::store::conversation::message::{
+    MessageDocument, MessageDocumentBuilder, PollVoteDocument,
 };
-use crate::store::conversation::message::{MessageDocument, MessageDocumentBuilder};
 use crate::store::discovery::Discovery;
 use crate::store::document::files::FileDocument;
 use crate::store::document::image_dag::ImageDag;
@@ -46,8 +59,10 @@ use crate::store::event_subscription::EventSubscription;
 use crate::store::topics::PeerTopic;
 use crate::store::{
     CommunityJoinEvents, CommunityUpdateKind, ConversationEvents, ConversationImageType,
-    MAX_COMMUNITY_CHANNELS, MAX_COMMUNITY_DESCRIPTION, MAX_CONVERSATION_BANNER_SIZE,
-    MAX_CONVERSATION_ICON_SIZE, MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE,
+    MAX_COMMUNITY_AUDIT_LOG_CATCH_UP, MAX_COMMUNITY_BAN_REASON_LENGTH, MAX_COMMUNITY_CHANNELS,
+    MAX_COMMUNITY_CHANNEL_CATEGORIES, MAX_COMMUNITY_DESCRIPTION, MAX_COMMUNITY_NAME_LENGTH,
+    MAX_CONVERSATION_BANNER_SIZE, MAX_CONVERSATION_ICON_SIZE, MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE,
+    SLOW_MODE_SKEW,
 };
 use crate::utils::{ByteCollection, ExtensionType};
 use crate::{
@@ -59,7 +74,7 @@ use crate::{
         identity::IdentityStore,
         keystore::Keystore,
         payload::{PayloadBuilder, PayloadMessage},
-        CommunityMessagingEvents, ConversationRequestKind, ConversationRequestResponse,
+        protocols, CommunityMessagingEvents, ConversationRequestKind, ConversationRequestResponse,
         ConversationResponseKind, DidExt, PeerIdExt,
     },
 };
@@ -68,6 +83,15 @@ use super::attachment::AttachmentStream;
 
 type AttachmentOneshot = (MessageDocument, oneshot::Sender<Result<(), Error>>);
 
+/// New message in an announcement channel, handed to the message store so that it can be
+/// cross-posted into the channels following it
+#[derive(Debug)]
+pub struct CommunityCrossPost {
+    pub community_id: Uuid,
+    pub channel_id: Uuid,
+    pub lines: Vec<String>,
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub enum CommunityTaskCommand {
@@ -88,6 +112,10 @@ pub enum CommunityTaskCommand {
         location: Location,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    SetCommunityListing {
+        invite_id: Option<Uuid>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     CreateCommunityInvite {
         target_user: Option<DID>,
         expiry: Option<DateTime<Utc>>,
@@ -101,6 +129,9 @@ pub enum CommunityTaskCommand {
         invite_id: Uuid,
         response: oneshot::Sender<Result<CommunityInvite, Error>>,
     },
+    ListCommunityInvites {
+        response: oneshot::Sender<Result<Vec<CommunityInvite>, Error>>,
+    },
     EditCommunityInvite {
         invite_id: Uuid,
         invite: CommunityInvite,
@@ -123,6 +154,26 @@ pub enum CommunityTaskCommand {
         new_name: String,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    EditCommunityRolePosition {
+        role_id: RoleId,
+        position: u32,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    EditCommunityRoleColor {
+        role_id: RoleId,
+        color: Option<u32>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    EditCommunityRoleHoisted {
+        role_id: RoleId,
+        hoisted: bool,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    EditCommunityRoleMentionable {
+        role_id: RoleId,
+        mentionable: bool,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     GrantCommunityRole {
         role_id: RoleId,
         user: DID,
@@ -146,6 +197,49 @@ pub enum CommunityTaskCommand {
         channel_id: Uuid,
         response: oneshot::Sender<Result<CommunityChannel, Error>>,
     },
+    ReorderCommunityChannels {
+        channels: Vec<Uuid>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    CreateCommunityChannelCategory {
+        name: String,
+        response: oneshot::Sender<Result<CommunityChannelCategory, Error>>,
+    },
+    DeleteCommunityChannelCategory {
+        category_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    GetCommunityChannelCategory {
+        category_id: Uuid,
+        response: oneshot::Sender<Result<CommunityChannelCategory, Error>>,
+    },
+    MoveChannelToCategory {
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    GrantCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    RevokeCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    GrantCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    RevokeCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     EditCommunityName {
         name: String,
         response: oneshot::Sender<Result<(), Error>>,
@@ -176,6 +270,35 @@ pub enum CommunityTaskCommand {
         member: DID,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    BanCommunityMember {
+        member: DID,
+        reason: Option<String>,
+        expiry: Option<DateTime<Utc>>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    UnbanCommunityMember {
+        member: DID,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    ListCommunityBans {
+        response: oneshot::Sender<Result<Vec<CommunityBan>, Error>>,
+    },
+    TimeoutCommunityMember {
+        member: DID,
+        until: DateTime<Utc>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    TransferCommunityOwnership {
+        new_owner: DID,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    AcceptCommunityOwnership {
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    GetCommunityAuditLog {
+        options: CommunityAuditLogOptions,
+        response: oneshot::Sender<Result<Vec<CommunityAuditLogEntry>, Error>>,
+    },
     EditCommunityChannelName {
         channel_id: Uuid,
         name: String,
@@ -186,6 +309,26 @@ pub enum CommunityTaskCommand {
         description: Option<String>,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    EditCommunityChannelSlowMode {
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    FollowCommunityChannel {
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    UnfollowCommunityChannel {
+        channel_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    CrossPostCommunityMessage {
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+        lines: Vec<String>,
+    },
     GrantCommunityChannelPermission {
         channel_id: Uuid,
         permission: CommunityChannelPermission,
@@ -259,6 +402,15 @@ pub enum CommunityTaskCommand {
         message_id: Uuid,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    ListQuarantinedCommunityChannelMessages {
+        channel_id: Uuid,
+        response: oneshot::Sender<Result<Vec<QuarantinedMessage>, Error>>,
+    },
+    ReleaseQuarantinedCommunityChannelMessage {
+        channel_id: Uuid,
+        message_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     PinCommunityChannelMessage {
         channel_id: Uuid,
         message_id: Uuid,
@@ -272,6 +424,27 @@ pub enum CommunityTaskCommand {
         emoji: String,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    SendCommunityChannelPoll {
+        channel_id: Uuid,
+        poll: warp::raygun::Poll,
+        response: oneshot::Sender<Result<Uuid, Error>>,
+    },
+    VoteCommunityChannelPoll {
+        channel_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    CloseCommunityChannelPoll {
+        channel_id: Uuid,
+        message_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    CommunityChannelPollResults {
+        channel_id: Uuid,
+        message_id: Uuid,
+        response: oneshot::Sender<Result<PollResults, Error>>,
+    },
     SendCommunityChannelMesssageEvent {
         channel_id: Uuid,
         event: MessageEvent,
@@ -317,6 +490,7 @@ pub enum CommunityTaskCommand {
 pub struct CommunityTask {
     community_id: Uuid,
     ipfs: Ipfs,
+    message_filter: Option<MessageFilterHandle>,
     root: RootDocumentMap,
     file: FileStore,
     identity: IdentityStore,
@@ -337,6 +511,18 @@ pub struct CommunityTask {
 
     command_rx: futures::channel::mpsc::Receiver<CommunityTaskCommand>,
 
+    /// Inbound messages held back by the message filter
+    quarantine: Quarantine<CommunityMessagingEvents>,
+
+    /// Listing last published to the community directory
+    listing: Option<CommunityListingDocument>,
+
+    /// Date of the last message sent by each member in a channel, used to enforce slow mode.
+    /// This is only kept in memory so the limit resets when the task is restarted
+    last_sent: HashMap<(Uuid, DID), DateTime<Utc>>,
+
+    crosspost_tx: futures::channel::mpsc::Sender<CommunityCrossPost>,
+
     //TODO: replace queue
     queue: HashMap<DID, Vec<QueueItem>>,
 
@@ -375,14 +561,18 @@ impl CommunityTask {
     pub async fn new(
         community_id: Uuid,
         ipfs: &Ipfs,
+        message_filter: Option<MessageFilterHandle>,
         root: &RootDocumentMap,
         identity: &IdentityStore,
         file: &FileStore,
         discovery: &Discovery,
         command_rx: futures::channel::mpsc::Receiver<CommunityTaskCommand>,
+        crosspost_tx: futures::channel::mpsc::Sender<CommunityCrossPost>,
         _event_subscription: EventSubscription<RayGunEventKind>,
     ) -> Result<Self, Error> {
-        let document = root.get_community_document(community_id).await?;
+        let mut document = root.get_community_document(community_id).await?;
+        document.migrate_permissions();
+        document.normalize_role_positions();
         let main_topic = document.topic();
         let event_topic = document.event_topic();
         let request_topic = document.exchange_topic(&identity.did_key());
@@ -398,6 +588,7 @@ impl CommunityTask {
         let mut task = Self {
             community_id,
             ipfs: ipfs.clone(),
+            message_filter,
             root: root.clone(),
             file: file.clone(),
             identity: identity.clone(),
@@ -416,6 +607,10 @@ impl CommunityTask {
             event_broadcast: btx,
             _event_subscription,
             command_rx,
+            quarantine: root.get_quarantine(community_id).await.unwrap_or_default(),
+            listing: None,
+            last_sent: Default::default(),
+            crosspost_tx,
             queue: Default::default(),
             terminate: CommunityTermination::default(),
         };
@@ -723,6 +918,13 @@ impl CommunityTask {
                     .await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::SetCommunityListing {
+                response,
+                invite_id,
+            } => {
+                let result = self.set_community_listing(invite_id).await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::CreateCommunityInvite {
                 response,
                 target_user,
@@ -745,6 +947,10 @@ impl CommunityTask {
                 let result = self.get_community_invite(invite_id).await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::ListCommunityInvites { response } => {
+                let result = self.list_community_invites().await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::EditCommunityInvite {
                 response,
                 invite_id,
@@ -773,6 +979,40 @@ impl CommunityTask {
                 let result = self.edit_community_role_name(role_id, new_name).await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::EditCommunityRolePosition {
+                response,
+                role_id,
+                position,
+            } => {
+                let result = self.edit_community_role_position(role_id, position).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::EditCommunityRoleColor {
+                response,
+                role_id,
+                color,
+            } => {
+                let result = self.edit_community_role_color(role_id, color).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::EditCommunityRoleHoisted {
+                response,
+                role_id,
+                hoisted,
+            } => {
+                let result = self.edit_community_role_hoisted(role_id, hoisted).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::EditCommunityRoleMentionable {
+                response,
+                role_id,
+                mentionable,
+            } => {
+                let result = self
+                    .edit_community_role_mentionable(role_id, mentionable)
+                    .await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::GrantCommunityRole {
                 response,
                 role_id,
@@ -813,6 +1053,78 @@ impl CommunityTask {
                 let result = self.get_community_channel(channel_id).await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::ReorderCommunityChannels { response, channels } => {
+                let result = self.reorder_community_channels(channels).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::CreateCommunityChannelCategory { response, name } => {
+                let result = self.create_community_channel_category(name).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::DeleteCommunityChannelCategory {
+                response,
+                category_id,
+            } => {
+                let result = self.delete_community_channel_category(category_id).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::GetCommunityChannelCategory {
+                response,
+                category_id,
+            } => {
+                let result = self.get_community_channel_category(category_id);
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::MoveChannelToCategory {
+                response,
+                channel_id,
+                category_id,
+            } => {
+                let result = self.move_channel_to_category(channel_id, category_id).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::GrantCommunityChannelCategoryPermission {
+                response,
+                category_id,
+                permission,
+                role_id,
+            } => {
+                let result = self
+                    .grant_community_channel_category_permission(category_id, permission, role_id)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::RevokeCommunityChannelCategoryPermission {
+                response,
+                category_id,
+                permission,
+                role_id,
+            } => {
+                let result = self
+                    .revoke_community_channel_category_permission(category_id, permission, role_id)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::GrantCommunityChannelCategoryPermissionForAll {
+                response,
+                category_id,
+                permission,
+            } => {
+                let result = self
+                    .grant_community_channel_category_permission_for_all(category_id, permission)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::RevokeCommunityChannelCategoryPermissionForAll {
+                response,
+                category_id,
+                permission,
+            } => {
+                let result = self
+                    .revoke_community_channel_category_permission_for_all(category_id, permission)
+                    .await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::EditCommunityName { response, name } => {
                 let result = self.edit_community_name(name).await;
                 let _ = response.send(result);
@@ -858,6 +1170,45 @@ impl CommunityTask {
                 let result = self.remove_community_member(member).await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::BanCommunityMember {
+                response,
+                member,
+                reason,
+                expiry,
+            } => {
+                let result = self.ban_community_member(member, reason, expiry).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::UnbanCommunityMember { response, member } => {
+                let result = self.unban_community_member(member).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::ListCommunityBans { response } => {
+                let _ = response.send(Ok(self.list_community_bans()));
+            }
+            CommunityTaskCommand::TimeoutCommunityMember {
+                response,
+                member,
+                until,
+            } => {
+                let result = self.timeout_community_member(member, until).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::TransferCommunityOwnership {
+                response,
+                new_owner,
+            } => {
+                let result = self.transfer_community_ownership(new_owner).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::AcceptCommunityOwnership { response } => {
+                let result = self.accept_community_ownership().await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::GetCommunityAuditLog { response, options } => {
+                let result = self.get_community_audit_log(options).await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::EditCommunityChannelName {
                 response,
                 channel_id,
@@ -876,6 +1227,42 @@ impl CommunityTask {
                     .await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::EditCommunityChannelSlowMode {
+                response,
+                channel_id,
+                slow_mode,
+            } => {
+                let result = self
+                    .edit_community_channel_slow_mode(channel_id, slow_mode)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::FollowCommunityChannel {
+                response,
+                channel_id,
+                source_community_id,
+                source_channel_id,
+            } => {
+                let result = self
+                    .follow_community_channel(channel_id, source_community_id, source_channel_id)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::UnfollowCommunityChannel {
+                response,
+                channel_id,
+            } => {
+                let result = self.unfollow_community_channel(channel_id).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::CrossPostCommunityMessage {
+                source_community_id,
+                source_channel_id,
+                lines,
+            } => {
+                self.crosspost_community_message(source_community_id, source_channel_id, lines)
+                    .await;
+            }
             CommunityTaskCommand::GrantCommunityChannelPermission {
                 response,
                 channel_id,
@@ -1017,6 +1404,20 @@ impl CommunityTask {
                     .await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::ListQuarantinedCommunityChannelMessages {
+                channel_id,
+                response,
+            } => {
+                let _ = response.send(Ok(self.list_quarantined(channel_id)));
+            }
+            CommunityTaskCommand::ReleaseQuarantinedCommunityChannelMessage {
+                channel_id,
+                message_id,
+                response,
+            } => {
+                let result = self.release_quarantined(channel_id, message_id).await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::PinCommunityChannelMessage {
                 channel_id,
                 message_id,
@@ -1040,6 +1441,45 @@ impl CommunityTask {
                     .await;
                 let _ = response.send(result);
             }
+            CommunityTaskCommand::SendCommunityChannelPoll {
+                channel_id,
+                poll,
+                response,
+            } => {
+                let result = self.send_community_channel_poll(channel_id, poll).await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::VoteCommunityChannelPoll {
+                channel_id,
+                message_id,
+                options,
+                response,
+            } => {
+                let result = self
+                    .vote_community_channel_poll(channel_id, message_id, options)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::CloseCommunityChannelPoll {
+                channel_id,
+                message_id,
+                response,
+            } => {
+                let result = self
+                    .close_community_channel_poll(channel_id, message_id)
+                    .await;
+                let _ = response.send(result);
+            }
+            CommunityTaskCommand::CommunityChannelPollResults {
+                channel_id,
+                message_id,
+                response,
+            } => {
+                let result = self
+                    .community_channel_poll_results(channel_id, message_id)
+                    .await;
+                let _ = response.send(result);
+            }
             CommunityTaskCommand::SendCommunityChannelMesssageEvent {
                 channel_id,
                 event,
@@ -1133,6 +1573,8 @@ impl CommunityTask {
                 }
             }
         }
+        self.quarantine.clear();
+        self.save_quarantine().await;
         self.terminate.cancel();
         Ok(())
     }
@@ -1161,13 +1603,54 @@ impl CommunityTask {
 
         self.root.set_community_document(&self.document).await?;
         self.identity.export_root_document().await?;
+        self.sync_community_listing().await;
         Ok(())
     }
 
     pub async fn replace_document(&mut self, mut document: CommunityDocument) -> Result<(), Error> {
-        let keypair = self.root.keypair();
-        let did = keypair.to_did()?;
-        if self.document.owner.eq(&did) {
+        document.migrate_permissions();
+        document.normalize_role_positions();
+
+        if document.owner != self.document.owner {
+            // Ownership only changes by the new owner accepting an offer signed by the current owner,
+            // as long as the offer was not cancelled or replaced by a later one
+            let transfer = document
+                .ownership_transfer
+                .as_ref()
+                .ok_or(Error::Unauthorized)?;
+            if !self.document.is_valid_offer(transfer)
+                || transfer.new_owner != document.owner
+                || self
+                    .document
+                    .ownership_transfer
+                    .as_ref()
+                    .is_some_and(|last| last.date >= transfer.date)
+                || self.document.ownership_offer.as_ref().is_some_and(|offer| {
+                    self.document.is_valid_offer(offer) && offer.date > transfer.date
+                })
+            {
+                return Err(Error::Unauthorized);
+            }
+            document.ownership_offer = None;
+        } else {
+            document.ownership_transfer = self.document.ownership_transfer.clone();
+            // An offer is only taken from the document when it is signed by the owner and is not
+            // older than the one we have
+            let local_offer = self.document.ownership_offer.as_ref();
+            document.ownership_offer = match document.ownership_offer.take() {
+                Some(offer)
+                    if document.is_valid_offer(&offer)
+                        && local_offer.is_none_or(|local| offer.date >= local.date) =>
+                {
+                    Some(offer)
+                }
+                _ => local_offer.cloned(),
+            };
+        }
+
+        let keypair = self.root.keypair();
+        let did = keypair.to_did()?;
+        if document.owner.eq(&did) {
             document.sign(keypair)?;
         }
 
@@ -1176,9 +1659,124 @@ impl CommunityTask {
         self.root.set_community_document(&document).await?;
         self.identity.export_root_document().await?;
         self.document = document;
+        self.sync_community_listing().await;
         Ok(())
     }
 
+    /// Keeps the listing in the community directory up to date. Only the owner is able to publish
+    /// the listing, and it is only sent when it changes
+    async fn sync_community_listing(&mut self) {
+        if self.document.owner != self.identity.did_key()
+            || !matches!(
+                self.discovery.discovery_config(),
+                config::Discovery::Shuttle { .. }
+            )
+        {
+            return;
+        }
+
+        let listing = self.document.listing(self.root.keypair());
+        if listing == self.listing {
+            return;
+        }
+
+        let request = match &listing {
+            Some(listing) => CommunityDirectory::Publish {
+                listing: listing.clone(),
+            },
+            None => CommunityDirectory::Remove {
+                community_id: self.community_id,
+            },
+        };
+
+        match community_directory_request(&self.ipfs, self.root.keypair(), &self.discovery, request)
+            .await
+        {
+            Ok(_) => self.listing = listing,
+            Err(e) => {
+                tracing::warn!(community_id = %self.community_id, error = %e, "unable to update community listing")
+            }
+        }
+    }
+
+    /// Rejects a message from the sender if it was sent before the slow mode of the channel elapsed
+    fn check_slow_mode(
+        &self,
+        channel_id: Uuid,
+        sender: &DID,
+        date: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let Some(slow_mode) = self.document.slow_mode(sender, channel_id) else {
+            return Ok(());
+        };
+        let Some(last_sent) = self.last_sent.get(&(channel_id, sender.clone())) else {
+            return Ok(());
+        };
+        match slow_mode_elapsed(slow_mode, *last_sent, date) {
+            true => Ok(()),
+            false => Err(Error::CommunityChannelSlowMode),
+        }
+    }
+
+    /// Hands a new message in an announcement channel to the message store to be cross-posted
+    fn crosspost(&mut self, channel_id: Uuid, lines: Vec<String>) {
+        let is_announcement = self
+            .document
+            .channels
+            .get(&channel_id.to_string())
+            .is_some_and(|channel| channel.channel_type == CommunityChannelType::Announcement);
+        if !is_announcement || lines.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.crosspost_tx.try_send(CommunityCrossPost {
+            community_id: self.community_id,
+            channel_id,
+            lines,
+        }) {
+            tracing::warn!(community_id = %self.community_id, %channel_id, error = %e, "unable to cross-post message");
+        }
+    }
+
+    /// Posts a copy of the message into every channel we followed the announcement channel with
+    async fn crosspost_community_message(
+        &mut self,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+        lines: Vec<String>,
+    ) {
+        let own_did = self.identity.did_key();
+        let channels = self
+            .document
+            .channels
+            .values()
+            .filter(|channel| {
+                channel.following.as_ref().is_some_and(|following| {
+                    following.community_id == source_community_id
+                        && following.channel_id == source_channel_id
+                        && following.follower == own_did
+                })
+            })
+            .map(|channel| channel.id)
+            .collect::<Vec<_>>();
+
+        for channel_id in channels {
+            if let Err(e) = self
+                .send_community_channel_message(channel_id, lines.clone())
+                .await
+            {
+                tracing::warn!(community_id = %self.community_id, %channel_id, error = %e, "unable to post cross-posted message");
+            }
+        }
+    }
+
+    fn record_sent(&mut self, channel_id: Uuid, sender: DID, date: DateTime<Utc>) {
+        let last_sent = self.last_sent.entry((channel_id, sender)).or_insert(date);
+        if *last_sent < date {
+            *last_sent = date;
+        }
+    }
+
     async fn send_single_community_event(
         &mut self,
         did_key: &DID,
@@ -1227,6 +1825,10 @@ impl CommunityTask {
         let data = PayloadMessage::<CommunityMessagingEvents>::from_bytes(&msg.data)?;
         let sender = data.sender().to_did()?;
 
+        if self.document.is_banned(&sender) {
+            return Err(Error::CommunityMemberBanned);
+        }
+
         let keypair = self.root.keypair();
 
         let id = self.community_id;
@@ -1267,7 +1869,7 @@ impl CommunityTask {
             }
         };
 
-        message_event(self, &sender, event).await?;
+        message_event(self, &sender, event, true, false).await?;
 
         Ok(())
     }
@@ -1280,13 +1882,8 @@ impl CommunityTask {
             CommunityJoinEvents::Join => {
                 let now = Utc::now();
 
-                if !self.document.invites.iter().any(|(_, invite)| {
-                    invite.expiry.is_none_or(|expiry| expiry > now)
-                        && invite
-                            .target_user
-                            .as_ref()
-                            .is_none_or(|target| &sender == target)
-                }) {
+                if self.document.is_banned(&sender) {
+                    tracing::info!(%community_id, %sender, "Rejecting join request from banned user");
                     self.send_single_community_event(
                         &sender,
                         ConversationEvents::JoinCommunity {
@@ -1298,17 +1895,62 @@ impl CommunityTask {
                     return Ok(());
                 }
 
+                let Some(invite_id) = self
+                    .document
+                    .valid_invite(&sender, now)
+                    .map(|invite| invite.id)
+                else {
+                    self.send_single_community_event(
+                        &sender,
+                        ConversationEvents::JoinCommunity {
+                            community_id,
+                            community_document: None,
+                        },
+                    )
+                    .await?;
+                    return Ok(());
+                };
+
                 self.document.members.insert(sender.clone());
+                self.document
+                    .invite_joins
+                    .insert(sender.to_string(), invite_id);
+
+                let mut exhausted = vec![];
+                if let Some(invite) = self.document.invites.get_mut(&invite_id.to_string()) {
+                    invite.uses += 1;
+                    if invite.is_exhausted() {
+                        exhausted.push(invite_id);
+                    }
+                }
 
                 self.document.invites.retain(|_, invite| {
                     !invite
                         .target_user
                         .as_ref()
                         .is_some_and(|target| &sender == target)
+                        && !exhausted.contains(&invite.id)
                 });
 
+                if self
+                    .document
+                    .public_invite
+                    .is_some_and(|invite_id| exhausted.contains(&invite_id))
+                {
+                    self.document.public_invite.take();
+                }
+
                 self.set_document().await?;
 
+                for invite_id in exhausted {
+                    let _ = self
+                        .event_broadcast
+                        .send(MessageEventKind::DeletedCommunityInvite {
+                            community_id,
+                            invite_id,
+                        });
+                }
+
                 self.send_single_community_event(
                     &sender,
                     ConversationEvents::JoinCommunity {
@@ -1412,6 +2054,88 @@ impl CommunityTask {
         Ok(())
     }
 
+    fn list_quarantined(&self, channel_id: Uuid) -> Vec<QuarantinedMessage> {
+        self.quarantine
+            .values()
+            .filter(|entry| entry.source.channel_id() == Some(channel_id))
+            .map(|entry| entry.message.clone())
+            .collect()
+    }
+
+    async fn release_quarantined(
+        &mut self,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        if !self
+            .quarantine
+            .get(&message_id)
+            .is_some_and(|entry| entry.source.channel_id() == Some(channel_id))
+        {
+            return Err(Error::MessageNotFound);
+        }
+
+        let QuarantineEntry { sender, event, .. } = self
+            .quarantine
+            .shift_remove(&message_id)
+            .ok_or(Error::MessageNotFound)?;
+
+        self.save_quarantine().await;
+
+        message_event(self, &sender, event, false, true).await
+    }
+
+    async fn save_quarantine(&self) {
+        if let Err(e) = self
+            .root
+            .set_quarantine(self.community_id, &self.quarantine)
+            .await
+        {
+            tracing::warn!(community_id = %self.community_id, error = %e, "failed to store quarantine");
+        }
+    }
+
+    /// Pass an inbound message through the message filter, if one is set.
+    /// Returns `true` if the message should be stored
+    async fn filter_message(
+        &mut self,
+        sender: &DID,
+        channel_id: Uuid,
+        kind: FilterKind,
+        message: &warp::raygun::Message,
+        event: impl FnOnce() -> CommunityMessagingEvents,
+    ) -> bool {
+        let Some(message_filter) = self.message_filter.clone() else {
+            return true;
+        };
+
+        if sender == &self.identity.did_key() {
+            return true;
+        }
+
+        let source = FilterSource::Community {
+            community_id: self.community_id,
+            channel_id,
+        };
+
+        let store = filter::filter_message(
+            &message_filter,
+            &mut self.quarantine,
+            source,
+            sender,
+            kind,
+            message,
+            event,
+        )
+        .await;
+
+        if !store && self.quarantine.contains_key(&message.id()) {
+            self.save_quarantine().await;
+        }
+
+        store
+    }
+
     pub async fn send_message_event(&self, event: CommunityMessagingEvents) -> Result<(), Error> {
         let key = self.community_key(None)?;
 
@@ -1671,7 +2395,7 @@ impl CommunityTask {
             }
         }
 
-        let invite_doc = CommunityInviteDocument::new(target_user.clone(), expiry);
+        let invite_doc = CommunityInviteDocument::new(own_did.clone(), target_user.clone(), expiry);
         self.document
             .invites
             .insert(invite_doc.id.to_string(), invite_doc.clone());
@@ -1729,6 +2453,9 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
+        self.remove_community_invite(invite_id).await
+    }
+    async fn remove_community_invite(&mut self, invite_id: Uuid) -> Result<(), Error> {
         let invite = self
             .document
             .invites
@@ -1736,6 +2463,9 @@ impl CommunityTask {
             .ok_or(Error::CommunityInviteDoesntExist)?
             .clone();
         self.document.invites.swap_remove(&invite_id.to_string());
+        if self.document.public_invite == Some(invite_id) {
+            self.document.public_invite.take();
+        }
         self.set_document().await?;
 
         let _ = self
@@ -1777,6 +2507,15 @@ impl CommunityTask {
             None => Err(Error::CommunityInviteDoesntExist),
         }
     }
+    pub async fn list_community_invites(&mut self) -> Result<Vec<CommunityInvite>, Error> {
+        Ok(self
+            .document
+            .invites
+            .values()
+            .cloned()
+            .map(CommunityInvite::from)
+            .collect())
+    }
     pub async fn edit_community_invite(
         &mut self,
         invite_id: Uuid,
@@ -1797,6 +2536,10 @@ impl CommunityTask {
             .ok_or(Error::CommunityInviteDoesntExist)?;
         invite_doc.target_user = invite.target_user().cloned();
         invite_doc.expiry = invite.expiry();
+        invite_doc.max_uses = invite.max_uses();
+        if invite_doc.is_exhausted() {
+            return self.remove_community_invite(invite_id).await;
+        }
         self.set_document().await?;
 
         let _ = self
@@ -1844,7 +2587,15 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let role = CommunityRoleDocument::new(name.to_owned());
+        // New roles are placed at the bottom of the hierarchy
+        let position = self
+            .document
+            .roles
+            .values()
+            .map(|role| role.position + 1)
+            .max()
+            .unwrap_or_default();
+        let role = CommunityRoleDocument::new(name.to_owned(), position);
         self.document
             .roles
             .insert(role.id.to_string(), role.clone());
@@ -1878,6 +2629,12 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
+        if self.document.roles.contains_key(&role_id.to_string())
+            && !self.document.can_manage_role(own_did, role_id)
+        {
+            return Err(Error::Unauthorized);
+        }
+
         self.document.roles.swap_remove(&role_id.to_string());
         let _ = self
             .document
@@ -1922,6 +2679,61 @@ impl CommunityTask {
         role_id: RoleId,
         new_name: String,
     ) -> Result<(), Error> {
+        self.update_community_role(role_id, |role| role.name = new_name)
+            .await
+    }
+    pub async fn edit_community_role_position(
+        &mut self,
+        role_id: RoleId,
+        position: u32,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        self.can_edit_community_role(role_id)?;
+        if !self.document.outranks_position(own_did, position) {
+            return Err(Error::Unauthorized);
+        }
+        self.document.move_role(role_id, position);
+        self.publish_community_role_edit(role_id).await
+    }
+    pub async fn edit_community_role_color(
+        &mut self,
+        role_id: RoleId,
+        color: Option<u32>,
+    ) -> Result<(), Error> {
+        if color.is_some_and(|color| color > 0xFFFFFF) {
+            return Err(Error::OtherWithContext("color must be a RGB value".into()));
+        }
+        self.update_community_role(role_id, |role| role.color = color)
+            .await
+    }
+    pub async fn edit_community_role_hoisted(
+        &mut self,
+        role_id: RoleId,
+        hoisted: bool,
+    ) -> Result<(), Error> {
+        self.update_community_role(role_id, |role| role.hoisted = hoisted)
+            .await
+    }
+    pub async fn edit_community_role_mentionable(
+        &mut self,
+        role_id: RoleId,
+        mentionable: bool,
+    ) -> Result<(), Error> {
+        self.update_community_role(role_id, |role| role.mentionable = mentionable)
+            .await
+    }
+    async fn update_community_role(
+        &mut self,
+        role_id: RoleId,
+        update: impl FnOnce(&mut CommunityRoleDocument),
+    ) -> Result<(), Error> {
+        self.can_edit_community_role(role_id)?;
+        if let Some(role) = self.document.roles.get_mut(&role_id.to_string()) {
+            update(role);
+        }
+        self.publish_community_role_edit(role_id).await
+    }
+    fn can_edit_community_role(&self, role_id: RoleId) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
@@ -1930,11 +2742,16 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        self.document
-            .roles
-            .get_mut(&role_id.to_string())
-            .ok_or(Error::CommunityRoleDoesntExist)?
-            .name = new_name;
+        if !self.document.roles.contains_key(&role_id.to_string()) {
+            return Err(Error::CommunityRoleDoesntExist);
+        }
+
+        if !self.document.can_manage_role(own_did, role_id) {
+            return Err(Error::Unauthorized);
+        }
+        Ok(())
+    }
+    async fn publish_community_role_edit(&mut self, role_id: RoleId) -> Result<(), Error> {
         self.set_document().await?;
 
         let _ = self
@@ -1966,6 +2783,14 @@ impl CommunityTask {
             return Err(Error::InvalidCommunityMember);
         }
 
+        if !self.document.roles.contains_key(&role_id.to_string()) {
+            return Err(Error::CommunityRoleDoesntExist);
+        }
+
+        if !self.document.can_manage_role(own_did, role_id) {
+            return Err(Error::Unauthorized);
+        }
+
         self.document
             .roles
             .get_mut(&role_id.to_string())
@@ -2001,6 +2826,14 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
+        if !self.document.roles.contains_key(&role_id.to_string()) {
+            return Err(Error::CommunityRoleDoesntExist);
+        }
+
+        if !self.document.can_manage_role(own_did, role_id) {
+            return Err(Error::Unauthorized);
+        }
+
         self.document
             .roles
             .get_mut(&role_id.to_string())
@@ -2049,6 +2882,9 @@ impl CommunityTask {
         self.document
             .channels
             .insert(channel_doc.id.to_string(), channel_doc.clone());
+        if !self.document.channel_order.is_empty() {
+            self.document.channel_order.insert(channel_doc.id);
+        }
         self.set_document().await?;
 
         let _ = self
@@ -2082,6 +2918,7 @@ impl CommunityTask {
         }
 
         self.document.channels.swap_remove(&channel_id.to_string());
+        self.document.channel_order.shift_remove(&channel_id);
         self.set_document().await?;
 
         let _ = self
@@ -2121,361 +2958,411 @@ impl CommunityTask {
             .ok_or(Error::CommunityChannelDoesntExist)?;
         Ok(CommunityChannel::from(channel_doc.clone()))
     }
-
-    pub async fn edit_community_name(&mut self, name: String) -> Result<(), Error> {
+    pub async fn reorder_community_channels(&mut self, channels: Vec<Uuid>) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::EditName)
+            .has_permission(own_did, &CommunityPermission::EditChannels)
         {
             return Err(Error::Unauthorized);
         }
 
-        self.document.name = name.to_owned();
+        if channels
+            .iter()
+            .any(|id| !self.document.channels.contains_key(&id.to_string()))
+        {
+            return Err(Error::CommunityChannelDoesntExist);
+        }
+
+        let order = channels.iter().copied().collect::<IndexSet<_>>();
+        if order.len() != channels.len() || order.len() != self.document.channels.len() {
+            return Err(Error::InvalidCommunityChannelOrder);
+        }
+
+        self.document.channel_order = order;
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::EditedCommunityName {
+            .send(MessageEventKind::CommunityChannelsReordered {
                 community_id: self.community_id,
-                name: name.to_string(),
+                channels: self.document.ordered_channels(),
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::EditCommunityName {
-                    name: name.to_string(),
-                },
+                kind: CommunityUpdateKind::ReorderCommunityChannels,
             },
             true,
         )
         .await
     }
-    pub async fn edit_community_description(
+    pub async fn create_community_channel_category(
         &mut self,
-        description: Option<String>,
-    ) -> Result<(), Error> {
+        name: String,
+    ) -> Result<CommunityChannelCategory, Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::EditDescription)
+            .has_permission(own_did, &CommunityPermission::CreateChannels)
         {
             return Err(Error::Unauthorized);
         }
 
-        if let Some(desc) = &description {
-            if desc.is_empty() || desc.len() > MAX_COMMUNITY_DESCRIPTION {
-                return Err(Error::InvalidLength {
-                    context: "description".into(),
-                    minimum: Some(1),
-                    maximum: Some(MAX_COMMUNITY_DESCRIPTION),
-                    current: desc.len(),
-                });
-            }
+        if self.document.categories.len() >= MAX_COMMUNITY_CHANNEL_CATEGORIES {
+            return Err(Error::CommunityChannelCategoryLimitReached);
         }
-
-        self.document.description = description.clone();
+        let category_doc = CommunityChannelCategoryDocument::new(name);
+        self.document
+            .categories
+            .insert(category_doc.id.to_string(), category_doc.clone());
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::EditedCommunityDescription {
+            .send(MessageEventKind::CreatedCommunityChannelCategory {
                 community_id: self.community_id,
-                description: self.document.description.clone(),
+                category: CommunityChannelCategory::from(category_doc.clone()),
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::EditCommunityDescription {
-                    description: self.document.description.clone(),
+                kind: CommunityUpdateKind::CreateCommunityChannelCategory {
+                    category: category_doc.clone(),
                 },
             },
             true,
         )
-        .await
-    }
+        .await?;
 
-    pub async fn grant_community_permission(
+        Ok(CommunityChannelCategory::from(category_doc))
+    }
+    pub async fn delete_community_channel_category(
         &mut self,
-        permission: CommunityPermission,
-        role_id: RoleId,
+        category_id: Uuid,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+            .has_permission(own_did, &CommunityPermission::DeleteChannels)
         {
             return Err(Error::Unauthorized);
         }
 
-        match self.document.permissions.get_mut(&permission) {
-            Some(authorized_roles) => {
-                authorized_roles.insert(role_id);
-            }
-            None => {
-                let mut roles = IndexSet::new();
-                roles.insert(role_id);
-                self.document.permissions.insert(permission, roles);
-            }
-        }
+        self.document
+            .categories
+            .shift_remove(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        self.document
+            .channels
+            .values_mut()
+            .filter(|channel| channel.category == Some(category_id))
+            .for_each(|channel| channel.category = None);
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::GrantedCommunityPermission {
+            .send(MessageEventKind::DeletedCommunityChannelCategory {
                 community_id: self.community_id,
-                permission,
-                role_id,
+                category_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::GrantCommunityPermission {
-                    permission,
-                    role_id,
-                },
+                kind: CommunityUpdateKind::DeleteCommunityChannelCategory { category_id },
             },
             true,
         )
         .await
     }
-    pub async fn revoke_community_permission(
+    pub fn get_community_channel_category(
+        &self,
+        category_id: Uuid,
+    ) -> Result<CommunityChannelCategory, Error> {
+        let category_doc = self
+            .document
+            .categories
+            .get(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        let mut category = CommunityChannelCategory::from(category_doc.clone());
+        category.set_channels(self.document.category_channels(category_id));
+        Ok(category)
+    }
+    pub async fn move_channel_to_category(
         &mut self,
-        permission: CommunityPermission,
-        role_id: RoleId,
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::RevokePermissions)
+            .has_permission(own_did, &CommunityPermission::EditChannels)
         {
             return Err(Error::Unauthorized);
         }
 
-        if let Some(authorized_roles) = self.document.permissions.get_mut(&permission) {
-            authorized_roles.swap_remove(&role_id);
+        if let Some(category_id) = category_id {
+            if !self
+                .document
+                .categories
+                .contains_key(&category_id.to_string())
+            {
+                return Err(Error::CommunityChannelCategoryDoesntExist);
+            }
         }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        channel_doc.category = category_id;
+        channel_doc.modified = Utc::now();
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::RevokedCommunityPermission {
+            .send(MessageEventKind::MovedCommunityChannelToCategory {
                 community_id: self.community_id,
-                permission,
-                role_id,
+                channel_id,
+                category_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::RevokeCommunityPermission {
-                    permission,
-                    role_id,
+                kind: CommunityUpdateKind::MoveCommunityChannelToCategory {
+                    channel_id,
+                    category_id,
                 },
             },
             true,
         )
         .await
     }
-    pub async fn grant_community_permission_for_all(
-        &mut self,
-        permission: CommunityPermission,
-    ) -> Result<(), Error> {
+
+    pub async fn edit_community_name(&mut self, name: String) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+            .has_permission(own_did, &CommunityPermission::EditName)
         {
             return Err(Error::Unauthorized);
         }
 
-        if self.document.permissions.contains_key(&permission) {
-            self.document.permissions.swap_remove(&permission);
-            self.set_document().await?;
-        } else {
-            return Err(Error::PermissionAlreadyGranted);
+        if name.is_empty() || name.len() > MAX_COMMUNITY_NAME_LENGTH {
+            return Err(Error::InvalidLength {
+                context: "name".into(),
+                minimum: Some(1),
+                maximum: Some(MAX_COMMUNITY_NAME_LENGTH),
+                current: name.len(),
+            });
         }
 
+        self.document.name = name.to_owned();
+        self.set_document().await?;
+
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::GrantedCommunityPermissionForAll {
+            .send(MessageEventKind::EditedCommunityName {
                 community_id: self.community_id,
-                permission,
+                name: name.to_string(),
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::GrantCommunityPermissionForAll { permission },
+                kind: CommunityUpdateKind::EditCommunityName {
+                    name: name.to_string(),
+                },
             },
             true,
         )
         .await
     }
-    pub async fn revoke_community_permission_for_all(
-        &mut self,
-        permission: CommunityPermission,
-    ) -> Result<(), Error> {
+    pub async fn set_community_listing(&mut self, invite_id: Option<Uuid>) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
-        if !self
-            .document
-            .has_permission(own_did, &CommunityPermission::RevokePermissions)
-        {
+        if own_did != &self.document.owner {
             return Err(Error::Unauthorized);
         }
 
-        self.document
-            .permissions
-            .insert(permission, IndexSet::new());
+        if let Some(invite_id) = invite_id {
+            let invite = self
+                .document
+                .invites
+                .get(&invite_id.to_string())
+                .ok_or(Error::CommunityInviteDoesntExist)?;
+            // The advertised invite has to be usable by anyone finding the community
+            if invite.target_user.is_some() || !invite.is_valid_for(own_did, Utc::now()) {
+                return Err(Error::InvalidCommunityInvite);
+            }
+        }
+
+        // The listing may have been published before the task started, so we treat the current
+        // listing as the published one to make sure the directory is updated
+        self.listing = self.document.listing(self.root.keypair());
+        self.document.public_invite = invite_id;
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::RevokedCommunityPermissionForAll {
+            .send(MessageEventKind::EditedCommunityListing {
                 community_id: self.community_id,
-                permission,
+                invite_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::RevokeCommunityPermissionForAll { permission },
+                kind: CommunityUpdateKind::SetCommunityListing { invite_id },
             },
             true,
         )
         .await
     }
-    pub async fn remove_community_member(&mut self, member: DID) -> Result<(), Error> {
+    pub async fn edit_community_description(
+        &mut self,
+        description: Option<String>,
+    ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::RemoveMembers)
+            .has_permission(own_did, &CommunityPermission::EditDescription)
         {
             return Err(Error::Unauthorized);
         }
 
-        self.document.members.swap_remove(&member);
-        self.document.roles.iter_mut().for_each(|(_, r)| {
-            r.members.swap_remove(&member);
-        });
+        if let Some(desc) = &description {
+            if desc.is_empty() || desc.len() > MAX_COMMUNITY_DESCRIPTION {
+                return Err(Error::InvalidLength {
+                    context: "description".into(),
+                    minimum: Some(1),
+                    maximum: Some(MAX_COMMUNITY_DESCRIPTION),
+                    current: desc.len(),
+                });
+            }
+        }
+
+        self.document.description = description.clone();
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::RemovedCommunityMember {
+            .send(MessageEventKind::EditedCommunityDescription {
                 community_id: self.community_id,
-                member: member.clone(),
+                description: self.document.description.clone(),
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::RemoveCommunityMember { member },
+                kind: CommunityUpdateKind::EditCommunityDescription {
+                    description: self.document.description.clone(),
+                },
             },
             true,
         )
         .await
     }
 
-    pub async fn edit_community_channel_name(
+    pub async fn grant_community_permission(
         &mut self,
-        channel_id: Uuid,
-        name: String,
+        permission: CommunityPermission,
+        role_id: RoleId,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::EditChannels)
+            .has_permission(own_did, &CommunityPermission::GrantPermissions)
         {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
-            .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
-        channel_doc.name = name.to_owned();
+        match self.document.permissions.get_mut(&permission) {
+            Some(authorized_roles) => {
+                authorized_roles.insert(role_id);
+            }
+            None => {
+                let mut roles = IndexSet::new();
+                roles.insert(role_id);
+                self.document.permissions.insert(permission, roles);
+            }
+        }
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::EditedCommunityChannelName {
+            .send(MessageEventKind::GrantedCommunityPermission {
                 community_id: self.community_id,
-                channel_id,
-                name: name.to_string(),
+                permission,
+                role_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::EditCommunityChannelName {
-                    channel_id,
-                    name: name.to_string(),
+                kind: CommunityUpdateKind::GrantCommunityPermission {
+                    permission,
+                    role_id,
                 },
             },
             true,
         )
         .await
     }
-    pub async fn edit_community_channel_description(
+    pub async fn revoke_community_permission(
         &mut self,
-        channel_id: Uuid,
-        description: Option<String>,
+        permission: CommunityPermission,
+        role_id: RoleId,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::EditChannels)
+            .has_permission(own_did, &CommunityPermission::RevokePermissions)
         {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
-            .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
-        channel_doc.description = description.clone();
+        if let Some(authorized_roles) = self.document.permissions.get_mut(&permission) {
+            authorized_roles.swap_remove(&role_id);
+        }
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::EditedCommunityChannelDescription {
+            .send(MessageEventKind::RevokedCommunityPermission {
                 community_id: self.community_id,
-                channel_id,
-                description: description.clone(),
+                permission,
+                role_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::EditCommunityChannelDescription {
-                    channel_id,
-                    description,
+                kind: CommunityUpdateKind::RevokeCommunityPermission {
+                    permission,
+                    role_id,
                 },
             },
             true,
         )
         .await
     }
-    pub async fn grant_community_channel_permission(
+    pub async fn grant_community_permission_for_all(
         &mut self,
-        channel_id: Uuid,
-        permission: CommunityChannelPermission,
-        role_id: RoleId,
+        permission: CommunityPermission,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
@@ -2485,51 +3372,33 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
-            .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
-        match channel_doc.permissions.get_mut(&permission) {
-            Some(authorized_roles) => {
-                authorized_roles.insert(role_id);
-            }
-            None => {
-                let mut roles = IndexSet::new();
-                roles.insert(role_id);
-                channel_doc.permissions.insert(permission, roles);
-            }
+        if self.document.permissions.contains_key(&permission) {
+            self.document.permissions.swap_remove(&permission);
+            self.set_document().await?;
+        } else {
+            return Err(Error::PermissionAlreadyGranted);
         }
-        self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::GrantedCommunityChannelPermission {
+            .send(MessageEventKind::GrantedCommunityPermissionForAll {
                 community_id: self.community_id,
-                channel_id,
                 permission,
-                role_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::GrantCommunityChannelPermission {
-                    channel_id,
-                    permission,
-                    role_id,
-                },
+                kind: CommunityUpdateKind::GrantCommunityPermissionForAll { permission },
             },
             true,
         )
         .await
     }
-    pub async fn revoke_community_channel_permission(
+    pub async fn revoke_community_permission_for_all(
         &mut self,
-        channel_id: Uuid,
-        permission: CommunityChannelPermission,
-        role_id: RoleId,
+        permission: CommunityPermission,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
@@ -2539,57 +3408,799 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
-            .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
-        if let Some(authorized_roles) = channel_doc.permissions.get_mut(&permission) {
-            authorized_roles.swap_remove(&role_id);
-        }
+        self.document
+            .permissions
+            .insert(permission, IndexSet::new());
         self.set_document().await?;
 
         let _ = self
             .event_broadcast
-            .send(MessageEventKind::RevokedCommunityChannelPermission {
+            .send(MessageEventKind::RevokedCommunityPermissionForAll {
                 community_id: self.community_id,
-                channel_id,
                 permission,
-                role_id,
             });
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::RevokeCommunityChannelPermission {
-                    channel_id,
-                    permission,
-                    role_id,
-                },
+                kind: CommunityUpdateKind::RevokeCommunityPermissionForAll { permission },
             },
             true,
         )
         .await
     }
-    pub async fn grant_community_channel_permission_for_all(
-        &mut self,
-        channel_id: Uuid,
-        permission: CommunityChannelPermission,
-    ) -> Result<(), Error> {
+    pub async fn remove_community_member(&mut self, member: DID) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self
             .document
-            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+            .has_permission(own_did, &CommunityPermission::RemoveMembers)
         {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
-            .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
+        if !self.document.outranks(own_did, &member) {
+            return Err(Error::Unauthorized);
+        }
+
+        self.document.members.swap_remove(&member);
+        self.document.roles.iter_mut().for_each(|(_, r)| {
+            r.members.swap_remove(&member);
+        });
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::RemovedCommunityMember {
+                community_id: self.community_id,
+                member: member.clone(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::RemoveCommunityMember { member },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn ban_community_member(
+        &mut self,
+        member: DID,
+        reason: Option<String>,
+        expiry: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::BanMembers)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        if member == self.document.owner || &member == own_did {
+            return Err(Error::InvalidCommunityMember);
+        }
+
+        if !self.document.outranks(own_did, &member) {
+            return Err(Error::Unauthorized);
+        }
+
+        let reason = reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty());
+
+        if let Some(reason) = reason.as_ref() {
+            if reason.chars().count() > MAX_COMMUNITY_BAN_REASON_LENGTH {
+                return Err(Error::InvalidLength {
+                    context: "reason".into(),
+                    current: reason.chars().count(),
+                    minimum: None,
+                    maximum: Some(MAX_COMMUNITY_BAN_REASON_LENGTH),
+                });
+            }
+        }
+
+        self.document.members.swap_remove(&member);
+        self.document.roles.iter_mut().for_each(|(_, r)| {
+            r.members.swap_remove(&member);
+        });
+        self.document.invites.retain(|_, invite| {
+            !invite
+                .target_user
+                .as_ref()
+                .is_some_and(|target| &member == target)
+        });
+        self.document.timeouts.swap_remove(&member.to_string());
+        self.document.bans.retain(|_, ban| ban.is_active());
+        self.document.bans.insert(
+            member.to_string(),
+            CommunityBanDocument::new(member.clone(), reason, own_did.clone(), expiry),
+        );
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::BannedCommunityMember {
+                community_id: self.community_id,
+                member: member.clone(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::BanCommunityMember { member },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn unban_community_member(&mut self, member: DID) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::BanMembers)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        if !self.document.is_banned(&member) {
+            return Err(Error::CommunityBanDoesntExist);
+        }
+
+        self.document.bans.swap_remove(&member.to_string());
+        self.document.bans.retain(|_, ban| ban.is_active());
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::UnbannedCommunityMember {
+                community_id: self.community_id,
+                member: member.clone(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::UnbanCommunityMember { member },
+            },
+            true,
+        )
+        .await
+    }
+    pub fn list_community_bans(&self) -> Vec<CommunityBan> {
+        self.document
+            .bans
+            .values()
+            .filter(|ban| ban.is_active())
+            .cloned()
+            .map(CommunityBan::from)
+            .collect()
+    }
+    pub async fn timeout_community_member(
+        &mut self,
+        member: DID,
+        until: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::TimeoutMembers)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        if member == self.document.owner
+            || &member == own_did
+            || !self.document.members.contains(&member)
+        {
+            return Err(Error::InvalidCommunityMember);
+        }
+
+        if !self.document.outranks(own_did, &member) {
+            return Err(Error::Unauthorized);
+        }
+
+        let now = Utc::now();
+        self.document.timeouts.retain(|_, until| *until > now);
+        if until > now {
+            self.document.timeouts.insert(member.to_string(), until);
+        }
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::CommunityMemberTimedOut {
+                community_id: self.community_id,
+                member: member.clone(),
+                until,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::TimeoutCommunityMember { member, until },
+            },
+            true,
+        )
+        .await
+    }
+
+    pub async fn transfer_community_ownership(&mut self, new_owner: DID) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if own_did != &self.document.owner {
+            return Err(Error::Unauthorized);
+        }
+
+        let new_owner = (&new_owner != own_did).then_some(new_owner);
+
+        if let Some(new_owner) = &new_owner {
+            if !self.document.members.contains(new_owner) || self.document.is_banned(new_owner) {
+                return Err(Error::InvalidCommunityMember);
+            }
+        }
+
+        let offer = CommunityOwnershipOfferDocument::new(
+            self.root.keypair(),
+            self.community_id,
+            new_owner.clone().unwrap_or_else(|| own_did.clone()),
+        )?;
+        self.document.ownership_offer = Some(offer);
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::OfferedCommunityOwnership {
+                community_id: self.community_id,
+                new_owner: new_owner.clone(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::TransferCommunityOwnership { new_owner },
+            },
+            true,
+        )
+        .await
+    }
+
+    pub async fn accept_community_ownership(&mut self) -> Result<(), Error> {
+        let own_did = self.identity.did_key();
+        if self.document.pending_owner() != Some(&own_did) {
+            return Err(Error::CommunityOwnershipTransferDoesntExist);
+        }
+
+        let previous_owner = std::mem::replace(&mut self.document.owner, own_did.clone());
+        // The accepted offer is kept in the document so peers that missed it can still verify the transfer
+        self.document.ownership_transfer = self.document.ownership_offer.take();
+        self.document.timeouts.swap_remove(&own_did.to_string());
+
+        // Storing the document signs it with our key now that we are the owner, which counter-signs the transfer
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::TransferredCommunityOwnership {
+                community_id: self.community_id,
+                previous_owner: previous_owner.clone(),
+                new_owner: own_did,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::AcceptCommunityOwnership { previous_owner },
+            },
+            true,
+        )
+        .await
+    }
+
+    pub async fn get_community_audit_log(
+        &self,
+        options: CommunityAuditLogOptions,
+    ) -> Result<Vec<CommunityAuditLogEntry>, Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::ViewAuditLog)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let date_range = options.date_range();
+        let mut skip = options.skip();
+        let mut entries = vec![];
+        let mut visited = HashSet::new();
+        let mut frontier: Vec<CommunityAuditLogEntryDocument> = vec![];
+
+        let heads = self
+            .document
+            .audit_log
+            .into_iter()
+            .chain(self.document.concurrent_audit_logs.iter().copied());
+
+        for cid in heads {
+            if visited.insert(cid) {
+                frontier.push(self.get_audit_log_entry(cid).await?);
+            }
+        }
+
+        // Concurrent entries are walked together so the log is listed from newest to oldest
+        while let Some(index) = frontier
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, entry)| entry.date)
+            .map(|(index, _)| index)
+        {
+            if options.limit().is_some_and(|limit| entries.len() >= limit) {
+                break;
+            }
+
+            let entry = frontier.swap_remove(index);
+
+            for cid in entry.parents() {
+                if visited.insert(cid) {
+                    frontier.push(self.get_audit_log_entry(cid).await?);
+                }
+            }
+
+            if options.actor().is_some_and(|actor| actor != &entry.actor) {
+                continue;
+            }
+
+            if date_range
+                .as_ref()
+                .is_some_and(|range| !range.contains(&entry.date))
+            {
+                continue;
+            }
+
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+
+            entries.push(CommunityAuditLogEntry::from(entry));
+        }
+
+        Ok(entries)
+    }
+
+    async fn get_audit_log_entry(&self, cid: Cid) -> Result<CommunityAuditLogEntryDocument, Error> {
+        let entry: CommunityAuditLogEntryDocument = self
+            .ipfs
+            .get_dag(cid)
+            .timeout(Duration::from_secs(10))
+            .deserialized()
+            .await?;
+
+        entry.verify()?;
+
+        Ok(entry)
+    }
+
+    /// Append a signed entry for the update to the audit log, linking it to the current head and
+    /// merging any heads that were made concurrently
+    async fn append_audit_log(&mut self, kind: &CommunityUpdateKind) -> Result<(), Error> {
+        let keypair = self.root.keypair();
+        let entry = CommunityAuditLogEntryDocument::new(
+            keypair,
+            kind.into(),
+            self.document.audit_log,
+            std::mem::take(&mut self.document.concurrent_audit_logs),
+        )?;
+        let cid = self.ipfs.put_dag(entry).await?;
+        self.document.audit_log.replace(cid);
+        self.set_document().await
+    }
+
+    /// Merges the audit log of an incoming document into ours. The new head has to be signed by
+    /// the sender and record the update. Our heads found within the entries before it are replaced
+    /// by it, while the rest are kept as concurrent heads so that no entry is dropped when two
+    /// members update the community at the same time. A document without an audit log, such as
+    /// one from a peer that predates it, leaves ours unchanged
+    async fn merge_audit_log(
+        &self,
+        community: &mut CommunityDocument,
+        sender: &DID,
+        kind: &CommunityUpdateKind,
+    ) -> Result<(), Error> {
+        let mut heads = IndexSet::<Cid>::from_iter(
+            self.document
+                .audit_log
+                .into_iter()
+                .chain(self.document.concurrent_audit_logs.iter().copied()),
+        );
+
+        let Some(cid) = community.audit_log.filter(|cid| !heads.contains(cid)) else {
+            community.audit_log = self.document.audit_log;
+            community.concurrent_audit_logs = self.document.concurrent_audit_logs.clone();
+            return Ok(());
+        };
+
+        let head = self.get_audit_log_entry(cid).await?;
+
+        if head.actor != *sender || head.action != CommunityAuditAction::from(kind) {
+            return Err(Error::Unauthorized);
+        }
+
+        // Entries that cannot be resolved or verified end the walk, leaving our heads in place
+        let mut visited = HashSet::new();
+        let mut pending = head.parents().collect::<Vec<_>>();
+        let mut walked = 0;
+        while !heads.is_empty() && walked < MAX_COMMUNITY_AUDIT_LOG_CATCH_UP {
+            let Some(cid) = pending.pop() else {
+                break;
+            };
+
+            if !visited.insert(cid) || heads.shift_remove(&cid) {
+                continue;
+            }
+
+            walked += 1;
+
+            let Ok(entry) = self.get_audit_log_entry(cid).await else {
+                break;
+            };
+
+            pending.extend(entry.parents());
+        }
+
+        community.audit_log = Some(cid);
+        community.concurrent_audit_logs = heads.into_iter().collect();
+        Ok(())
+    }
+
+    pub async fn edit_community_channel_name(
+        &mut self,
+        channel_id: Uuid,
+        name: String,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::EditChannels)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        channel_doc.name = name.to_owned();
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::EditedCommunityChannelName {
+                community_id: self.community_id,
+                channel_id,
+                name: name.to_string(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::EditCommunityChannelName {
+                    channel_id,
+                    name: name.to_string(),
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn edit_community_channel_description(
+        &mut self,
+        channel_id: Uuid,
+        description: Option<String>,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::EditChannels)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        channel_doc.description = description.clone();
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::EditedCommunityChannelDescription {
+                community_id: self.community_id,
+                channel_id,
+                description: description.clone(),
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::EditCommunityChannelDescription {
+                    channel_id,
+                    description,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn edit_community_channel_slow_mode(
+        &mut self,
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::EditChannels)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let slow_mode = slow_mode.filter(|duration| !duration.is_zero());
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        channel_doc.slow_mode = slow_mode;
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::EditedCommunityChannelSlowMode {
+                community_id: self.community_id,
+                channel_id,
+                slow_mode,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::EditCommunityChannelSlowMode {
+                    channel_id,
+                    slow_mode,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    /// The source community and channel are validated by the caller, as they are held by another
+    /// community task
+    pub async fn follow_community_channel(
+        &mut self,
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::EditChannels)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+
+        // Announcement channels cannot follow other channels, which prevents copies from being
+        // cross-posted in a loop
+        if channel_doc.channel_type == CommunityChannelType::Announcement {
+            return Err(Error::InvalidCommunityChannelType);
+        }
+
+        channel_doc.following = Some(CommunityChannelFollowDocument {
+            community_id: source_community_id,
+            channel_id: source_channel_id,
+            follower: own_did.clone(),
+        });
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::FollowedCommunityChannel {
+                community_id: self.community_id,
+                channel_id,
+                source_community_id,
+                source_channel_id,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::FollowCommunityChannel {
+                    channel_id,
+                    source_community_id,
+                    source_channel_id,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn unfollow_community_channel(&mut self, channel_id: Uuid) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::EditChannels)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        if channel_doc.following.take().is_none() {
+            return Ok(());
+        }
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::UnfollowedCommunityChannel {
+                community_id: self.community_id,
+                channel_id,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::UnfollowCommunityChannel { channel_id },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn grant_community_channel_permission(
+        &mut self,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        match channel_doc.permissions.get_mut(&permission) {
+            Some(authorized_roles) => {
+                authorized_roles.insert(role_id);
+            }
+            None => {
+                let mut roles = IndexSet::new();
+                roles.insert(role_id);
+                channel_doc.permissions.insert(permission, roles);
+            }
+        }
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::GrantedCommunityChannelPermission {
+                community_id: self.community_id,
+                channel_id,
+                permission,
+                role_id,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::GrantCommunityChannelPermission {
+                    channel_id,
+                    permission,
+                    role_id,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn revoke_community_channel_permission(
+        &mut self,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::RevokePermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        if let Some(authorized_roles) = channel_doc.permissions.get_mut(&permission) {
+            authorized_roles.swap_remove(&role_id);
+        }
+        self.set_document().await?;
+
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::RevokedCommunityChannelPermission {
+                community_id: self.community_id,
+                channel_id,
+                permission,
+                role_id,
+            });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::RevokeCommunityChannelPermission {
+                    channel_id,
+                    permission,
+                    role_id,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn grant_community_channel_permission_for_all(
+        &mut self,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
         if channel_doc.permissions.contains_key(&permission) {
             channel_doc.permissions.swap_remove(&permission);
             self.set_document().await?;
@@ -2597,20 +4208,210 @@ impl CommunityTask {
             return Err(Error::PermissionAlreadyGranted);
         }
 
-        let _ =
-            self.event_broadcast
-                .send(MessageEventKind::GrantedCommunityChannelPermissionForAll {
-                    community_id: self.community_id,
-                    channel_id,
-                    permission,
-                });
+        let _ =
+            self.event_broadcast
+                .send(MessageEventKind::GrantedCommunityChannelPermissionForAll {
+                    community_id: self.community_id,
+                    channel_id,
+                    permission,
+                });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::GrantCommunityChannelPermissionForAll {
+                    channel_id,
+                    permission,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn revoke_community_channel_permission_for_all(
+        &mut self,
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::RevokePermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel_doc = self
+            .document
+            .channels
+            .get_mut(&channel_id.to_string())
+            .ok_or(Error::CommunityChannelDoesntExist)?;
+        channel_doc.permissions.insert(permission, IndexSet::new());
+        self.set_document().await?;
+
+        let _ =
+            self.event_broadcast
+                .send(MessageEventKind::RevokedCommunityChannelPermissionForAll {
+                    community_id: self.community_id,
+                    channel_id,
+                    permission,
+                });
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::RevokeCommunityChannelPermissionForAll {
+                    channel_id,
+                    permission,
+                },
+            },
+            true,
+        )
+        .await
+    }
+
+    pub async fn grant_community_channel_category_permission(
+        &mut self,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let category_doc = self
+            .document
+            .categories
+            .get_mut(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        match category_doc.permissions.get_mut(&permission) {
+            Some(authorized_roles) => {
+                authorized_roles.insert(role_id);
+            }
+            None => {
+                let mut roles = IndexSet::new();
+                roles.insert(role_id);
+                category_doc.permissions.insert(permission, roles);
+            }
+        }
+        self.set_document().await?;
+
+        let _ = self.event_broadcast.send(
+            MessageEventKind::GrantedCommunityChannelCategoryPermission {
+                community_id: self.community_id,
+                category_id,
+                permission,
+                role_id,
+            },
+        );
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::GrantCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn revoke_community_channel_category_permission(
+        &mut self,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::RevokePermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let category_doc = self
+            .document
+            .categories
+            .get_mut(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        if let Some(authorized_roles) = category_doc.permissions.get_mut(&permission) {
+            authorized_roles.swap_remove(&role_id);
+        }
+        self.set_document().await?;
+
+        let _ = self.event_broadcast.send(
+            MessageEventKind::RevokedCommunityChannelCategoryPermission {
+                community_id: self.community_id,
+                category_id,
+                permission,
+                role_id,
+            },
+        );
+
+        self.publish(
+            None,
+            CommunityMessagingEvents::UpdateCommunity {
+                community: self.document.clone(),
+                kind: CommunityUpdateKind::RevokeCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                },
+            },
+            true,
+        )
+        .await
+    }
+    pub async fn grant_community_channel_category_permission_for_all(
+        &mut self,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::GrantPermissions)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let category_doc = self
+            .document
+            .categories
+            .get_mut(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        if category_doc.permissions.contains_key(&permission) {
+            category_doc.permissions.swap_remove(&permission);
+            self.set_document().await?;
+        } else {
+            return Err(Error::PermissionAlreadyGranted);
+        }
+
+        let _ = self.event_broadcast.send(
+            MessageEventKind::GrantedCommunityChannelCategoryPermissionForAll {
+                community_id: self.community_id,
+                category_id,
+                permission,
+            },
+        );
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::GrantCommunityChannelPermissionForAll {
-                    channel_id,
+                kind: CommunityUpdateKind::GrantCommunityChannelCategoryPermissionForAll {
+                    category_id,
                     permission,
                 },
             },
@@ -2618,9 +4419,9 @@ impl CommunityTask {
         )
         .await
     }
-    pub async fn revoke_community_channel_permission_for_all(
+    pub async fn revoke_community_channel_category_permission_for_all(
         &mut self,
-        channel_id: Uuid,
+        category_id: Uuid,
         permission: CommunityChannelPermission,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
@@ -2631,28 +4432,28 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let channel_doc = self
+        let category_doc = self
             .document
-            .channels
-            .get_mut(&channel_id.to_string())
-            .ok_or(Error::CommunityChannelDoesntExist)?;
-        channel_doc.permissions.insert(permission, IndexSet::new());
+            .categories
+            .get_mut(&category_id.to_string())
+            .ok_or(Error::CommunityChannelCategoryDoesntExist)?;
+        category_doc.permissions.insert(permission, IndexSet::new());
         self.set_document().await?;
 
-        let _ =
-            self.event_broadcast
-                .send(MessageEventKind::RevokedCommunityChannelPermissionForAll {
-                    community_id: self.community_id,
-                    channel_id,
-                    permission,
-                });
+        let _ = self.event_broadcast.send(
+            MessageEventKind::RevokedCommunityChannelCategoryPermissionForAll {
+                community_id: self.community_id,
+                category_id,
+                permission,
+            },
+        );
 
         self.publish(
             None,
             CommunityMessagingEvents::UpdateCommunity {
                 community: self.document.clone(),
-                kind: CommunityUpdateKind::RevokeCommunityChannelPermissionForAll {
-                    channel_id,
+                kind: CommunityUpdateKind::RevokeCommunityChannelCategoryPermissionForAll {
+                    category_id,
                     permission,
                 },
             },
@@ -2784,70 +4585,298 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        match self.document.channels.get(&channel_id.to_string()) {
-            None => Err(Error::CommunityChannelDoesntExist),
-            Some(channel) => {
-                channel
-                    .get_messages_reference_stream(&self.ipfs, options)
-                    .await
-            }
-        }
+        match self.document.channels.get(&channel_id.to_string()) {
+            None => Err(Error::CommunityChannelDoesntExist),
+            Some(channel) => {
+                channel
+                    .get_messages_reference_stream(&self.ipfs, options)
+                    .await
+            }
+        }
+    }
+    pub async fn community_channel_message_status(
+        &self,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<MessageStatus, Error> {
+        let own_did = &self.identity.did_key();
+        if !self.document.has_channel_permission(
+            own_did,
+            &CommunityChannelPermission::ViewChannel,
+            channel_id,
+        ) {
+            return Err(Error::Unauthorized);
+        }
+
+        let channel = match self.document.channels.get(&channel_id.to_string()) {
+            None => return Err(Error::CommunityChannelDoesntExist),
+            Some(c) => c,
+        };
+
+        let messages = channel.get_message_list(&self.ipfs).await?;
+
+        if !messages.iter().any(|document| document.id == message_id) {
+            return Err(Error::MessageNotFound);
+        }
+
+        let _list = self
+            .document
+            .participants()
+            .iter()
+            .filter(|did| own_did.ne(did))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        // TODO:
+        // for peer in list {
+        //     if let Some(list) = self.queue.get(&peer) {
+        //         for item in list {
+        //             let Queue { id, m_id, .. } = item;
+        //             if self.document.id() == *id {
+        //                 if let Some(m_id) = m_id {
+        //                     if message_id == *m_id {
+        //                         return Ok(MessageStatus::NotSent);
+        //                     }
+        //                 }
+        //             }
+        //         }
+        //     }
+        // }
+
+        //Not a guarantee that it been sent but for now since the message exist locally and not marked in queue, we will assume it have been sent
+        Ok(MessageStatus::Sent)
+    }
+    pub async fn send_community_channel_message(
+        &mut self,
+        channel_id: Uuid,
+        messages: Vec<String>,
+    ) -> Result<Uuid, Error> {
+        let own_did = &self.identity.did_key();
+        if !self.document.has_channel_permission(
+            own_did,
+            &CommunityChannelPermission::SendMessages,
+            channel_id,
+        ) {
+            return Err(Error::Unauthorized);
+        }
+
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
+        }
+
+        self.check_slow_mode(channel_id, own_did, Utc::now())?;
+
+        if !self.document.channels.contains_key(&channel_id.to_string()) {
+            return Err(Error::CommunityChannelDoesntExist);
+        }
+
+        if messages.is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+
+        let lines_value_length: usize = messages
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim())
+            .map(|s| s.chars().count())
+            .sum();
+
+        if lines_value_length == 0 || lines_value_length > MAX_MESSAGE_SIZE {
+            tracing::error!(
+                current_size = lines_value_length,
+                max = MAX_MESSAGE_SIZE,
+                "length of message is invalid"
+            );
+            return Err(Error::InvalidLength {
+                context: "message".into(),
+                current: lines_value_length,
+                minimum: Some(MIN_MESSAGE_SIZE),
+                maximum: Some(MAX_MESSAGE_SIZE),
+            });
+        }
+
+        let keypair = self.root.keypair();
+        let own_did = self.identity.did_key();
+
+        let keystore = pubkey_or_keystore(&*self)?;
+
+        let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
+            .set_conversation_id(channel_id)
+            .set_sender(own_did.clone())
+            .set_message(messages.clone())?
+            .build()?;
+
+        let message_id = message.id;
+
+        let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
+            Some(c) => c,
+            None => return Err(Error::CommunityChannelDoesntExist),
+        };
+
+        let _message_cid = channel
+            .insert_message_document(&self.ipfs, &message)
+            .await?;
+
+        self.record_sent(channel_id, message.sender.to_did(), message.date);
+        self.crosspost(channel_id, messages);
+
+        // let recipients = self.document.participants();
+
+        self.set_document().await?;
+
+        let event = MessageEventKind::CommunityMessageSent {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+        };
+
+        if let Err(e) = self.event_broadcast.clone().send(event) {
+            tracing::error!(conversation_id=%channel_id, error = %e, "Error broadcasting event");
+        }
+
+        let message_id = message.id;
+
+        let event = CommunityMessagingEvents::New {
+            community_id: self.community_id,
+            channel_id,
+            message,
+        };
+
+        // if !recipients.is_empty() {
+        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
+        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
+        //             let _ = self
+        //                 .message_command
+        //                 .clone()
+        //                 .send(MessageCommand::InsertMessage {
+        //                     peer_id,
+        //                     conversation_id: channel_id,
+        //                     recipients: recipients.iter().cloned().collect(),
+        //                     message_id,
+        //                     message_cid,
+        //                 })
+        //                 .await;
+        //         }
+        //     }
+        // }
+
+        self.publish(Some(message_id), event, true)
+            .await
+            .map(|_| message_id)
     }
-    pub async fn community_channel_message_status(
-        &self,
+    pub async fn edit_community_channel_message(
+        &mut self,
         channel_id: Uuid,
         message_id: Uuid,
-    ) -> Result<MessageStatus, Error> {
+        messages: Vec<String>,
+    ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self.document.has_channel_permission(
             own_did,
-            &CommunityChannelPermission::ViewChannel,
+            &CommunityChannelPermission::SendMessages,
             channel_id,
         ) {
             return Err(Error::Unauthorized);
         }
 
-        let channel = match self.document.channels.get(&channel_id.to_string()) {
-            None => return Err(Error::CommunityChannelDoesntExist),
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
+        }
+
+        let tx = self.event_broadcast.clone();
+
+        if messages.is_empty() {
+            return Err(Error::EmptyMessage);
+        }
+
+        let lines_value_length: usize = messages
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim())
+            .map(|s| s.chars().count())
+            .sum();
+
+        if lines_value_length == 0 || lines_value_length > MAX_MESSAGE_SIZE {
+            tracing::error!(
+                current_size = lines_value_length,
+                max = MAX_MESSAGE_SIZE,
+                "length of message is invalid"
+            );
+            return Err(Error::InvalidLength {
+                context: "message".into(),
+                current: lines_value_length,
+                minimum: Some(MIN_MESSAGE_SIZE),
+                maximum: Some(MAX_MESSAGE_SIZE),
+            });
+        }
+
+        let keypair = self.root.keypair();
+
+        let keystore = pubkey_or_keystore(&*self)?;
+
+        let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
             Some(c) => c,
+            None => return Err(Error::CommunityChannelDoesntExist),
         };
 
-        let messages = channel.get_message_list(&self.ipfs).await?;
+        let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
 
-        if !messages.iter().any(|document| document.id == message_id) {
-            return Err(Error::MessageNotFound);
+        if message_document.sender() != self.identity.did_key() {
+            return Err(Error::InvalidMessage);
         }
+        message_document.set_message(keypair, keystore.as_ref(), &messages)?;
 
-        let _list = self
-            .document
-            .participants()
-            .iter()
-            .filter(|did| own_did.ne(did))
-            .cloned()
-            .collect::<Vec<_>>();
+        let nonce = message_document.nonce_from_message()?;
+        let signature = message_document.signature.expect("message to be signed");
 
-        // TODO:
-        // for peer in list {
-        //     if let Some(list) = self.queue.get(&peer) {
-        //         for item in list {
-        //             let Queue { id, m_id, .. } = item;
-        //             if self.document.id() == *id {
-        //                 if let Some(m_id) = m_id {
-        //                     if message_id == *m_id {
-        //                         return Ok(MessageStatus::NotSent);
-        //                     }
-        //                 }
-        //             }
+        let _message_cid = channel
+            .update_message_document(&self.ipfs, &message_document)
+            .await?;
+
+        // let recipients = self.document.participants();
+
+        self.set_document().await?;
+
+        let _ = tx.send(MessageEventKind::CommunityMessageEdited {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+        });
+
+        let event = CommunityMessagingEvents::Edit {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+            modified: message_document.modified.expect("message to be modified"),
+            lines: messages,
+            nonce: nonce.to_vec(),
+            signature: signature.into(),
+        };
+
+        // if !recipients.is_empty() {
+        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
+        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
+        //             let _ = self
+        //                 .message_command
+        //                 .clone()
+        //                 .send(MessageCommand::InsertMessage {
+        //                     peer_id,
+        //                     conversation_id: channel_id,
+        //                     recipients: recipients.iter().cloned().collect(),
+        //                     message_id,
+        //                     message_cid,
+        //                 })
+        //                 .await;
         //         }
         //     }
         // }
 
-        //Not a guarantee that it been sent but for now since the message exist locally and not marked in queue, we will assume it have been sent
-        Ok(MessageStatus::Sent)
+        self.publish(None, event, true).await
     }
-    pub async fn send_community_channel_message(
+    pub async fn reply_to_community_channel_message(
         &mut self,
         channel_id: Uuid,
+        message_id: Uuid,
         messages: Vec<String>,
     ) -> Result<Uuid, Error> {
         let own_did = &self.identity.did_key();
@@ -2859,10 +4888,14 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        if !self.document.channels.contains_key(&channel_id.to_string()) {
-            return Err(Error::CommunityChannelDoesntExist);
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
         }
 
+        self.check_slow_mode(channel_id, own_did, Utc::now())?;
+
+        let tx = self.event_broadcast.clone();
+
         if messages.is_empty() {
             return Err(Error::EmptyMessage);
         }
@@ -2889,6 +4922,7 @@ impl CommunityTask {
         }
 
         let keypair = self.root.keypair();
+
         let own_did = self.identity.did_key();
 
         let keystore = pubkey_or_keystore(&*self)?;
@@ -2896,41 +4930,177 @@ impl CommunityTask {
         let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
             .set_conversation_id(channel_id)
             .set_sender(own_did.clone())
+            .set_replied(message_id)
             .set_message(messages.clone())?
             .build()?;
 
-        let message_id = message.id;
+        let message_id = message.id;
+
+        let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
+            Some(c) => c,
+            None => return Err(Error::CommunityChannelDoesntExist),
+        };
+
+        let _message_cid = channel
+            .insert_message_document(&self.ipfs, &message)
+            .await?;
+
+        self.record_sent(channel_id, message.sender.to_did(), message.date);
+        self.crosspost(channel_id, messages);
+
+        // let recipients = self.document.participants();
+
+        self.set_document().await?;
+
+        let event = MessageEventKind::CommunityMessageSent {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+        };
+
+        if let Err(e) = tx.send(event) {
+            tracing::error!(id=%self.community_id, error = %e, "Error broadcasting event");
+        }
+
+        let event = CommunityMessagingEvents::New {
+            community_id: self.community_id,
+            channel_id,
+            message,
+        };
+
+        // if !recipients.is_empty() {
+        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
+        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
+        //             let _ = self
+        //                 .message_command
+        //                 .clone()
+        //                 .send(MessageCommand::InsertMessage {
+        //                     peer_id,
+        //                     conversation_id: channel_id,
+        //                     recipients: recipients.iter().cloned().collect(),
+        //                     message_id,
+        //                     message_cid,
+        //                 })
+        //                 .await;
+        //         }
+        //     }
+        // }
+
+        self.publish(Some(message_id), event, true)
+            .await
+            .map(|_| message_id)
+    }
+    pub async fn delete_community_channel_message(
+        &mut self,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::DeleteMessages)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let tx = self.event_broadcast.clone();
+
+        let event = CommunityMessagingEvents::Delete {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+        };
+
+        let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
+            Some(c) => c,
+            None => return Err(Error::CommunityChannelDoesntExist),
+        };
+
+        channel.delete_message(&self.ipfs, message_id).await?;
+
+        self.set_document().await?;
+
+        // if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
+        //     for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
+        //         let _ = self
+        //             .message_command
+        //             .clone()
+        //             .send(MessageCommand::RemoveMessage {
+        //                 peer_id,
+        //                 conversation_id: channel_id,
+        //                 message_id,
+        //             })
+        //             .await;
+        //     }
+        // }
+
+        let _ = tx.send(MessageEventKind::CommunityMessageDeleted {
+            community_id: self.community_id,
+            channel_id,
+            message_id,
+        });
+        self.publish(None, event, true).await?;
+        Ok(())
+    }
+    pub async fn pin_community_channel_message(
+        &mut self,
+        channel_id: Uuid,
+        message_id: Uuid,
+        state: PinState,
+    ) -> Result<(), Error> {
+        let own_did = &self.identity.did_key();
+        if !self
+            .document
+            .has_permission(own_did, &CommunityPermission::PinMessages)
+        {
+            return Err(Error::Unauthorized);
+        }
+
+        let tx = self.event_broadcast.clone();
+
+        let own_did = self.identity.did_key();
 
         let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
             Some(c) => c,
             None => return Err(Error::CommunityChannelDoesntExist),
         };
 
+        let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
+
+        let event = match state {
+            PinState::Pin => {
+                if message_document.pinned() {
+                    return Ok(());
+                }
+                message_document.set_pin(true);
+                MessageEventKind::CommunityMessagePinned {
+                    community_id: self.community_id,
+                    channel_id,
+                    message_id,
+                }
+            }
+            PinState::Unpin => {
+                if !message_document.pinned() {
+                    return Ok(());
+                }
+                message_document.set_pin(false);
+                MessageEventKind::CommunityMessageUnpinned {
+                    community_id: self.community_id,
+                    channel_id,
+                    message_id,
+                }
+            }
+        };
+
         let _message_cid = channel
-            .insert_message_document(&self.ipfs, &message)
+            .update_message_document(&self.ipfs, &message_document)
             .await?;
 
         // let recipients = self.document.participants();
 
         self.set_document().await?;
 
-        let event = MessageEventKind::CommunityMessageSent {
-            community_id: self.community_id,
-            channel_id,
-            message_id,
-        };
-
-        if let Err(e) = self.event_broadcast.clone().send(event) {
-            tracing::error!(conversation_id=%channel_id, error = %e, "Error broadcasting event");
-        }
-
-        let message_id = message.id;
-
-        let event = CommunityMessagingEvents::New {
-            community_id: self.community_id,
-            channel_id,
-            message,
-        };
+        let _ = tx.send(event);
 
         // if !recipients.is_empty() {
         //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
@@ -2950,55 +5120,41 @@ impl CommunityTask {
         //     }
         // }
 
-        self.publish(Some(message_id), event, true)
-            .await
-            .map(|_| message_id)
+        let event = CommunityMessagingEvents::Pin {
+            community_id: self.community_id,
+            channel_id,
+            member: own_did,
+            message_id,
+            state,
+        };
+
+        self.publish(None, event, true).await
     }
-    pub async fn edit_community_channel_message(
+    pub async fn react_to_community_channel_message(
         &mut self,
         channel_id: Uuid,
         message_id: Uuid,
-        messages: Vec<String>,
+        state: ReactionState,
+        emoji: String,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
         if !self.document.has_channel_permission(
             own_did,
-            &CommunityChannelPermission::SendMessages,
+            &CommunityChannelPermission::ViewChannel,
             channel_id,
         ) {
             return Err(Error::Unauthorized);
         }
 
-        let tx = self.event_broadcast.clone();
-
-        if messages.is_empty() {
-            return Err(Error::EmptyMessage);
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
         }
 
-        let lines_value_length: usize = messages
-            .iter()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.trim())
-            .map(|s| s.chars().count())
-            .sum();
-
-        if lines_value_length == 0 || lines_value_length > MAX_MESSAGE_SIZE {
-            tracing::error!(
-                current_size = lines_value_length,
-                max = MAX_MESSAGE_SIZE,
-                "length of message is invalid"
-            );
-            return Err(Error::InvalidLength {
-                context: "message".into(),
-                current: lines_value_length,
-                minimum: Some(MIN_MESSAGE_SIZE),
-                maximum: Some(MAX_MESSAGE_SIZE),
-            });
-        }
+        let tx = self.event_broadcast.clone();
 
-        let keypair = self.root.keypair();
+        let own_did = self.identity.did_key();
 
-        let keystore = pubkey_or_keystore(&*self)?;
+        // let recipients = self.document.participants();
 
         let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
             Some(c) => c,
@@ -3007,38 +5163,55 @@ impl CommunityTask {
 
         let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
 
-        if message_document.sender() != self.identity.did_key() {
-            return Err(Error::InvalidMessage);
-        }
-        message_document.set_message(keypair, keystore.as_ref(), &messages)?;
+        let message_cid;
 
-        let nonce = message_document.nonce_from_message()?;
-        let signature = message_document.signature.expect("message to be signed");
+        match state {
+            ReactionState::Add => {
+                message_document.add_reaction(&emoji, own_did.clone())?;
 
-        let _message_cid = channel
-            .update_message_document(&self.ipfs, &message_document)
-            .await?;
+                message_cid = channel
+                    .update_message_document(&self.ipfs, &message_document)
+                    .await?;
+                self.set_document().await?;
 
-        // let recipients = self.document.participants();
+                _ = tx.send(MessageEventKind::CommunityMessageReactionAdded {
+                    community_id: self.community_id,
+                    channel_id,
+                    message_id,
+                    did_key: own_did.clone(),
+                    reaction: emoji.clone(),
+                });
+            }
+            ReactionState::Remove => {
+                message_document.remove_reaction(&emoji, own_did.clone())?;
 
-        self.set_document().await?;
+                message_cid = channel
+                    .update_message_document(&self.ipfs, &message_document)
+                    .await?;
 
-        let _ = tx.send(MessageEventKind::CommunityMessageEdited {
-            community_id: self.community_id,
-            channel_id,
-            message_id,
-        });
+                self.set_document().await?;
 
-        let event = CommunityMessagingEvents::Edit {
+                let _ = tx.send(MessageEventKind::CommunityMessageReactionRemoved {
+                    community_id: self.community_id,
+                    channel_id,
+                    message_id,
+                    did_key: own_did.clone(),
+                    reaction: emoji.clone(),
+                });
+            }
+        }
+
+        let event = CommunityMessagingEvents::React {
             community_id: self.community_id,
             channel_id,
+            reactor: own_did,
             message_id,
-            modified: message_document.modified.expect("message to be modified"),
-            lines: messages,
-            nonce: nonce.to_vec(),
-            signature: signature.into(),
+            state,
+            emoji,
         };
 
+        _ = message_cid;
+
         // if !recipients.is_empty() {
         //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
         //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
@@ -3059,11 +5232,10 @@ impl CommunityTask {
 
         self.publish(None, event, true).await
     }
-    pub async fn reply_to_community_channel_message(
+    pub async fn send_community_channel_poll(
         &mut self,
         channel_id: Uuid,
-        message_id: Uuid,
-        messages: Vec<String>,
+        poll: warp::raygun::Poll,
     ) -> Result<Uuid, Error> {
         let own_did = &self.identity.did_key();
         if !self.document.has_channel_permission(
@@ -3074,35 +5246,17 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let tx = self.event_broadcast.clone();
-
-        if messages.is_empty() {
-            return Err(Error::EmptyMessage);
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
         }
 
-        let lines_value_length: usize = messages
-            .iter()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.trim())
-            .map(|s| s.chars().count())
-            .sum();
+        self.check_slow_mode(channel_id, own_did, Utc::now())?;
 
-        if lines_value_length == 0 || lines_value_length > MAX_MESSAGE_SIZE {
-            tracing::error!(
-                current_size = lines_value_length,
-                max = MAX_MESSAGE_SIZE,
-                "length of message is invalid"
-            );
-            return Err(Error::InvalidLength {
-                context: "message".into(),
-                current: lines_value_length,
-                minimum: Some(MIN_MESSAGE_SIZE),
-                maximum: Some(MAX_MESSAGE_SIZE),
-            });
+        if !self.document.channels.contains_key(&channel_id.to_string()) {
+            return Err(Error::CommunityChannelDoesntExist);
         }
 
         let keypair = self.root.keypair();
-
         let own_did = self.identity.did_key();
 
         let keystore = pubkey_or_keystore(&*self)?;
@@ -3110,8 +5264,8 @@ impl CommunityTask {
         let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
             .set_conversation_id(channel_id)
             .set_sender(own_did.clone())
-            .set_replied(message_id)
-            .set_message(messages)?
+            .set_message(vec![poll.question().to_string()])?
+            .set_poll(poll)?
             .build()?;
 
         let message_id = message.id;
@@ -3121,11 +5275,11 @@ impl CommunityTask {
             None => return Err(Error::CommunityChannelDoesntExist),
         };
 
-        let _message_cid = channel
+        channel
             .insert_message_document(&self.ipfs, &message)
             .await?;
 
-        // let recipients = self.document.participants();
+        self.record_sent(channel_id, message.sender.to_did(), message.date);
 
         self.set_document().await?;
 
@@ -3135,8 +5289,8 @@ impl CommunityTask {
             message_id,
         };
 
-        if let Err(e) = tx.send(event) {
-            tracing::error!(id=%self.community_id, error = %e, "Error broadcasting event");
+        if let Err(e) = self.event_broadcast.clone().send(event) {
+            tracing::error!(conversation_id=%channel_id, error = %e, "Error broadcasting event");
         }
 
         let event = CommunityMessagingEvents::New {
@@ -3145,96 +5299,75 @@ impl CommunityTask {
             message,
         };
 
-        // if !recipients.is_empty() {
-        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
-        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
-        //             let _ = self
-        //                 .message_command
-        //                 .clone()
-        //                 .send(MessageCommand::InsertMessage {
-        //                     peer_id,
-        //                     conversation_id: channel_id,
-        //                     recipients: recipients.iter().cloned().collect(),
-        //                     message_id,
-        //                     message_cid,
-        //                 })
-        //                 .await;
-        //         }
-        //     }
-        // }
-
         self.publish(Some(message_id), event, true)
             .await
             .map(|_| message_id)
     }
-    pub async fn delete_community_channel_message(
+    pub async fn vote_community_channel_poll(
         &mut self,
         channel_id: Uuid,
         message_id: Uuid,
+        options: Vec<usize>,
     ) -> Result<(), Error> {
         let own_did = &self.identity.did_key();
-        if !self
-            .document
-            .has_permission(own_did, &CommunityPermission::DeleteMessages)
-        {
+        if !self.document.has_channel_permission(
+            own_did,
+            &CommunityChannelPermission::ViewChannel,
+            channel_id,
+        ) {
             return Err(Error::Unauthorized);
         }
 
-        let tx = self.event_broadcast.clone();
+        let keypair = self.root.keypair();
 
-        let event = CommunityMessagingEvents::Delete {
-            community_id: self.community_id,
-            channel_id,
-            message_id,
-        };
+        let keystore = pubkey_or_keystore(&*self)?;
 
         let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
             Some(c) => c,
             None => return Err(Error::CommunityChannelDoesntExist),
         };
 
-        channel.delete_message(&self.ipfs, message_id).await?;
+        let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
+
+        let poll = message_document.poll(keypair, keystore.as_ref())?;
+
+        let vote = PollVoteDocument::new(
+            keypair,
+            channel_id,
+            message_id,
+            options.into_iter().collect(),
+        )?;
+
+        message_document.apply_poll_vote(&poll, vote.clone(), Utc::now())?;
+
+        channel
+            .update_message_document(&self.ipfs, &message_document)
+            .await?;
 
         self.set_document().await?;
 
-        // if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
-        //     for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
-        //         let _ = self
-        //             .message_command
-        //             .clone()
-        //             .send(MessageCommand::RemoveMessage {
-        //                 peer_id,
-        //                 conversation_id: channel_id,
-        //                 message_id,
-        //             })
-        //             .await;
-        //     }
-        // }
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::CommunityPollUpdated {
+                community_id: self.community_id,
+                channel_id,
+                message_id,
+            });
 
-        let _ = tx.send(MessageEventKind::CommunityMessageDeleted {
+        let event = CommunityMessagingEvents::PollVote {
             community_id: self.community_id,
             channel_id,
             message_id,
-        });
-        self.publish(None, event, true).await?;
-        Ok(())
+            vote,
+        };
+
+        self.publish(None, event, true).await
     }
-    pub async fn pin_community_channel_message(
+    pub async fn close_community_channel_poll(
         &mut self,
         channel_id: Uuid,
         message_id: Uuid,
-        state: PinState,
     ) -> Result<(), Error> {
-        let own_did = &self.identity.did_key();
-        if !self
-            .document
-            .has_permission(own_did, &CommunityPermission::PinMessages)
-        {
-            return Err(Error::Unauthorized);
-        }
-
-        let tx = self.event_broadcast.clone();
-
         let own_did = self.identity.did_key();
 
         let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
@@ -3244,76 +5377,42 @@ impl CommunityTask {
 
         let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
 
-        let event = match state {
-            PinState::Pin => {
-                if message_document.pinned() {
-                    return Ok(());
-                }
-                message_document.set_pin(true);
-                MessageEventKind::CommunityMessagePinned {
-                    community_id: self.community_id,
-                    channel_id,
-                    message_id,
-                }
-            }
-            PinState::Unpin => {
-                if !message_document.pinned() {
-                    return Ok(());
-                }
-                message_document.set_pin(false);
-                MessageEventKind::CommunityMessageUnpinned {
-                    community_id: self.community_id,
-                    channel_id,
-                    message_id,
-                }
-            }
-        };
+        if message_document.sender() != own_did {
+            return Err(Error::Unauthorized);
+        }
 
-        let _message_cid = channel
+        let closed = Utc::now();
+
+        message_document.close_poll(closed)?;
+
+        channel
             .update_message_document(&self.ipfs, &message_document)
             .await?;
 
-        // let recipients = self.document.participants();
-
         self.set_document().await?;
 
-        let _ = tx.send(event);
-
-        // if !recipients.is_empty() {
-        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
-        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
-        //             let _ = self
-        //                 .message_command
-        //                 .clone()
-        //                 .send(MessageCommand::InsertMessage {
-        //                     peer_id,
-        //                     conversation_id: channel_id,
-        //                     recipients: recipients.iter().cloned().collect(),
-        //                     message_id,
-        //                     message_cid,
-        //                 })
-        //                 .await;
-        //         }
-        //     }
-        // }
+        let _ = self
+            .event_broadcast
+            .send(MessageEventKind::CommunityPollUpdated {
+                community_id: self.community_id,
+                channel_id,
+                message_id,
+            });
 
-        let event = CommunityMessagingEvents::Pin {
+        let event = CommunityMessagingEvents::PollClose {
             community_id: self.community_id,
             channel_id,
-            member: own_did,
             message_id,
-            state,
+            closed,
         };
 
         self.publish(None, event, true).await
     }
-    pub async fn react_to_community_channel_message(
-        &mut self,
+    pub async fn community_channel_poll_results(
+        &self,
         channel_id: Uuid,
         message_id: Uuid,
-        state: ReactionState,
-        emoji: String,
-    ) -> Result<(), Error> {
+    ) -> Result<PollResults, Error> {
         let own_did = &self.identity.did_key();
         if !self.document.has_channel_permission(
             own_did,
@@ -3323,87 +5422,18 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
-        let tx = self.event_broadcast.clone();
-
-        let own_did = self.identity.did_key();
+        let keypair = self.root.keypair();
 
-        // let recipients = self.document.participants();
+        let keystore = pubkey_or_keystore(self)?;
 
-        let channel = match self.document.channels.get_mut(&channel_id.to_string()) {
+        let channel = match self.document.channels.get(&channel_id.to_string()) {
             Some(c) => c,
             None => return Err(Error::CommunityChannelDoesntExist),
         };
 
-        let mut message_document = channel.get_message_document(&self.ipfs, message_id).await?;
-
-        let message_cid;
-
-        match state {
-            ReactionState::Add => {
-                message_document.add_reaction(&emoji, own_did.clone())?;
-
-                message_cid = channel
-                    .update_message_document(&self.ipfs, &message_document)
-                    .await?;
-                self.set_document().await?;
-
-                _ = tx.send(MessageEventKind::CommunityMessageReactionAdded {
-                    community_id: self.community_id,
-                    channel_id,
-                    message_id,
-                    did_key: own_did.clone(),
-                    reaction: emoji.clone(),
-                });
-            }
-            ReactionState::Remove => {
-                message_document.remove_reaction(&emoji, own_did.clone())?;
-
-                message_cid = channel
-                    .update_message_document(&self.ipfs, &message_document)
-                    .await?;
-
-                self.set_document().await?;
-
-                let _ = tx.send(MessageEventKind::CommunityMessageReactionRemoved {
-                    community_id: self.community_id,
-                    channel_id,
-                    message_id,
-                    did_key: own_did.clone(),
-                    reaction: emoji.clone(),
-                });
-            }
-        }
-
-        let event = CommunityMessagingEvents::React {
-            community_id: self.community_id,
-            channel_id,
-            reactor: own_did,
-            message_id,
-            state,
-            emoji,
-        };
-
-        _ = message_cid;
-
-        // if !recipients.is_empty() {
-        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
-        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
-        //             let _ = self
-        //                 .message_command
-        //                 .clone()
-        //                 .send(MessageCommand::InsertMessage {
-        //                     peer_id,
-        //                     conversation_id: channel_id,
-        //                     recipients: recipients.iter().cloned().collect(),
-        //                     message_id,
-        //                     message_cid,
-        //                 })
-        //                 .await;
-        //         }
-        //     }
-        // }
+        let message_document = channel.get_message_document(&self.ipfs, message_id).await?;
 
-        self.publish(None, event, true).await
+        message_document.poll_results(keypair, keystore.as_ref())
     }
     pub async fn send_community_channel_messsage_event(
         &mut self,
@@ -3465,6 +5495,12 @@ impl CommunityTask {
             return Err(Error::Unauthorized);
         }
 
+        if self.document.is_timed_out(own_did) {
+            return Err(Error::CommunityMemberTimedOut);
+        }
+
+        self.check_slow_mode(channel_id, own_did, Utc::now())?;
+
         let keystore = pubkey_or_keystore(&*self)?;
 
         let stream = AttachmentStream::new(
@@ -3582,6 +5618,8 @@ impl CommunityTask {
             .insert_message_document(&self.ipfs, &message)
             .await?;
 
+        self.record_sent(channel_id, message.sender.to_did(), message.date);
+
         // let recipients = self.document.participants().clone();
 
         self.set_document().await?;
@@ -3629,6 +5667,19 @@ impl CommunityTask {
         event: CommunityMessagingEvents,
         queue: bool,
     ) -> Result<(), Error> {
+        // Every change to the community is recorded before being sent out so that the document
+        // peers receive points to the latest entry in the audit log
+        let event = match event {
+            CommunityMessagingEvents::UpdateCommunity { kind, .. } => {
+                self.append_audit_log(&kind).await?;
+                CommunityMessagingEvents::UpdateCommunity {
+                    community: self.document.clone(),
+                    kind,
+                }
+            }
+            event => event,
+        };
+
         let keypair = self.root.keypair();
         let own_did = self.identity.did_key();
 
@@ -3746,6 +5797,8 @@ async fn message_event(
     this: &mut CommunityTask,
     sender: &DID,
     events: CommunityMessagingEvents,
+    filter: bool,
+    replay: bool,
 ) -> Result<(), Error> {
     let community_id = this.community_id;
 
@@ -3758,10 +5811,14 @@ async fn message_event(
         CommunityMessagingEvents::New {
             community_id,
             channel_id,
-            message,
+            mut message,
         } => {
             message.verify()?;
 
+            // The close date is not covered by a signature, so a poll is only closed by a
+            // `PollClose` from its sender
+            message.poll_closed = None;
+
             let message_id = message.id;
 
             if !this
@@ -3772,6 +5829,34 @@ async fn message_event(
                 return Err(Error::IdentityDoesntExist);
             }
 
+            if this.document.is_timed_out(&message.sender.to_did()) {
+                return Err(Error::CommunityMemberTimedOut);
+            }
+
+            // Messages held back until a key exchange completed or released from quarantine are
+            // delivered together, so they are not rejected for it
+            let date = slow_mode_date(message.date, Utc::now());
+            if !replay {
+                this.check_slow_mode(
+                    channel_id,
+                    &message.sender.to_did(),
+                    date + chrono::Duration::milliseconds(SLOW_MODE_SKEW),
+                )?;
+            }
+
+            if this
+                .document
+                .channels
+                .get(&channel_id.to_string())
+                .is_some_and(|channel| channel.channel_type == CommunityChannelType::Announcement)
+                && !this.document.has_permission(
+                    &message.sender.to_did(),
+                    &CommunityPermission::PostAnnouncements,
+                )
+            {
+                return Err(Error::Unauthorized);
+            }
+
             let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
                 Some(c) => c,
                 None => return Err(Error::CommunityChannelDoesntExist),
@@ -3806,10 +5891,36 @@ async fn message_event(
                 });
             }
 
+            if filter
+                && !this
+                    .filter_message(
+                        sender,
+                        channel_id,
+                        FilterKind::New,
+                        &resolved_message,
+                        || CommunityMessagingEvents::New {
+                            community_id,
+                            channel_id,
+                            message: message.clone(),
+                        },
+                    )
+                    .await
+            {
+                return Ok(());
+            }
+
+            let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
+                Some(c) => c,
+                None => return Err(Error::CommunityChannelDoesntExist),
+            };
+
             channel
                 .insert_message_document(&this.ipfs, &message)
                 .await?;
 
+            this.record_sent(channel_id, message.sender.to_did(), date);
+            this.crosspost(channel_id, resolved_message.lines().to_vec());
+
             this.set_document().await?;
 
             if let Err(e) = this
@@ -3832,6 +5943,10 @@ async fn message_event(
             nonce,
             signature,
         } => {
+            if this.document.is_timed_out(sender) {
+                return Err(Error::CommunityMemberTimedOut);
+            }
+
             let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
                 Some(c) => c,
                 None => return Err(Error::CommunityChannelDoesntExist),
@@ -3984,6 +6099,10 @@ async fn message_event(
             state,
             emoji,
         } => {
+            if this.document.is_timed_out(&reactor) {
+                return Err(Error::CommunityMemberTimedOut);
+            }
+
             let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
                 Some(c) => c,
                 None => return Err(Error::CommunityChannelDoesntExist),
@@ -4037,6 +6156,86 @@ async fn message_event(
                 }
             }
         }
+        CommunityMessagingEvents::PollVote {
+            community_id,
+            channel_id,
+            message_id,
+            vote,
+        } => {
+            if vote.voter != *sender
+                || !this.document.has_channel_permission(
+                    sender,
+                    &CommunityChannelPermission::ViewChannel,
+                    channel_id,
+                )
+            {
+                return Err(Error::Unauthorized);
+            }
+
+            let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
+                Some(c) => c,
+                None => return Err(Error::CommunityChannelDoesntExist),
+            };
+
+            let mut message_document = channel.get_message_document(&this.ipfs, message_id).await?;
+
+            let poll = message_document.poll(keypair, keystore.as_ref())?;
+
+            message_document.apply_poll_vote(&poll, vote, Utc::now())?;
+
+            channel
+                .update_message_document(&this.ipfs, &message_document)
+                .await?;
+
+            this.set_document().await?;
+
+            if let Err(e) = this
+                .event_broadcast
+                .send(MessageEventKind::CommunityPollUpdated {
+                    community_id,
+                    channel_id,
+                    message_id,
+                })
+            {
+                tracing::warn!(%channel_id, error = %e, "Error broadcasting event");
+            }
+        }
+        CommunityMessagingEvents::PollClose {
+            community_id,
+            channel_id,
+            message_id,
+            closed,
+        } => {
+            let channel = match this.document.channels.get_mut(&channel_id.to_string()) {
+                Some(c) => c,
+                None => return Err(Error::CommunityChannelDoesntExist),
+            };
+
+            let mut message_document = channel.get_message_document(&this.ipfs, message_id).await?;
+
+            if message_document.sender() != *sender {
+                return Err(Error::Unauthorized);
+            }
+
+            message_document.close_poll(closed)?;
+
+            channel
+                .update_message_document(&this.ipfs, &message_document)
+                .await?;
+
+            this.set_document().await?;
+
+            if let Err(e) = this
+                .event_broadcast
+                .send(MessageEventKind::CommunityPollUpdated {
+                    community_id,
+                    channel_id,
+                    message_id,
+                })
+            {
+                tracing::warn!(%channel_id, error = %e, "Error broadcasting event");
+            }
+        }
         CommunityMessagingEvents::JoinedCommunity { community_id, user } => {
             if let Err(e) = this
                 .event_broadcast
@@ -4045,7 +6244,11 @@ async fn message_event(
                 tracing::warn!(%community_id, error = %e, "Error broadcasting event");
             }
         }
-        CommunityMessagingEvents::UpdateCommunity { community, kind } => {
+        CommunityMessagingEvents::UpdateCommunity {
+            mut community,
+            kind,
+        } => {
+            this.merge_audit_log(&mut community, sender, &kind).await?;
             match kind {
                 CommunityUpdateKind::LeaveCommunity => {
                     this.replace_document(community).await?;
@@ -4081,14 +6284,25 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::EditCommunityInvite { invite_id } => {
+                    let exhausted = community
+                        .invites
+                        .get(&invite_id.to_string())
+                        .is_some_and(|invite| invite.is_exhausted());
+                    if exhausted {
+                        community.invites.swap_remove(&invite_id.to_string());
+                    }
                     this.replace_document(community).await?;
-                    if let Err(e) =
-                        this.event_broadcast
-                            .send(MessageEventKind::EditedCommunityInvite {
-                                community_id,
-                                invite_id,
-                            })
-                    {
+                    let event = match exhausted {
+                        true => MessageEventKind::DeletedCommunityInvite {
+                            community_id,
+                            invite_id,
+                        },
+                        false => MessageEventKind::EditedCommunityInvite {
+                            community_id,
+                            invite_id,
+                        },
+                    };
+                    if let Err(e) = this.event_broadcast.send(event) {
                         tracing::warn!(%community_id, error = %e, "Error broadcasting event");
                     }
                 }
@@ -4117,6 +6331,22 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::EditCommunityRole { role_id } => {
+                    let positions = [&this.document, &community].map(|document| {
+                        document
+                            .roles
+                            .get(&role_id.to_string())
+                            .map(|role| role.position)
+                    });
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::EditRoles)
+                        || positions
+                            .into_iter()
+                            .flatten()
+                            .any(|position| !this.document.outranks_position(sender, position))
+                    {
+                        return Err(Error::Unauthorized);
+                    }
                     this.replace_document(community).await?;
                     if let Err(e) =
                         this.event_broadcast
@@ -4129,6 +6359,13 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::GrantCommunityRole { role_id, user } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::GrantRoles)
+                        || !this.document.can_manage_role(sender, role_id)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
                     this.replace_document(community).await?;
                     if let Err(e) =
                         this.event_broadcast
@@ -4142,6 +6379,13 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::RevokeCommunityRole { role_id, user } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::RevokeRoles)
+                        || !this.document.can_manage_role(sender, role_id)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
                     this.replace_document(community).await?;
                     if let Err(e) =
                         this.event_broadcast
@@ -4179,6 +6423,15 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::EditCommunityName { name } => {
+                    if name.is_empty() || name.len() > MAX_COMMUNITY_NAME_LENGTH {
+                        return Err(Error::InvalidLength {
+                            context: "name".into(),
+                            minimum: Some(1),
+                            maximum: Some(MAX_COMMUNITY_NAME_LENGTH),
+                            current: name.len(),
+                        });
+                    }
+
                     this.replace_document(community).await?;
                     if let Err(e) = this
                         .event_broadcast
@@ -4288,6 +6541,13 @@ async fn message_event(
                     }
                 }
                 CommunityUpdateKind::RemoveCommunityMember { member } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::RemoveMembers)
+                        || !this.document.outranks(sender, &member)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
                     this.replace_document(community).await?;
                     if let Err(e) =
                         this.event_broadcast
@@ -4299,6 +6559,117 @@ async fn message_event(
                         tracing::warn!(%community_id, error = %e, "Error broadcasting event");
                     }
                 }
+                CommunityUpdateKind::BanCommunityMember { member } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::BanMembers)
+                        || !this.document.outranks(sender, &member)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::BannedCommunityMember {
+                                community_id,
+                                member,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::UnbanCommunityMember { member } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::BanMembers)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::UnbannedCommunityMember {
+                                community_id,
+                                member,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::TimeoutCommunityMember { member, until } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::TimeoutMembers)
+                        || !this.document.outranks(sender, &member)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::CommunityMemberTimedOut {
+                                community_id,
+                                member,
+                                until,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::TransferCommunityOwnership { new_owner } => {
+                    if sender != &this.document.owner
+                        || community.pending_owner() != new_owner.as_ref()
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::OfferedCommunityOwnership {
+                                community_id,
+                                new_owner,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::AcceptCommunityOwnership { previous_owner } => {
+                    // The transfer itself is verified against the offer signed by the previous owner
+                    // when the document is replaced
+                    if &community.owner != sender || previous_owner != this.document.owner {
+                        return Err(Error::Unauthorized);
+                    }
+
+                    let new_owner = community.owner.clone();
+                    this.replace_document(community).await?;
+
+                    // The directory only accepts listings from the owner that published them, so
+                    // the previous owner removes it for the new owner to publish
+                    if previous_owner == own_did && this.listing.is_some() {
+                        if let Err(e) = community_directory_request(
+                            &this.ipfs,
+                            this.root.keypair(),
+                            &this.discovery,
+                            CommunityDirectory::Remove { community_id },
+                        )
+                        .await
+                        {
+                            tracing::warn!(%community_id, error = %e, "unable to remove community listing");
+                        }
+                        this.listing.take();
+                    }
+
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::TransferredCommunityOwnership {
+                                community_id,
+                                previous_owner,
+                                new_owner,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
                 CommunityUpdateKind::EditCommunityChannelName { channel_id, name } => {
                     this.replace_document(community).await?;
                     if let Err(e) =
@@ -4327,6 +6698,69 @@ async fn message_event(
                         tracing::warn!(%community_id, error = %e, "Error broadcasting event");
                     }
                 }
+                CommunityUpdateKind::EditCommunityChannelSlowMode {
+                    channel_id,
+                    slow_mode,
+                } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::EditChannels)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::EditedCommunityChannelSlowMode {
+                            community_id,
+                            channel_id,
+                            slow_mode,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::FollowCommunityChannel {
+                    channel_id,
+                    source_community_id,
+                    source_channel_id,
+                } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::EditChannels)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::FollowedCommunityChannel {
+                                community_id,
+                                channel_id,
+                                source_community_id,
+                                source_channel_id,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::UnfollowCommunityChannel { channel_id } => {
+                    if !this
+                        .document
+                        .has_permission(sender, &CommunityPermission::EditChannels)
+                    {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::UnfollowedCommunityChannel {
+                                community_id,
+                                channel_id,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
                 CommunityUpdateKind::GrantCommunityChannelPermission {
                     channel_id,
                     permission,
@@ -4391,6 +6825,134 @@ async fn message_event(
                         tracing::warn!(%community_id, error = %e, "Error broadcasting event");
                     }
                 }
+                CommunityUpdateKind::CreateCommunityChannelCategory { category } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::CreatedCommunityChannelCategory {
+                            community_id,
+                            category: CommunityChannelCategory::from(category),
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::DeleteCommunityChannelCategory { category_id } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::DeletedCommunityChannelCategory {
+                            community_id,
+                            category_id,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::GrantCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::GrantedCommunityChannelCategoryPermission {
+                            community_id,
+                            category_id,
+                            permission,
+                            role_id,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::RevokeCommunityChannelCategoryPermission {
+                    category_id,
+                    permission,
+                    role_id,
+                } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::RevokedCommunityChannelCategoryPermission {
+                            community_id,
+                            category_id,
+                            permission,
+                            role_id,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::GrantCommunityChannelCategoryPermissionForAll {
+                    category_id,
+                    permission,
+                } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::GrantedCommunityChannelCategoryPermissionForAll {
+                            community_id,
+                            category_id,
+                            permission,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::RevokeCommunityChannelCategoryPermissionForAll {
+                    category_id,
+                    permission,
+                } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::RevokedCommunityChannelCategoryPermissionForAll {
+                            community_id,
+                            category_id,
+                            permission,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::ReorderCommunityChannels => {
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::CommunityChannelsReordered {
+                                community_id,
+                                channels: this.document.ordered_channels(),
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::SetCommunityListing { invite_id } => {
+                    if sender != &this.document.owner {
+                        return Err(Error::Unauthorized);
+                    }
+                    this.replace_document(community).await?;
+                    if let Err(e) =
+                        this.event_broadcast
+                            .send(MessageEventKind::EditedCommunityListing {
+                                community_id,
+                                invite_id,
+                            })
+                    {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
+                CommunityUpdateKind::MoveCommunityChannelToCategory {
+                    channel_id,
+                    category_id,
+                } => {
+                    this.replace_document(community).await?;
+                    if let Err(e) = this.event_broadcast.send(
+                        MessageEventKind::MovedCommunityChannelToCategory {
+                            community_id,
+                            channel_id,
+                            category_id,
+                        },
+                    ) {
+                        tracing::warn!(%community_id, error = %e, "Error broadcasting event");
+                    }
+                }
             }
         }
         _ => {}
@@ -4399,6 +6961,60 @@ async fn message_event(
     Ok(())
 }
 
+/// Sends a request to the shuttle nodes hosting the community directory, returning the first
+/// successful response
+pub async fn community_directory_request(
+    ipfs: &Ipfs,
+    keypair: &Keypair,
+    discovery: &Discovery,
+    request: CommunityDirectory,
+) -> Result<ShuttleMessageResponse, Error> {
+    let config::Discovery::Shuttle { addresses } = discovery.discovery_config() else {
+        return Err(Error::OtherWithContext(
+            "community directory requires a shuttle node".into(),
+        ));
+    };
+
+    let payload = PayloadBuilder::new(keypair, ShuttleMessageRequest::from(request)).build()?;
+    let bytes = payload.to_bytes()?;
+
+    for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
+        let response = match ipfs
+            .send_request(peer_id, (protocols::SHUTTLE_MESSAGE, bytes.clone()))
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!(error = %e, %peer_id, "unable to send request to shuttle node");
+                continue;
+            }
+        };
+
+        let payload: PayloadMessage<ShuttleMessageResponse> =
+            match PayloadMessage::from_bytes(&response) {
+                Ok(payload) => payload,
+                Err(e) => {
+                    tracing::error!(error = %e, %peer_id, "unable to process payload");
+                    continue;
+                }
+            };
+
+        match payload.message(None) {
+            Ok(ShuttleMessageResponse::Error(e)) => {
+                tracing::error!(error = %e, %peer_id, "error handling request");
+            }
+            Ok(response) => return Ok(response),
+            Err(e) => {
+                tracing::error!(%peer_id, error = %e, "invalid message");
+            }
+        }
+    }
+
+    Err(Error::OtherWithContext(
+        "no shuttle node was able to handle the request".into(),
+    ))
+}
+
 async fn process_request_response_event(
     this: &mut CommunityTask,
     req: Message,
@@ -4560,7 +7176,7 @@ async fn process_pending_payload(this: &mut CommunityTask) {
             }
         };
 
-        if let Err(e) = message_event(this, &sender, event).await {
+        if let Err(e) = message_event(this, &sender, event, true, true).await {
             tracing::error!(name = "process_pending_payload", %conversation_id, %sender, error = %e, "failed to process message")
         }
     }
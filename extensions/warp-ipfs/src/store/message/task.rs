@@ -1,4 +1,5 @@
 use bytes::Bytes;
+use chrono::Utc;
 use either::Either;
 use futures::channel::oneshot;
 use futures::stream::BoxStream;
@@ -11,7 +12,7 @@ use rust_ipfs::{IpfsPath, PeerId, SubscriptionStream};
 use serde::{Deserialize, Serialize};
 use std::borrow::BorrowMut;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -19,12 +20,16 @@ use std::str::FromStr;
 use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 use uuid::Uuid;
+use warp::constellation::file::FileType;
 use warp::constellation::ConstellationProgressStream;
 use warp::crypto::DID;
+#[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+use warp::raygun::VoiceNote;
 use warp::raygun::{
-    AttachmentEventStream, ConversationImage, GroupPermissionOpt, Location, MessageEvent,
-    MessageOptions, MessageReference, MessageStatus, MessageType, Messages, MessagesType,
-    RayGunEventKind,
+    AttachmentEventStream, AttachmentFilter, ConversationAttachment, ConversationImage, Draft,
+    GroupPermissionOpt, Location, MessageBuilder, MessageEvent, MessageOptions, MessageReference,
+    MessageStatus, MessageType, Messages, MessagesType, PollResults, QuarantinedMessage,
+    RayGunEventKind, SyncStatus,
 };
 use warp::{
     crypto::generate,
@@ -36,9 +41,14 @@ use warp::{
 };
 use web_time::Instant;
 
-// use crate::config;
+use crate::config;
+use crate::filter::{
+    self, FilterKind, FilterSource, MessageFilterHandle, Quarantine, QuarantineEntry,
+};
 // use crate::shuttle::message::client::MessageCommand;
-use crate::store::conversation::message::{MessageDocument, MessageDocumentBuilder};
+use crate::store::conversation::message::{
+    MessageDocument, MessageDocumentBuilder, PollVoteDocument,
+};
 use crate::store::discovery::Discovery;
 use crate::store::document::files::FileDocument;
 use crate::store::document::image_dag::ImageDag;
@@ -62,13 +72,34 @@ use crate::{
         keystore::Keystore,
         payload::{PayloadBuilder, PayloadMessage},
         ConversationRequestKind, ConversationRequestResponse, ConversationResponseKind,
-        ConversationUpdateKind, DidExt, MessagingEvents, PeerIdExt, MAX_CONVERSATION_DESCRIPTION,
-        MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE,
+        ConversationUpdateKind, DidExt, MessagingEvents, PeerIdExt, MAX_ATTACHMENT,
+        MAX_CONVERSATION_DESCRIPTION, MAX_MESSAGE_SIZE, MIN_MESSAGE_SIZE,
     },
 };
 
 type AttachmentOneshot = (MessageDocument, oneshot::Sender<Result<(), Error>>);
 
+/// Duration an event is considered active by other participants unless rebroadcasted
+const MESSAGE_EVENT_TTL: Duration = Duration::from_secs(10);
+/// Interval in which active events are rebroadcasted
+const MESSAGE_EVENT_REBROADCAST_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound on the ttl a participant may request for an event
+const MAX_MESSAGE_EVENT_TTL: Duration = Duration::from_secs(60);
+/// Delay before retrieving missing messages from participants that are online
+const MESSAGE_SYNC_DELAY: Duration = Duration::from_secs(5);
+/// Duration without a response before retrieving missing messages is considered stalled
+const MESSAGE_SYNC_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of attachment thumbnails resolved at the same time when listing attachments
+const ATTACHMENT_RESOLVE_CONCURRENCY: usize = 8;
+/// Maximum number of message ids sent in response to a single request
+const MESSAGE_SYNC_PAGE_SIZE: usize = 256;
+/// Maximum number of messages requested at once
+const MESSAGE_SYNC_BATCH_SIZE: usize = 32;
+/// Window in which the number of sync requests accepted from a participant is limited
+const MESSAGE_SYNC_REQUEST_WINDOW: Duration = Duration::from_secs(10);
+/// Maximum number of sync requests accepted from a participant within the window
+const MAX_MESSAGE_SYNC_REQUESTS: usize = 20;
+
 use super::DownloadStream;
 
 #[derive(Debug)]
@@ -82,6 +113,9 @@ pub enum ConversationTaskCommand {
         favorite: bool,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    MarkRead {
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     GetMessage {
         message_id: Uuid,
         response: oneshot::Sender<Result<warp::raygun::Message, Error>>,
@@ -127,6 +161,10 @@ pub enum ConversationTaskCommand {
         lines: Vec<String>,
         response: oneshot::Sender<Result<Uuid, Error>>,
     },
+    SendMessageWith {
+        message: MessageBuilder,
+        response: oneshot::Sender<Result<Uuid, Error>>,
+    },
     EditMessage {
         message_id: Uuid,
         lines: Vec<String>,
@@ -152,12 +190,39 @@ pub enum ConversationTaskCommand {
         emoji: String,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    SendContactCard {
+        did: DID,
+        response: oneshot::Sender<Result<Uuid, Error>>,
+    },
+    SendPoll {
+        poll: warp::raygun::Poll,
+        response: oneshot::Sender<Result<Uuid, Error>>,
+    },
+    VotePoll {
+        message_id: Uuid,
+        options: Vec<usize>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    ClosePoll {
+        message_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    PollResults {
+        message_id: Uuid,
+        response: oneshot::Sender<Result<PollResults, Error>>,
+    },
     AttachMessage {
         message_id: Option<Uuid>,
         locations: Vec<Location>,
         lines: Vec<String>,
         response: oneshot::Sender<Result<(Uuid, AttachmentEventStream), Error>>,
     },
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    AttachVoiceNote {
+        data: Bytes,
+        voice_note: VoiceNote,
+        response: oneshot::Sender<Result<(Uuid, AttachmentEventStream), Error>>,
+    },
     DownloadAttachment {
         message_id: Uuid,
         file: String,
@@ -177,6 +242,35 @@ pub enum ConversationTaskCommand {
         event: MessageEvent,
         response: oneshot::Sender<Result<(), Error>>,
     },
+    TypingParticipants {
+        response: oneshot::Sender<Result<Vec<DID>, Error>>,
+    },
+    ListAttachments {
+        filter: AttachmentFilter,
+        response: oneshot::Sender<Result<Vec<ConversationAttachment>, Error>>,
+    },
+    ListQuarantined {
+        response: oneshot::Sender<Result<Vec<QuarantinedMessage>, Error>>,
+    },
+    SetDraft {
+        lines: Vec<String>,
+        attachments: Vec<PathBuf>,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    GetDraft {
+        response: oneshot::Sender<Result<Option<Draft>, Error>>,
+    },
+    SyncStatus {
+        response: oneshot::Sender<Result<SyncStatus, Error>>,
+    },
+    ForceResync {
+        peer: DID,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
+    ReleaseQuarantined {
+        message_id: Uuid,
+        response: oneshot::Sender<Result<(), Error>>,
+    },
     UpdateIcon {
         location: Location,
         response: oneshot::Sender<Result<(), Error>>,
@@ -228,6 +322,8 @@ pub enum ConversationTaskCommand {
 pub struct ConversationTask {
     conversation_id: Uuid,
     ipfs: Ipfs,
+    config: config::Config,
+    message_filter: Option<MessageFilterHandle>,
     root: RootDocumentMap,
     file: FileStore,
     identity: IdentityStore,
@@ -247,12 +343,67 @@ pub struct ConversationTask {
 
     command_rx: futures::channel::mpsc::Receiver<ConversationTaskCommand>,
 
+    /// Events sent by us that are rebroadcasted until cancelled
+    local_events: HashSet<MessageEvent>,
+    /// Events received from other participants along with when they expire
+    remote_events: HashMap<(DID, MessageEvent), Instant>,
+
+    /// Inbound messages held back by the message filter
+    quarantine: Quarantine<MessagingEvents>,
+
+    /// Progress of retrieving missing messages from other participants
+    sync: MessageSync,
+    /// Sync requests received from each participant within the current window
+    sync_requests: HashMap<DID, (Instant, usize)>,
+
     //TODO: replace queue
     queue: HashMap<DID, Vec<QueueItem>>,
 
     terminate: ConversationTermination,
 }
 
+#[derive(Default)]
+struct MessageSync {
+    /// Participants asked for their messages that have yet to respond
+    awaiting: IndexSet<DID>,
+    /// Messages requested along with the participant they were requested from
+    pending: IndexMap<Uuid, DID>,
+    /// Number of messages requested since the sync started
+    total: usize,
+    /// Last time a request was sent or a response was received
+    updated: Option<Instant>,
+    stalled: bool,
+}
+
+impl MessageSync {
+    fn is_active(&self) -> bool {
+        !self.awaiting.is_empty() || !self.pending.is_empty()
+    }
+
+    fn status(&self) -> SyncStatus {
+        if !self.is_active() {
+            return SyncStatus::InSync;
+        }
+
+        if self.stalled {
+            let peers = self
+                .awaiting
+                .iter()
+                .chain(self.pending.values())
+                .cloned()
+                .collect::<IndexSet<_>>();
+            return SyncStatus::Stalled {
+                peers: Vec::from_iter(peers),
+            };
+        }
+
+        SyncStatus::Syncing {
+            received: self.total - self.pending.len(),
+            total: self.total,
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 struct ConversationTermination {
     terminate: bool,
@@ -285,6 +436,8 @@ impl ConversationTask {
     pub async fn new(
         conversation_id: Uuid,
         ipfs: &Ipfs,
+        config: &config::Config,
+        message_filter: Option<MessageFilterHandle>,
         root: &RootDocumentMap,
         identity: &IdentityStore,
         file: &FileStore,
@@ -308,6 +461,8 @@ impl ConversationTask {
         let mut task = Self {
             conversation_id,
             ipfs: ipfs.clone(),
+            config: config.clone(),
+            message_filter,
             root: root.clone(),
             file: file.clone(),
             identity: identity.clone(),
@@ -325,6 +480,14 @@ impl ConversationTask {
             event_broadcast: btx,
             event_subscription,
             command_rx,
+            local_events: Default::default(),
+            remote_events: Default::default(),
+            quarantine: root
+                .get_quarantine(conversation_id)
+                .await
+                .unwrap_or_default(),
+            sync: MessageSync::default(),
+            sync_requests: Default::default(),
             queue: Default::default(),
             terminate: ConversationTermination::default(),
         };
@@ -376,6 +539,14 @@ impl ConversationTask {
             }
         }
 
+        if task.document.pinned.is_none() {
+            if let Err(e) = task.document.rebuild_pinned_index(ipfs).await {
+                tracing::warn!(%conversation_id, error = %e, "unable to rebuild pinned index");
+            } else {
+                task.set_document().await?;
+            }
+        }
+
         tracing::info!(%conversation_id, "conversation task created");
         Ok(task)
     }
@@ -393,6 +564,14 @@ impl ConversationTask {
 
         let mut check_mailbox = Delay::new(Duration::from_secs(5));
 
+        let mut event_expiry_timer = Delay::new(Duration::from_secs(1));
+
+        let mut event_rebroadcast_timer = Delay::new(MESSAGE_EVENT_REBROADCAST_INTERVAL);
+
+        let mut sync_timer = Delay::new(MESSAGE_SYNC_DELAY);
+
+        let mut initial_sync = true;
+
         loop {
             tokio::select! {
                 biased;
@@ -436,6 +615,26 @@ impl ConversationTask {
                     // _ = this.load_from_mailbox().await;
                     check_mailbox.reset(Duration::from_secs(60));
                 }
+                _ = &mut event_expiry_timer => {
+                    expire_message_events(this);
+                    event_expiry_timer.reset(Duration::from_secs(1));
+                }
+                _ = &mut event_rebroadcast_timer => {
+                    for event in this.local_events.clone() {
+                        if let Err(e) = this.broadcast_event(event).await {
+                            tracing::warn!(%conversation_id, error = %e, "unable to rebroadcast event");
+                        }
+                    }
+                    event_rebroadcast_timer.reset(MESSAGE_EVENT_REBROADCAST_INTERVAL);
+                }
+                _ = &mut sync_timer => {
+                    if initial_sync {
+                        initial_sync = false;
+                        this.sync_messages().await;
+                    }
+                    this.check_sync_stalled();
+                    sync_timer.reset(Duration::from_secs(1));
+                }
             }
         }
     }
@@ -639,6 +838,10 @@ impl ConversationTask {
                 let result = self.set_favorite_conversation(favorite).await;
                 let _ = response.send(result);
             }
+            ConversationTaskCommand::MarkRead { response } => {
+                let result = self.mark_read().await;
+                let _ = response.send(result);
+            }
             ConversationTaskCommand::GetMessage {
                 message_id,
                 response,
@@ -699,6 +902,10 @@ impl ConversationTask {
                 let result = self.send_message(lines).await;
                 let _ = response.send(result);
             }
+            ConversationTaskCommand::SendMessageWith { message, response } => {
+                let result = self.send_message_with(message).await;
+                let _ = response.send(result);
+            }
             ConversationTaskCommand::EditMessage {
                 message_id,
                 lines,
@@ -739,6 +946,36 @@ impl ConversationTask {
                 let result = self.react(message_id, state, emoji).await;
                 let _ = response.send(result);
             }
+            ConversationTaskCommand::SendContactCard { did, response } => {
+                let result = self.send_contact_card(did).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::SendPoll { poll, response } => {
+                let result = self.send_poll(poll).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::VotePoll {
+                message_id,
+                options,
+                response,
+            } => {
+                let result = self.vote_poll(message_id, options).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::ClosePoll {
+                message_id,
+                response,
+            } => {
+                let result = self.close_poll(message_id).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::PollResults {
+                message_id,
+                response,
+            } => {
+                let result = self.poll_results(message_id).await;
+                let _ = response.send(result);
+            }
             ConversationTaskCommand::AttachMessage {
                 message_id,
                 locations,
@@ -748,6 +985,15 @@ impl ConversationTask {
                 let result = self.attach(message_id, locations, lines);
                 let _ = response.send(result);
             }
+            #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+            ConversationTaskCommand::AttachVoiceNote {
+                data,
+                voice_note,
+                response,
+            } => {
+                let result = self.attach_voice_note(data, voice_note);
+                let _ = response.send(result);
+            }
             ConversationTaskCommand::DownloadAttachment {
                 message_id,
                 file,
@@ -773,6 +1019,42 @@ impl ConversationTask {
                 let result = self.cancel_event(event).await;
                 let _ = response.send(result);
             }
+            ConversationTaskCommand::TypingParticipants { response } => {
+                let _ = response.send(Ok(self.typing_participants()));
+            }
+            ConversationTaskCommand::SetDraft {
+                lines,
+                attachments,
+                response,
+            } => {
+                let result = self.set_draft(lines, attachments).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::GetDraft { response } => {
+                let result = self.root.get_draft(self.conversation_id).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::SyncStatus { response } => {
+                let _ = response.send(Ok(self.sync.status()));
+            }
+            ConversationTaskCommand::ForceResync { peer, response } => {
+                let result = self.request_messages(&peer).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::ListAttachments { filter, response } => {
+                let result = self.list_attachments(filter).await;
+                let _ = response.send(result);
+            }
+            ConversationTaskCommand::ListQuarantined { response } => {
+                let _ = response.send(Ok(self.list_quarantined()));
+            }
+            ConversationTaskCommand::ReleaseQuarantined {
+                message_id,
+                response,
+            } => {
+                let result = self.release_quarantined(message_id).await;
+                let _ = response.send(result);
+            }
             ConversationTaskCommand::UpdateIcon { location, response } => {
                 let result = self
                     .update_conversation_image(location, ConversationImageType::Icon)
@@ -845,6 +1127,7 @@ impl ConversationTask {
     pub async fn delete(&mut self) -> Result<(), Error> {
         // TODO: Maybe announce to network of the local node removal here
         self.document.messages.take();
+        self.document.attachments.take();
         self.document.deleted = true;
         self.set_document().await?;
         if let Ok(mut ks_map) = self.root.get_keystore_map().await {
@@ -854,6 +1137,11 @@ impl ConversationTask {
                 }
             }
         }
+        if let Err(e) = self.root.set_draft(self.conversation_id, None).await {
+            tracing::warn!(conversation_id = %self.conversation_id, error = %e, "failed to remove draft");
+        }
+        self.quarantine.clear();
+        self.save_quarantine().await;
         self.terminate.cancel();
         Ok(())
     }
@@ -890,26 +1178,6 @@ impl ConversationTask {
         Ok(())
     }
 
-    pub async fn replace_document(
-        &mut self,
-        mut document: ConversationDocument,
-    ) -> Result<(), Error> {
-        let keypair = self.root.keypair();
-        if let Some(creator) = document.creator.as_ref() {
-            let did = keypair.to_did()?;
-            if creator.eq(&did) && matches!(document.conversation_type(), ConversationType::Group) {
-                document.sign(keypair)?;
-            }
-        }
-
-        document.verify()?;
-
-        self.root.set_conversation_document(&document).await?;
-        self.identity.export_root_document().await?;
-        self.document = document;
-        Ok(())
-    }
-
     async fn send_single_conversation_event(
         &mut self,
         did_key: &DID,
@@ -1035,6 +1303,11 @@ impl ConversationTask {
         self.set_document().await
     }
 
+    async fn mark_read(&mut self) -> Result<(), Error> {
+        self.document.last_read = Some(Utc::now());
+        self.set_document().await
+    }
+
     async fn process_msg_event(&mut self, msg: Message) -> Result<(), Error> {
         let data = PayloadMessage::<MessagingEvents>::from_bytes(&msg.data)?;
         let sender = data.sender().to_did()?;
@@ -1105,7 +1378,7 @@ impl ConversationTask {
             }
         };
 
-        message_event(self, &sender, event).await?;
+        message_event(self, &sender, event, true).await?;
 
         Ok(())
     }
@@ -1194,29 +1467,92 @@ impl ConversationTask {
         }
     }
 
+    /// Handles every recipient added or removed by merging the recipients of a peer, as the merge
+    /// can include changes other than the one named by the update
+    async fn recipients_merged(&mut self, previous: &[DID]) {
+        let conversation_id = self.conversation_id;
+        let recipients = self.document.recipients.clone();
+
+        for did in recipients.iter().filter(|did| !previous.contains(did)) {
+            if !self.discovery.contains(did).await {
+                let _ = self.discovery.insert(did).await;
+            }
+
+            if let Err(e) = self.request_key(did).await {
+                tracing::error!(%conversation_id, error = %e, "error requesting key");
+            }
+
+            if let Err(e) = self.event_broadcast.send(MessageEventKind::RecipientAdded {
+                conversation_id,
+                recipient: did.clone(),
+            }) {
+                tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
+            }
+        }
+
+        let mut changed = false;
+        for did in previous.iter().filter(|did| !recipients.contains(did)) {
+            //Maybe remove participant from discovery?
+
+            changed |= self.document.permissions.shift_remove(did).is_some();
+
+            if self.document.excluded.remove(did).is_some() {
+                changed = true;
+                continue;
+            }
+
+            if let Err(e) = self
+                .event_broadcast
+                .send(MessageEventKind::RecipientRemoved {
+                    conversation_id,
+                    recipient: did.clone(),
+                })
+            {
+                tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
+            }
+        }
+
+        if changed {
+            if let Err(e) = self.set_document().await {
+                tracing::error!(%conversation_id, error = %e, "unable to update document");
+            }
+        }
+    }
+
     async fn request_key(&mut self, did: &DID) -> Result<(), Error> {
         let request = ConversationRequestResponse::Request {
             conversation_id: self.conversation_id,
             kind: ConversationRequestKind::Key,
         };
 
-        let conversation = &self.document;
-
-        if !conversation.recipients().contains(did) {
+        if !self.document.recipients().contains(did) {
             //TODO: user is not a recipient of the conversation
             return Err(Error::PublicKeyInvalid);
         }
 
+        self.send_exchange(did, request).await?;
+
+        // TODO: Store request locally and hold any messages and events until key is received from peer
+
+        Ok(())
+    }
+
+    /// Send a request or response directly to a participant, queuing it if they are unreachable
+    async fn send_exchange(
+        &mut self,
+        did: &DID,
+        event: ConversationRequestResponse,
+    ) -> Result<(), Error> {
         let keypair = self.root.keypair();
 
-        let payload = PayloadBuilder::new(keypair, request)
+        let payload = PayloadBuilder::new(keypair, event)
             .add_recipient(did)?
             .from_ipfs(&self.ipfs)
             .await?;
 
         let bytes = payload.to_bytes()?;
 
-        let topic = conversation.exchange_topic(did);
+        let topic = self.document.exchange_topic(did);
 
         let peers = self.ipfs.pubsub_peers(Some(topic.clone())).await?;
         let peer_id = did.to_peer_id()?;
@@ -1236,11 +1572,115 @@ impl ConversationTask {
             .await;
         }
 
-        // TODO: Store request locally and hold any messages and events until key is received from peer
+        Ok(())
+    }
+
+    /// Ask a participant for the messages they have so that any missing can be retrieved
+    async fn request_messages(&mut self, did: &DID) -> Result<(), Error> {
+        if did == &self.identity.did_key() || !self.document.recipients().contains(did) {
+            return Err(Error::IdentityDoesntExist);
+        }
 
+        self.request_message_page(did, 0).await?;
+
+        self.sync.awaiting.insert(did.clone());
+        self.update_sync();
         Ok(())
     }
 
+    async fn request_message_page(&mut self, did: &DID, offset: usize) -> Result<(), Error> {
+        let request = ConversationRequestResponse::Request {
+            conversation_id: self.conversation_id,
+            kind: ConversationRequestKind::RetrieveMessages {
+                start: None,
+                end: None,
+                offset,
+                limit: Some(MESSAGE_SYNC_PAGE_SIZE),
+            },
+        };
+
+        self.send_exchange(did, request).await
+    }
+
+    /// Returns `true` if a sync request from the participant is within the rate limit
+    fn allow_sync_request(&mut self, did: &DID) -> bool {
+        let (window, count) = self
+            .sync_requests
+            .entry(did.clone())
+            .or_insert_with(|| (Instant::now(), 0));
+
+        if window.elapsed() >= MESSAGE_SYNC_REQUEST_WINDOW {
+            *window = Instant::now();
+            *count = 0;
+        }
+
+        *count += 1;
+        *count <= MAX_MESSAGE_SYNC_REQUESTS
+    }
+
+    /// Retrieve missing messages from participants that are online
+    async fn sync_messages(&mut self) {
+        let own_did = self.identity.did_key();
+
+        let Ok(peers) = self.ipfs.pubsub_peers(Some(self.document.topic())).await else {
+            return;
+        };
+
+        let recipients = self
+            .document
+            .recipients()
+            .into_iter()
+            .filter(|did| did != &own_did)
+            .filter(|did| {
+                did.to_peer_id()
+                    .map(|peer_id| peers.contains(&peer_id))
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        for did in recipients {
+            if let Err(e) = self.request_messages(&did).await {
+                tracing::warn!(id = %self.conversation_id, %did, error = %e, "Unable to request messages");
+            }
+        }
+    }
+
+    /// Record that the sync made progress, resetting it once nothing is outstanding
+    fn update_sync(&mut self) {
+        self.sync.updated.replace(Instant::now());
+        self.sync.stalled = false;
+
+        if !self.sync.is_active() {
+            self.sync = MessageSync::default();
+        }
+
+        let _ = self.event_broadcast.send(MessageEventKind::SyncProgress {
+            conversation_id: self.conversation_id,
+            status: self.sync.status(),
+        });
+    }
+
+    fn check_sync_stalled(&mut self) {
+        if !self.sync.is_active() || self.sync.stalled {
+            return;
+        }
+
+        let Some(updated) = self.sync.updated else {
+            return;
+        };
+
+        if updated.elapsed() < MESSAGE_SYNC_STALL_TIMEOUT {
+            return;
+        }
+
+        self.sync.stalled = true;
+
+        let _ = self.event_broadcast.send(MessageEventKind::SyncProgress {
+            conversation_id: self.conversation_id,
+            status: self.sync.status(),
+        });
+    }
+
     //TODO: Send a request to recipient(s) of the chat to ack if message been delivered if message is marked "sent" unless we receive an event acknowledging the message itself
     //Note:
     //  - For group chat, this can be ignored unless we decide to have a full acknowledgement from all recipients in which case, we can mark it as "sent"
@@ -1290,6 +1730,16 @@ impl ConversationTask {
     }
 
     pub async fn send_message(&mut self, messages: Vec<String>) -> Result<Uuid, Error> {
+        self.send_message_with(MessageBuilder::new(messages)).await
+    }
+
+    pub async fn edit_message(
+        &mut self,
+        message_id: Uuid,
+        messages: Vec<String>,
+    ) -> Result<(), Error> {
+        let tx = self.event_broadcast.clone();
+
         if messages.is_empty() {
             return Err(Error::EmptyMessage);
         }
@@ -1316,39 +1766,45 @@ impl ConversationTask {
         }
 
         let keypair = self.root.keypair();
-        let own_did = self.identity.did_key();
 
         let keystore = pubkey_or_keystore(&*self)?;
 
-        let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
-            .set_conversation_id(self.conversation_id)
-            .set_sender(own_did.clone())
-            .set_message(messages.clone())?
-            .build()?;
+        let mut message_document = self
+            .document
+            .get_message_document(&self.ipfs, message_id)
+            .await?;
 
-        let message_id = message.id;
+        if message_document.sender() != self.identity.did_key() {
+            return Err(Error::InvalidMessage);
+        }
+
+        message_document.set_message(keypair, keystore.as_ref(), &messages)?;
+
+        let nonce = message_document.nonce_from_message()?;
+        let signature = message_document.signature.expect("message to be signed");
 
         let _message_cid = self
             .document
-            .insert_message_document(&self.ipfs, &message)
+            .update_message_document(&self.ipfs, &message_document)
             .await?;
 
         // let recipients = self.document.recipients();
 
         self.set_document().await?;
 
-        let event = MessageEventKind::MessageSent {
+        let _ = tx.send(MessageEventKind::MessageEdited {
             conversation_id: self.conversation_id,
             message_id,
-        };
+        });
 
-        if let Err(e) = self.event_broadcast.clone().send(event) {
-            tracing::error!(conversation_id=%self.conversation_id, error = %e, "Error broadcasting event");
-        }
-
-        let message_id = message.id;
-
-        let event = MessagingEvents::New { message };
+        let event = MessagingEvents::Edit {
+            conversation_id: self.conversation_id,
+            message_id,
+            modified: message_document.modified.expect("message to be modified"),
+            lines: messages,
+            nonce: nonce.to_vec(),
+            signature: signature.into(),
+        };
 
         // if !recipients.is_empty() {
         //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
@@ -1368,16 +1824,14 @@ impl ConversationTask {
         //     }
         // }
 
-        self.publish(Some(message_id), event, true)
-            .await
-            .map(|_| message_id)
+        self.publish(None, event, true).await
     }
 
-    pub async fn edit_message(
+    pub async fn reply_message(
         &mut self,
         message_id: Uuid,
         messages: Vec<String>,
-    ) -> Result<(), Error> {
+    ) -> Result<Uuid, Error> {
         let tx = self.event_broadcast.clone();
 
         if messages.is_empty() {
@@ -1405,47 +1859,44 @@ impl ConversationTask {
             });
         }
 
-        let keypair = self.root.keypair();
+        let clock = self.document.tick_clock();
 
-        let keystore = pubkey_or_keystore(&*self)?;
+        let keypair = self.root.keypair();
 
-        let mut message_document = self
-            .document
-            .get_message_document(&self.ipfs, message_id)
-            .await?;
+        let own_did = self.identity.did_key();
 
-        if message_document.sender() != self.identity.did_key() {
-            return Err(Error::InvalidMessage);
-        }
+        let keystore = pubkey_or_keystore(&*self)?;
 
-        message_document.set_message(keypair, keystore.as_ref(), &messages)?;
+        let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
+            .set_conversation_id(self.conversation_id)
+            .set_sender(own_did.clone())
+            .set_clock(clock)
+            .set_replied(message_id)
+            .set_message(messages)?
+            .build()?;
 
-        let nonce = message_document.nonce_from_message()?;
-        let signature = message_document.signature.expect("message to be signed");
+        let message_id = message.id;
 
         let _message_cid = self
             .document
-            .update_message_document(&self.ipfs, &message_document)
+            .insert_message_document(&self.ipfs, &message)
             .await?;
 
         // let recipients = self.document.recipients();
 
         self.set_document().await?;
 
-        let _ = tx.send(MessageEventKind::MessageEdited {
-            conversation_id: self.conversation_id,
-            message_id,
-        });
-
-        let event = MessagingEvents::Edit {
+        let event = MessageEventKind::MessageSent {
             conversation_id: self.conversation_id,
             message_id,
-            modified: message_document.modified.expect("message to be modified"),
-            lines: messages,
-            nonce: nonce.to_vec(),
-            signature: signature.into(),
         };
 
+        if let Err(e) = tx.send(event) {
+            tracing::error!(id=%self.conversation_id, error = %e, "Error broadcasting event");
+        }
+
+        let event = MessagingEvents::New { message };
+
         // if !recipients.is_empty() {
         //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
         //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
@@ -1464,15 +1915,13 @@ impl ConversationTask {
         //     }
         // }
 
-        self.publish(None, event, true).await
+        self.publish(Some(message_id), event, true)
+            .await
+            .map(|_| message_id)
     }
 
-    pub async fn reply_message(
-        &mut self,
-        message_id: Uuid,
-        messages: Vec<String>,
-    ) -> Result<Uuid, Error> {
-        let tx = self.event_broadcast.clone();
+    pub async fn send_message_with(&mut self, builder: MessageBuilder) -> Result<Uuid, Error> {
+        let messages = builder.lines().to_vec();
 
         if messages.is_empty() {
             return Err(Error::EmptyMessage);
@@ -1499,28 +1948,63 @@ impl ConversationTask {
             });
         }
 
+        let recipients = self.document.recipients();
+
+        if builder
+            .mentions()
+            .iter()
+            .any(|did| !recipients.contains(did))
+        {
+            return Err(Error::IdentityDoesntExist);
+        }
+
+        if let Some(replied) = builder.replied() {
+            self.document
+                .contains(&self.ipfs, replied)
+                .await?
+                .then_some(())
+                .ok_or(Error::MessageNotFound)?;
+        }
+
+        self.send_message_document(|message| {
+            message
+                .set_replied(builder.replied())
+                .set_mentions(builder.mentions().iter().cloned())
+                .set_message(messages)?
+                .set_metadata(builder.metadata().clone())
+        })
+        .await
+    }
+
+    /// Builds a new message from our identity, stores it and sends it to the other participants
+    async fn send_message_document(
+        &mut self,
+        build: impl for<'a> FnOnce(
+            MessageDocumentBuilder<'a>,
+        ) -> Result<MessageDocumentBuilder<'a>, Error>,
+    ) -> Result<Uuid, Error> {
+        let clock = self.document.tick_clock();
+
         let keypair = self.root.keypair();
 
         let own_did = self.identity.did_key();
 
         let keystore = pubkey_or_keystore(&*self)?;
 
-        let message = MessageDocumentBuilder::new(keypair, keystore.as_ref())
-            .set_conversation_id(self.conversation_id)
-            .set_sender(own_did.clone())
-            .set_replied(message_id)
-            .set_message(messages)?
-            .build()?;
+        let message = build(
+            MessageDocumentBuilder::new(keypair, keystore.as_ref())
+                .set_conversation_id(self.conversation_id)
+                .set_sender(own_did.clone())
+                .set_clock(clock),
+        )?
+        .build()?;
 
         let message_id = message.id;
 
-        let _message_cid = self
-            .document
+        self.document
             .insert_message_document(&self.ipfs, &message)
             .await?;
 
-        // let recipients = self.document.recipients();
-
         self.set_document().await?;
 
         let event = MessageEventKind::MessageSent {
@@ -1528,30 +2012,12 @@ impl ConversationTask {
             message_id,
         };
 
-        if let Err(e) = tx.send(event) {
-            tracing::error!(id=%self.conversation_id, error = %e, "Error broadcasting event");
+        if let Err(e) = self.event_broadcast.clone().send(event) {
+            tracing::error!(conversation_id=%self.conversation_id, error = %e, "Error broadcasting event");
         }
 
         let event = MessagingEvents::New { message };
 
-        // if !recipients.is_empty() {
-        //     if let config::Discovery::Shuttle { addresses } = self.discovery.discovery_config() {
-        //         for peer_id in addresses.iter().filter_map(|addr| addr.peer_id()) {
-        //             let _ = self
-        //                 .message_command
-        //                 .clone()
-        //                 .send(MessageCommand::InsertMessage {
-        //                     peer_id,
-        //                     conversation_id: self.conversation_id,
-        //                     recipients: recipients.clone(),
-        //                     message_id,
-        //                     message_cid,
-        //                 })
-        //                 .await;
-        //         }
-        //     }
-        // }
-
         self.publish(Some(message_id), event, true)
             .await
             .map(|_| message_id)
@@ -1608,7 +2074,14 @@ impl ConversationTask {
                 if message_document.pinned() {
                     return Ok(());
                 }
+
+                if self.document.pinned_count() >= self.config.store_setting().max_pinned_messages {
+                    return Err(Error::PinnedMessageLimitReached);
+                }
+
                 message_document.set_pin(true);
+                self.document
+                    .pin_message(message_id, own_did.clone(), Utc::now());
                 MessageEventKind::MessagePinned {
                     conversation_id: self.conversation_id,
                     message_id,
@@ -1619,6 +2092,7 @@ impl ConversationTask {
                     return Ok(());
                 }
                 message_document.set_pin(false);
+                self.document.unpin_message(message_id);
                 MessageEventKind::MessageUnpinned {
                     conversation_id: self.conversation_id,
                     message_id,
@@ -1750,7 +2224,161 @@ impl ConversationTask {
         self.publish(None, event, true).await
     }
 
-    pub async fn send_event(&self, event: MessageEvent) -> Result<(), Error> {
+    pub async fn send_contact_card(&mut self, did: DID) -> Result<Uuid, Error> {
+        let document = self.identity.identity_document(&did).await?;
+
+        self.send_message_document(|message| message.set_contact_card(&document))
+            .await
+    }
+
+    pub async fn send_poll(&mut self, poll: warp::raygun::Poll) -> Result<Uuid, Error> {
+        self.send_message_document(|message| {
+            message
+                .set_message(vec![poll.question().to_string()])?
+                .set_poll(poll)
+        })
+        .await
+    }
+
+    pub async fn vote_poll(&mut self, message_id: Uuid, options: Vec<usize>) -> Result<(), Error> {
+        let keypair = self.root.keypair();
+
+        let keystore = pubkey_or_keystore(&*self)?;
+
+        let mut message_document = self
+            .document
+            .get_message_document(&self.ipfs, message_id)
+            .await?;
+
+        let poll = message_document.poll(keypair, keystore.as_ref())?;
+
+        let vote = PollVoteDocument::new(
+            keypair,
+            self.conversation_id,
+            message_id,
+            options.into_iter().collect(),
+        )?;
+
+        message_document.apply_poll_vote(&poll, vote.clone(), Utc::now())?;
+
+        self.document
+            .update_message_document(&self.ipfs, &message_document)
+            .await?;
+
+        self.set_document().await?;
+
+        let _ = self.event_broadcast.send(MessageEventKind::PollUpdated {
+            conversation_id: self.conversation_id,
+            message_id,
+        });
+
+        let event = MessagingEvents::PollVote {
+            conversation_id: self.conversation_id,
+            message_id,
+            vote,
+        };
+
+        self.publish(None, event, true).await
+    }
+
+    pub async fn close_poll(&mut self, message_id: Uuid) -> Result<(), Error> {
+        let own_did = self.identity.did_key();
+
+        let mut message_document = self
+            .document
+            .get_message_document(&self.ipfs, message_id)
+            .await?;
+
+        if message_document.sender() != own_did {
+            return Err(Error::Unauthorized);
+        }
+
+        let closed = Utc::now();
+
+        message_document.close_poll(closed)?;
+
+        self.document
+            .update_message_document(&self.ipfs, &message_document)
+            .await?;
+
+        self.set_document().await?;
+
+        let _ = self.event_broadcast.send(MessageEventKind::PollUpdated {
+            conversation_id: self.conversation_id,
+            message_id,
+        });
+
+        let event = MessagingEvents::PollClose {
+            conversation_id: self.conversation_id,
+            message_id,
+            closed,
+        };
+
+        self.publish(None, event, true).await
+    }
+
+    pub async fn poll_results(&self, message_id: Uuid) -> Result<PollResults, Error> {
+        let keypair = self.root.keypair();
+
+        let keystore = pubkey_or_keystore(self)?;
+
+        let message_document = self
+            .document
+            .get_message_document(&self.ipfs, message_id)
+            .await?;
+
+        message_document.poll_results(keypair, keystore.as_ref())
+    }
+
+    pub async fn set_draft(
+        &mut self,
+        lines: Vec<String>,
+        attachments: Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        let lines_value_length: usize = lines
+            .iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim())
+            .map(|s| s.chars().count())
+            .sum();
+
+        if lines_value_length > MAX_MESSAGE_SIZE {
+            return Err(Error::InvalidLength {
+                context: "draft".into(),
+                current: lines_value_length,
+                minimum: None,
+                maximum: Some(MAX_MESSAGE_SIZE),
+            });
+        }
+
+        if attachments.len() > MAX_ATTACHMENT {
+            return Err(Error::InvalidLength {
+                context: "attachments".into(),
+                current: attachments.len(),
+                minimum: None,
+                maximum: Some(MAX_ATTACHMENT),
+            });
+        }
+
+        let draft = Draft::new(lines, attachments, Utc::now());
+        let draft = (!draft.is_empty()).then_some(draft);
+
+        self.root.set_draft(self.conversation_id, draft).await?;
+        self.identity.export_root_document().await?;
+
+        let _ = self.event_broadcast.send(MessageEventKind::DraftUpdated {
+            conversation_id: self.conversation_id,
+        });
+
+        Ok(())
+    }
+
+    pub async fn send_event(&mut self, event: MessageEvent) -> Result<(), Error> {
+        self.local_events.insert(event);
+        self.broadcast_event(event).await
+    }
+
+    async fn broadcast_event(&self, event: MessageEvent) -> Result<(), Error> {
         let conversation_id = self.conversation_id;
         let member = self.identity.did_key();
 
@@ -1759,11 +2387,13 @@ impl ConversationTask {
             member,
             event,
             cancelled: false,
+            ttl: Some(MESSAGE_EVENT_TTL.as_secs()),
         };
         self.send_message_event(event).await
     }
 
-    pub async fn cancel_event(&self, event: MessageEvent) -> Result<(), Error> {
+    pub async fn cancel_event(&mut self, event: MessageEvent) -> Result<(), Error> {
+        self.local_events.remove(&event);
         let member = self.identity.did_key();
         let conversation_id = self.conversation_id;
         let event = MessagingEvents::Event {
@@ -1771,10 +2401,153 @@ impl ConversationTask {
             member,
             event,
             cancelled: true,
+            ttl: None,
         };
         self.send_message_event(event).await
     }
 
+    fn typing_participants(&self) -> Vec<DID> {
+        let now = Instant::now();
+        self.remote_events
+            .iter()
+            .filter(|((_, event), expire)| *event == MessageEvent::Typing && **expire > now)
+            .map(|((did, _), _)| did.clone())
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    async fn list_attachments(
+        &mut self,
+        filter: AttachmentFilter,
+    ) -> Result<Vec<ConversationAttachment>, Error> {
+        if self.document.attachments.is_none() {
+            self.document.rebuild_attachment_index(&self.ipfs).await?;
+            self.set_document().await?;
+        }
+
+        let references = self
+            .document
+            .attachment_references(&self.ipfs)
+            .await?
+            .filter(|reference| {
+                futures::future::ready(
+                    filter
+                        .sender()
+                        .is_none_or(|sender| &reference.sender == sender)
+                        && filter
+                            .date_range()
+                            .is_none_or(|range| range.contains(&reference.date))
+                        && filter.file_type().is_none_or(|file_type| {
+                            file_type_matches(file_type, &reference.file.file_type)
+                        }),
+                )
+            })
+            .skip(filter.skip())
+            .take(filter.limit().unwrap_or(usize::MAX))
+            .collect::<Vec<_>>()
+            .await;
+
+        let conversation_id = self.conversation_id;
+        let ipfs = self.ipfs.clone();
+
+        // Thumbnails are resolved concurrently. An attachment whose thumbnail cannot be resolved is
+        // still returned, just without the thumbnail
+        let attachments = futures::stream::iter(references)
+            .map(|reference| {
+                let ipfs = ipfs.clone();
+                async move {
+                    let file = match reference.file.resolve_to_file(&ipfs, false).await {
+                        Ok(file) => file,
+                        Err(e) => {
+                            tracing::warn!(%conversation_id, message_id = %reference.message_id, error = %e, "Unable to resolve attachment thumbnail");
+                            reference.file.to_file()
+                        }
+                    };
+
+                    ConversationAttachment::new(
+                        conversation_id,
+                        reference.message_id,
+                        reference.sender,
+                        reference.date,
+                        file,
+                    )
+                }
+            })
+            .buffered(ATTACHMENT_RESOLVE_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+
+        Ok(attachments)
+    }
+
+    fn list_quarantined(&self) -> Vec<QuarantinedMessage> {
+        self.quarantine
+            .values()
+            .map(|entry| entry.message.clone())
+            .collect()
+    }
+
+    async fn release_quarantined(&mut self, message_id: Uuid) -> Result<(), Error> {
+        let QuarantineEntry { sender, event, .. } = self
+            .quarantine
+            .shift_remove(&message_id)
+            .ok_or(Error::MessageNotFound)?;
+
+        self.save_quarantine().await;
+
+        message_event(self, &sender, event, false).await
+    }
+
+    async fn save_quarantine(&self) {
+        if let Err(e) = self
+            .root
+            .set_quarantine(self.conversation_id, &self.quarantine)
+            .await
+        {
+            tracing::warn!(conversation_id = %self.conversation_id, error = %e, "failed to store quarantine");
+        }
+    }
+
+    /// Pass an inbound message through the message filter, if one is set.
+    /// Returns `true` if the message should be stored
+    async fn filter_message(
+        &mut self,
+        sender: &DID,
+        kind: FilterKind,
+        message: &warp::raygun::Message,
+        event: impl FnOnce() -> MessagingEvents,
+    ) -> bool {
+        let Some(message_filter) = self.message_filter.clone() else {
+            return true;
+        };
+
+        if sender == &self.identity.did_key() {
+            return true;
+        }
+
+        let source = FilterSource::Conversation {
+            conversation_id: self.conversation_id,
+        };
+
+        let store = filter::filter_message(
+            &message_filter,
+            &mut self.quarantine,
+            source,
+            sender,
+            kind,
+            message,
+            event,
+        )
+        .await;
+
+        if !store && self.quarantine.contains_key(&message.id()) {
+            self.save_quarantine().await;
+        }
+
+        store
+    }
+
     pub async fn send_message_event(&self, event: MessagingEvents) -> Result<(), Error> {
         let key = self.conversation_key(None)?;
 
@@ -1840,7 +2613,7 @@ impl ConversationTask {
             return Err(Error::IdentityExist);
         }
 
-        self.document.recipients.push(did_key.clone());
+        self.document.add_recipient(did_key);
 
         self.set_document().await?;
 
@@ -1905,7 +2678,7 @@ impl ConversationTask {
             return Err(Error::IdentityDoesntExist);
         }
 
-        self.document.recipients.retain(|did| did.ne(did_key));
+        self.document.remove_recipient(did_key);
         self.set_document().await?;
 
         let event = MessagingEvents::UpdateConversation {
@@ -1961,7 +2734,7 @@ impl ConversationTask {
         debug_assert!(!self.document.recipients.contains(did_key));
         debug_assert!(!self.document.restrict.contains(did_key));
 
-        self.document.restrict.push(did_key.clone());
+        self.document.add_restricted(did_key);
 
         self.set_document().await?;
 
@@ -2000,9 +2773,7 @@ impl ConversationTask {
 
         debug_assert!(self.document.restrict.contains(did_key));
 
-        self.document
-            .restrict
-            .retain(|restricted| restricted != did_key);
+        self.document.remove_restricted(did_key);
 
         self.set_document().await?;
 
@@ -2049,7 +2820,8 @@ impl ConversationTask {
             return Err(Error::Unauthorized);
         }
 
-        self.document.name = (!name.is_empty()).then_some(name.to_string());
+        self.document
+            .set_name((!name.is_empty()).then_some(name.to_string()), own_did);
 
         self.set_document().await?;
 
@@ -2342,7 +3114,8 @@ impl ConversationTask {
             }
         }
 
-        self.document.description = desc.map(ToString::to_string);
+        self.document
+            .set_description(desc.map(ToString::to_string), &self.identity.did_key());
 
         self.set_document().await?;
 
@@ -2371,6 +3144,8 @@ impl ConversationTask {
     ) -> Result<(Uuid, AttachmentEventStream), Error> {
         let conversation_id = self.conversation_id;
 
+        let clock = self.document.tick_clock();
+
         let keystore = pubkey_or_keystore(&*self)?;
 
         let stream = AttachmentStream::new(
@@ -2381,6 +3156,7 @@ impl ConversationTask {
             keystore,
             self.attachment_tx.clone(),
         )
+        .set_clock(clock)
         .set_reply(reply_id)
         .set_locations(locations)?
         .set_lines(messages)?;
@@ -2390,6 +3166,41 @@ impl ConversationTask {
         Ok((message_id, stream.boxed()))
     }
 
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    pub fn attach_voice_note(
+        &mut self,
+        data: Bytes,
+        voice_note: VoiceNote,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+        let conversation_id = self.conversation_id;
+
+        let clock = self.document.tick_clock();
+
+        let keystore = pubkey_or_keystore(&*self)?;
+
+        let location = Location::Stream {
+            name: format!("voice-note-{}.ogg", Uuid::new_v4()),
+            size: Some(data.len()),
+            stream: futures::stream::once(async move { Ok(data) }).boxed(),
+        };
+
+        let stream = AttachmentStream::new(
+            self.root.keypair(),
+            &self.identity.did_key(),
+            &self.file,
+            conversation_id,
+            keystore,
+            self.attachment_tx.clone(),
+        )
+        .set_clock(clock)
+        .set_locations(vec![location])?
+        .set_voice_note(voice_note);
+
+        let message_id = stream.message_id();
+
+        Ok((message_id, stream.boxed()))
+    }
+
     async fn store_direct_for_attachment(&mut self, message: MessageDocument) -> Result<(), Error> {
         let conversation_id = self.conversation_id;
         let message_id = message.id;
@@ -2690,6 +3501,7 @@ async fn message_event(
     this: &mut ConversationTask,
     sender: &DID,
     events: MessagingEvents,
+    filter: bool,
 ) -> Result<(), Error> {
     let conversation_id = this.conversation_id;
 
@@ -2699,9 +3511,13 @@ async fn message_event(
     let keystore = pubkey_or_keystore(&*this)?;
 
     match events {
-        MessagingEvents::New { message } => {
+        MessagingEvents::New { mut message } => {
             message.verify()?;
 
+            // The close date is not covered by a signature, so a poll is only closed by a
+            // `PollClose` from its sender
+            message.poll_closed = None;
+
             if this.document.id != message.conversation_id {
                 return Err(Error::InvalidConversation);
             }
@@ -2724,6 +3540,11 @@ async fn message_event(
                 .resolve(&this.ipfs, keypair, false, keystore.as_ref())
                 .await?;
 
+            let contact_card = match message.message_type {
+                MessageType::ContactCard => Some(message.contact_card(keypair, keystore.as_ref())?),
+                _ => None,
+            };
+
             let lines_value_length: usize = resolved_message
                 .lines()
                 .iter()
@@ -2745,6 +3566,18 @@ async fn message_event(
                 });
             }
 
+            if filter
+                && !this
+                    .filter_message(sender, FilterKind::New, &resolved_message, || {
+                        MessagingEvents::New {
+                            message: message.clone(),
+                        }
+                    })
+                    .await
+            {
+                return Ok(());
+            }
+
             let conversation_id = message.conversation_id;
 
             this.document
@@ -2753,6 +3586,12 @@ async fn message_event(
 
             this.set_document().await?;
 
+            if let Some(document) = contact_card {
+                if let Err(e) = this.identity.import_identity_document(&document).await {
+                    tracing::warn!(%conversation_id, error = %e, "Unable to import contact card");
+                }
+            }
+
             if let Err(e) = this
                 .event_broadcast
                 .send(MessageEventKind::MessageReceived {
@@ -2799,6 +3638,32 @@ async fn message_event(
                 });
             }
 
+            if filter && this.message_filter.is_some() {
+                let mut resolved_message = message_document
+                    .resolve(&this.ipfs, keypair, false, keystore.as_ref())
+                    .await?;
+                resolved_message.set_lines(lines.clone());
+                resolved_message.set_modified(modified);
+
+                let event = || MessagingEvents::Edit {
+                    conversation_id,
+                    message_id,
+                    modified,
+                    lines: lines.clone(),
+                    nonce: nonce.clone(),
+                    signature: signature.clone(),
+                };
+
+                if !this
+                    .filter_message(sender, FilterKind::Edit, &resolved_message, event)
+                    .await
+                {
+                    return Ok(());
+                }
+            }
+
+            let keypair = this.root.keypair();
+
             message_document.set_message_with_nonce(
                 keypair,
                 keystore.as_ref(),
@@ -2866,7 +3731,14 @@ async fn message_event(
                     if message_document.pinned() {
                         return Ok(());
                     }
+                    if this.document.pinned_count()
+                        >= this.config.store_setting().max_pinned_messages
+                    {
+                        return Err(Error::PinnedMessageLimitReached);
+                    }
                     message_document.set_pin(true);
+                    this.document
+                        .pin_message(message_id, sender.clone(), Utc::now());
                     MessageEventKind::MessagePinned {
                         conversation_id,
                         message_id,
@@ -2877,6 +3749,7 @@ async fn message_event(
                         return Ok(());
                     }
                     message_document.set_pin(false);
+                    this.document.unpin_message(message_id);
                     MessageEventKind::MessageUnpinned {
                         conversation_id,
                         message_id,
@@ -2951,18 +3824,71 @@ async fn message_event(
                 }
             }
         }
-        MessagingEvents::UpdateConversation {
-            mut conversation,
-            kind,
+        MessagingEvents::PollVote {
+            conversation_id,
+            message_id,
+            vote,
+        } => {
+            if vote.voter != *sender {
+                return Err(Error::InvalidSignature);
+            }
+
+            let mut message_document = this
+                .document
+                .get_message_document(&this.ipfs, message_id)
+                .await?;
+
+            let poll = message_document.poll(keypair, keystore.as_ref())?;
+
+            message_document.apply_poll_vote(&poll, vote, Utc::now())?;
+
+            this.document
+                .update_message_document(&this.ipfs, &message_document)
+                .await?;
+
+            this.set_document().await?;
+
+            if let Err(e) = this.event_broadcast.send(MessageEventKind::PollUpdated {
+                conversation_id,
+                message_id,
+            }) {
+                tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
+            }
+        }
+        MessagingEvents::PollClose {
+            conversation_id,
+            message_id,
+            closed,
         } => {
+            let mut message_document = this
+                .document
+                .get_message_document(&this.ipfs, message_id)
+                .await?;
+
+            if message_document.sender() != *sender {
+                return Err(Error::Unauthorized);
+            }
+
+            message_document.close_poll(closed)?;
+
+            this.document
+                .update_message_document(&this.ipfs, &message_document)
+                .await?;
+
+            this.set_document().await?;
+
+            if let Err(e) = this.event_broadcast.send(MessageEventKind::PollUpdated {
+                conversation_id,
+                message_id,
+            }) {
+                tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
+            }
+        }
+        MessagingEvents::UpdateConversation { conversation, kind } => {
             conversation.verify()?;
-            conversation.excluded = this.document.excluded.clone();
-            conversation.messages = this.document.messages;
-            conversation.favorite = this.document.favorite;
-            conversation.archived = this.document.archived;
 
             match kind {
-                ConversationUpdateKind::AddParticipant { did } => {
+                ConversationUpdateKind::AddParticipant { .. } => {
                     if !this.document.creator.as_ref().is_some_and(|c| c == sender)
                         && !this
                             .document
@@ -2972,26 +3898,12 @@ async fn message_event(
                         return Err(Error::Unauthorized);
                     }
 
-                    if this.document.recipients.contains(&did) {
-                        return Ok(());
-                    }
-
-                    if !this.discovery.contains(&did).await {
-                        let _ = this.discovery.insert(&did).await;
-                    }
-
-                    this.replace_document(conversation).await?;
+                    let previous = this.document.recipients.clone();
 
-                    if let Err(e) = this.request_key(&did).await {
-                        tracing::error!(%conversation_id, error = %e, "error requesting key");
-                    }
+                    this.document.merge_recipients(&conversation);
+                    this.set_document().await?;
 
-                    if let Err(e) = this.event_broadcast.send(MessageEventKind::RecipientAdded {
-                        conversation_id,
-                        recipient: did,
-                    }) {
-                        tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
-                    }
+                    this.recipients_merged(&previous).await;
                 }
                 ConversationUpdateKind::RemoveParticipant { did } => {
                     if !this.document.creator.as_ref().is_some_and(|c| c == sender)
@@ -3006,27 +3918,12 @@ async fn message_event(
                         return Err(Error::IdentityDoesntExist);
                     }
 
-                    this.document.permissions.shift_remove(&did);
-
-                    //Maybe remove participant from discovery?
-
-                    let can_emit = !this.document.excluded.contains_key(&did);
+                    let previous = this.document.recipients.clone();
 
-                    this.document.excluded.remove(&did);
-
-                    this.replace_document(conversation).await?;
+                    this.document.merge_recipients(&conversation);
+                    this.set_document().await?;
 
-                    if can_emit {
-                        if let Err(e) =
-                            this.event_broadcast
-                                .send(MessageEventKind::RecipientRemoved {
-                                    conversation_id,
-                                    recipient: did,
-                                })
-                        {
-                            tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
-                        }
-                    }
+                    this.recipients_merged(&previous).await;
                 }
                 ConversationUpdateKind::ChangeName { name: Some(name) } => {
                     if !this.document.creator.as_ref().is_some_and(|c| c == sender)
@@ -3049,19 +3946,22 @@ async fn message_event(
                             maximum: Some(255),
                         });
                     }
-                    if let Some(current_name) = this.document.name.as_ref() {
-                        if current_name.eq(&name) {
-                            return Ok(());
-                        }
-                    }
+                    let previous = this.document.name();
+
+                    this.document.merge_name(&conversation);
+                    this.set_document().await?;
 
-                    this.replace_document(conversation).await?;
+                    let current = this.document.name();
+
+                    if previous == current {
+                        return Ok(());
+                    }
 
                     if let Err(e) =
                         this.event_broadcast
                             .send(MessageEventKind::ConversationNameUpdated {
                                 conversation_id,
-                                name: name.to_string(),
+                                name: current.unwrap_or_default(),
                             })
                     {
                         tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
@@ -3078,13 +3978,22 @@ async fn message_event(
                         return Err(Error::Unauthorized);
                     }
 
-                    this.replace_document(conversation).await?;
+                    let previous = this.document.name();
+
+                    this.document.merge_name(&conversation);
+                    this.set_document().await?;
+
+                    let current = this.document.name();
+
+                    if previous == current {
+                        return Ok(());
+                    }
 
                     if let Err(e) =
                         this.event_broadcast
                             .send(MessageEventKind::ConversationNameUpdated {
                                 conversation_id,
-                                name: String::new(),
+                                name: current.unwrap_or_default(),
                             })
                     {
                         tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
@@ -3095,7 +4004,8 @@ async fn message_event(
                     if !this.document.creator.as_ref().is_some_and(|c| c == sender) {
                         return Err(Error::Unauthorized);
                     }
-                    this.replace_document(conversation).await?;
+                    this.document.merge_restrict(&conversation);
+                    this.set_document().await?;
                     //TODO: Maybe add a api event to emit for when blocked users are added/removed from the document
                     //      but for now, we can leave this as a silent update since the block list would be for internal handling for now
                 }
@@ -3106,7 +4016,7 @@ async fn message_event(
 
                     let (added, removed) = this.document.permissions.compare_with_new(&permissions);
                     this.document.permissions = permissions;
-                    this.replace_document(conversation).await?;
+                    this.set_document().await?;
 
                     if let Err(e) = this.event_broadcast.send(
                         MessageEventKind::ConversationPermissionsUpdated {
@@ -3128,7 +4038,8 @@ async fn message_event(
                     {
                         return Err(Error::Unauthorized);
                     }
-                    this.replace_document(conversation).await?;
+                    this.document.icon = conversation.icon;
+                    this.set_document().await?;
 
                     if let Err(e) = this
                         .event_broadcast
@@ -3148,7 +4059,8 @@ async fn message_event(
                     {
                         return Err(Error::Unauthorized);
                     }
-                    this.replace_document(conversation).await?;
+                    this.document.banner = conversation.banner;
+                    this.set_document().await?;
 
                     if let Err(e) = this
                         .event_broadcast
@@ -3176,18 +4088,21 @@ async fn message_event(
                                 current: desc.len(),
                             });
                         }
+                    }
 
-                        if matches!(this.document.description.as_ref(), Some(current_desc) if current_desc == desc)
-                        {
-                            return Ok(());
-                        }
+                    let previous = this.document.description.clone();
+
+                    this.document.merge_description(&conversation);
+                    this.set_document().await?;
+
+                    if previous == this.document.description {
+                        return Ok(());
                     }
 
-                    this.replace_document(conversation).await?;
                     if let Err(e) = this.event_broadcast.send(
                         MessageEventKind::ConversationDescriptionChanged {
                             conversation_id,
-                            description,
+                            description: this.document.description.clone(),
                         },
                     ) {
                         tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
@@ -3288,6 +4203,93 @@ async fn process_request_response_event(
                     .await;
                 }
             }
+            ConversationRequestKind::RetrieveMessages {
+                start,
+                end,
+                offset,
+                limit,
+            } => {
+                if !this.document.recipients().contains(&sender) {
+                    return Err(Error::IdentityDoesntExist);
+                }
+
+                if !this.allow_sync_request(&sender) {
+                    tracing::warn!(%conversation_id, %sender, "Too many sync requests");
+                    return Ok(());
+                }
+
+                let ids = match (start, end) {
+                    (None, None) => this.document.stored_message_ids(&this.ipfs).await?,
+                    // Filtering by date requires the message documents to be loaded
+                    _ => this
+                        .document
+                        .get_message_list(&this.ipfs)
+                        .await?
+                        .into_iter()
+                        .filter(|message| start.is_none_or(|start| message.date >= start))
+                        .filter(|message| end.is_none_or(|end| message.date <= end))
+                        .map(|message| message.id)
+                        .collect(),
+                };
+
+                let limit = limit
+                    .unwrap_or(MESSAGE_SYNC_PAGE_SIZE)
+                    .min(MESSAGE_SYNC_PAGE_SIZE);
+
+                let next = (offset.saturating_add(limit) < ids.len()).then(|| offset + limit);
+
+                let messages = ids.into_iter().skip(offset).take(limit).collect::<Vec<_>>();
+
+                tracing::info!(%conversation_id, "Sending {} message ids to {sender}", messages.len());
+
+                let response = ConversationRequestResponse::Response {
+                    conversation_id,
+                    kind: ConversationResponseKind::HaveMessages { messages, next },
+                };
+
+                this.send_exchange(&sender, response).await?;
+            }
+            ConversationRequestKind::WantMessages { message_ids } => {
+                if !this.document.recipients().contains(&sender) {
+                    return Err(Error::IdentityDoesntExist);
+                }
+
+                if message_ids.len() > MESSAGE_SYNC_BATCH_SIZE {
+                    return Err(Error::InvalidLength {
+                        context: "message_ids".into(),
+                        current: message_ids.len(),
+                        minimum: None,
+                        maximum: Some(MESSAGE_SYNC_BATCH_SIZE),
+                    });
+                }
+
+                if !this.allow_sync_request(&sender) {
+                    tracing::warn!(%conversation_id, %sender, "Too many sync requests");
+                    return Ok(());
+                }
+
+                let mut messages = Vec::with_capacity(message_ids.len());
+
+                for message_id in message_ids {
+                    match this
+                        .document
+                        .get_message_document(&this.ipfs, message_id)
+                        .await
+                    {
+                        Ok(message) => messages.push(message),
+                        Err(e) => {
+                            tracing::warn!(%conversation_id, %message_id, error = %e, "Unable to get message");
+                        }
+                    }
+                }
+
+                let response = ConversationRequestResponse::Response {
+                    conversation_id,
+                    kind: ConversationResponseKind::Messages { messages },
+                };
+
+                this.send_exchange(&sender, response).await?;
+            }
             _ => {
                 tracing::info!(%conversation_id, "Unimplemented/Unsupported Event");
             }
@@ -3320,6 +4322,84 @@ async fn process_request_response_event(
                     }
                 }
             }
+            ConversationResponseKind::HaveMessages { messages, next } => {
+                if !this.sync.awaiting.contains(&sender) {
+                    tracing::warn!(%conversation_id, %sender, "Received message ids that were not requested");
+                    return Ok(());
+                }
+
+                let known = match this.document.message_ids(&this.ipfs).await {
+                    Ok(known) => known,
+                    Err(e) => {
+                        this.sync.awaiting.shift_remove(&sender);
+                        this.update_sync();
+                        return Err(e);
+                    }
+                };
+
+                let missing = messages
+                    .into_iter()
+                    .filter(|id| !known.contains(id) && !this.sync.pending.contains_key(id))
+                    .collect::<Vec<_>>();
+
+                for message_ids in missing.chunks(MESSAGE_SYNC_BATCH_SIZE) {
+                    let request = ConversationRequestResponse::Request {
+                        conversation_id,
+                        kind: ConversationRequestKind::WantMessages {
+                            message_ids: message_ids.to_vec(),
+                        },
+                    };
+
+                    if let Err(e) = this.send_exchange(&sender, request).await {
+                        tracing::warn!(%conversation_id, %sender, error = %e, "Unable to request messages");
+                        continue;
+                    }
+
+                    for message_id in message_ids {
+                        this.sync.pending.insert(*message_id, sender.clone());
+                    }
+                    this.sync.total += message_ids.len();
+                }
+
+                let requested_next = match next {
+                    Some(offset) => match this.request_message_page(&sender, offset).await {
+                        Ok(_) => true,
+                        Err(e) => {
+                            tracing::warn!(%conversation_id, %sender, error = %e, "Unable to request message ids");
+                            false
+                        }
+                    },
+                    None => false,
+                };
+
+                if !requested_next {
+                    this.sync.awaiting.shift_remove(&sender);
+                }
+
+                this.update_sync();
+            }
+            ConversationResponseKind::Messages { messages } => {
+                for message in messages {
+                    if this.sync.pending.get(&message.id) != Some(&sender) {
+                        tracing::warn!(%conversation_id, %sender, "Received message that was not requested");
+                        continue;
+                    }
+
+                    this.sync.pending.shift_remove(&message.id);
+
+                    let author = message.sender.to_did();
+
+                    match message_event(this, &author, MessagingEvents::New { message }, true).await
+                    {
+                        Ok(_) | Err(Error::MessageFound) => {}
+                        Err(e) => {
+                            tracing::warn!(%conversation_id, %sender, error = %e, "Unable to store retrieved message");
+                        }
+                    }
+                }
+
+                this.update_sync();
+            }
             _ => {
                 tracing::info!(%conversation_id, "Unimplemented/Unsupported Event");
             }
@@ -3372,7 +4452,7 @@ async fn process_pending_payload(this: &mut ConversationTask) {
             }
         };
 
-        if let Err(e) = message_event(this, &sender, event).await {
+        if let Err(e) = message_event(this, &sender, event, true).await {
             tracing::error!(name = "process_pending_payload", %conversation_id, %sender, error = %e, "failed to process message")
         }
     }
@@ -3397,19 +4477,44 @@ async fn process_conversation_event(
         member,
         event,
         cancelled,
+        ttl,
     } = event
     {
+        // Participants are only able to start or cancel events for themselves
+        if member != sender {
+            return Err(Error::Unauthorized);
+        }
+
         let ev = match cancelled {
-            true => MessageEventKind::EventCancelled {
-                conversation_id,
-                did_key: member,
-                event,
-            },
-            false => MessageEventKind::EventReceived {
-                conversation_id,
-                did_key: member,
-                event,
-            },
+            true => {
+                this.remote_events.remove(&(member.clone(), event));
+                MessageEventKind::EventCancelled {
+                    conversation_id,
+                    did_key: member,
+                    event,
+                }
+            }
+            false => {
+                let ttl = ttl
+                    .map(Duration::from_secs)
+                    .unwrap_or(MESSAGE_EVENT_TTL)
+                    .min(MAX_MESSAGE_EVENT_TTL);
+
+                // Rebroadcasted events only extend the expiry of an already active event
+                if this
+                    .remote_events
+                    .insert((member.clone(), event), Instant::now() + ttl)
+                    .is_some()
+                {
+                    return Ok(());
+                }
+
+                MessageEventKind::EventReceived {
+                    conversation_id,
+                    did_key: member,
+                    event,
+                }
+            }
         };
 
         if let Err(e) = this.event_broadcast.send(ev) {
@@ -3420,6 +4525,52 @@ async fn process_conversation_event(
     Ok(())
 }
 
+/// Check if the type of a file matches the requested type, treating a `*` subtype as a wildcard
+fn file_type_matches(filter: &FileType, file_type: &FileType) -> bool {
+    match (filter, file_type) {
+        (FileType::Mime(filter), FileType::Mime(mime)) => {
+            filter.ty() == mime.ty()
+                && (filter.subty().as_str() == "*" || filter.subty() == mime.subty())
+        }
+        (filter, file_type) => filter == file_type,
+    }
+}
+
+fn expire_message_events(this: &mut ConversationTask) {
+    let conversation_id = this.conversation_id;
+    for event in expired_message_events(conversation_id, &mut this.remote_events, Instant::now()) {
+        if let Err(e) = this.event_broadcast.send(event) {
+            tracing::warn!(%conversation_id, error = %e, "Error broadcasting event");
+        }
+    }
+}
+
+/// Removes the events that were not rebroadcasted before they expired, returning the cancellations
+/// to emit in their place
+fn expired_message_events(
+    conversation_id: Uuid,
+    events: &mut HashMap<(DID, MessageEvent), Instant>,
+    now: Instant,
+) -> Vec<MessageEventKind> {
+    let expired = events
+        .iter()
+        .filter(|(_, expire)| **expire <= now)
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+
+    expired
+        .into_iter()
+        .map(|(member, event)| {
+            events.remove(&(member.clone(), event));
+            MessageEventKind::EventCancelled {
+                conversation_id,
+                did_key: member,
+                event,
+            }
+        })
+        .collect()
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
 struct QueueItem {
     m_id: Option<Uuid>,
@@ -3524,3 +4675,45 @@ fn pubkey_or_keystore(conversation: &ConversationTask) -> Result<Either<DID, Key
 
     Ok(keystore)
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use rust_ipfs::Keypair;
+    use uuid::Uuid;
+    use warp::raygun::{MessageEvent, MessageEventKind};
+    use web_time::Instant;
+
+    use super::{expired_message_events, MESSAGE_EVENT_TTL};
+    use crate::store::PeerIdExt;
+
+    #[test]
+    fn events_are_cancelled_once_the_ttl_elapses() -> anyhow::Result<()> {
+        let conversation_id = Uuid::new_v4();
+        let member = Keypair::generate_ed25519().to_did()?;
+        let now = Instant::now();
+
+        let mut events = HashMap::new();
+        events.insert(
+            (member.clone(), MessageEvent::Typing),
+            now + MESSAGE_EVENT_TTL,
+        );
+
+        assert!(expired_message_events(conversation_id, &mut events, now).is_empty());
+        assert_eq!(events.len(), 1);
+
+        let expired = expired_message_events(conversation_id, &mut events, now + MESSAGE_EVENT_TTL);
+        assert!(events.is_empty());
+        assert_eq!(expired.len(), 1);
+        assert!(matches!(
+            &expired[0],
+            MessageEventKind::EventCancelled {
+                conversation_id: id,
+                did_key,
+                event: MessageEvent::Typing,
+            } if *id == conversation_id && *did_key == member
+        ));
+        Ok(())
+    }
+}
@@ -12,9 +12,13 @@ pub mod phonebook;
 pub mod queue;
 
 use chrono::{DateTime, Utc};
-use community::{CommunityChannelDocument, CommunityDocument, CommunityRoleDocument};
+use community::{
+    CommunityChannelCategoryDocument, CommunityChannelDocument, CommunityDocument,
+    CommunityRoleDocument,
+};
 use rust_ipfs as ipfs;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::store::community::CommunityInviteDocument;
@@ -30,12 +34,17 @@ use warp::{
     error::Error,
     multipass::identity::IdentityStatus,
     raygun::{
-        community::{CommunityChannelPermission, CommunityPermission, RoleId},
+        community::{
+            CommunityAuditAction, CommunityChannelPermission, CommunityPermission, RoleId,
+        },
         GroupPermissions, MessageEvent, PinState, ReactionState,
     },
 };
 
-use conversation::{message::MessageDocument, ConversationDocument};
+use conversation::{
+    message::{MessageDocument, PollVoteDocument},
+    ConversationDocument,
+};
 
 pub const MAX_THUMBNAIL_SIZE: usize = 5_242_880;
 pub const MAX_IMAGE_SIZE: usize = 2_097_152;
@@ -55,7 +64,26 @@ pub const MAX_METADATA_ENTRIES: usize = 20;
 pub const MAX_THUMBNAIL_STREAM_SIZE: usize = 20 * 1024 * 1024;
 pub const MAX_CONVERSATION_ICON_SIZE: usize = 4 * 1024 * 1024;
 pub const MAX_CONVERSATION_BANNER_SIZE: usize = 8 * 1024 * 1024;
-pub const MAX_COMMUNITY_CHANNELS: usize = 20;
+pub const MAX_COMMUNITY_CHANNELS: usize = 100;
+pub const MAX_COMMUNITY_CHANNEL_CATEGORIES: usize = 25;
+pub const MAX_COMMUNITY_BAN_REASON_LENGTH: usize = 512;
+/// Maximum number of audit log entries walked when catching up to a newer head
+pub const MAX_COMMUNITY_AUDIT_LOG_CATCH_UP: usize = 256;
+pub const MAX_CONVERSATION_LABEL_LENGTH: usize = 32;
+pub const MAX_CONVERSATION_LABELS: usize = 64;
+pub const MAX_QUARANTINED_MESSAGES: usize = 256;
+pub const MAX_SAVED_MESSAGES: usize = 512;
+pub const MAX_SAVED_MESSAGE_NOTE_LENGTH: usize = 512;
+pub const MAX_POLL_OPTIONS: usize = 20;
+pub const MAX_POLL_OPTION_LENGTH: usize = 256;
+/// Maximum length of a voice note, in seconds
+pub const MAX_VOICE_NOTE_DURATION: usize = 600;
+pub const VOICE_NOTE_WAVEFORM_LENGTH: usize = 64;
+/// Maximum amount of time, in milliseconds, a message clock may be ahead of the local clock
+pub const MAX_CLOCK_DRIFT: u64 = 5 * 60 * 1000;
+/// Amount of time, in milliseconds, a message from a peer may be dated early in a slow mode channel
+/// to allow for differences between clocks
+pub const SLOW_MODE_SKEW: i64 = 2 * 1000;
 
 pub(crate) mod protocols {
     use rust_ipfs::libp2p::StreamProtocol;
@@ -78,6 +106,7 @@ pub(crate) enum ConversationImageType {
     Banner,
 }
 pub const MAX_CONVERSATION_DESCRIPTION: usize = 256;
+pub const MAX_COMMUNITY_NAME_LENGTH: usize = 255;
 pub const MAX_COMMUNITY_DESCRIPTION: usize = 256;
 pub const MAX_REACTIONS: usize = 30;
 
@@ -362,18 +391,35 @@ pub enum ConversationRequestKind {
     RetrieveMessages {
         start: Option<DateTime<Utc>>,
         end: Option<DateTime<Utc>>,
+        #[serde(default)]
+        offset: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
     },
     WantMessage {
         message_id: Uuid,
     },
+    WantMessages {
+        message_ids: Vec<Uuid>,
+    },
 }
 
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ConversationResponseKind {
-    Key { key: Vec<u8> },
+    Key {
+        key: Vec<u8>,
+    },
     Pong,
-    HaveMessages { messages: Vec<Uuid> },
+    HaveMessages {
+        messages: Vec<Uuid>,
+        /// Offset of the next page of ids, if there are more
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        next: Option<usize>,
+    },
+    Messages {
+        messages: Vec<MessageDocument>,
+    },
     AcknowledgementConfirmed,
 }
 
@@ -415,6 +461,16 @@ pub enum MessagingEvents {
         state: ReactionState,
         emoji: String,
     },
+    PollVote {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        vote: PollVoteDocument,
+    },
+    PollClose {
+        conversation_id: Uuid,
+        message_id: Uuid,
+        closed: DateTime<Utc>,
+    },
     UpdateConversation {
         conversation: ConversationDocument,
         kind: ConversationUpdateKind,
@@ -424,6 +480,9 @@ pub enum MessagingEvents {
         member: DID,
         event: MessageEvent,
         cancelled: bool,
+        /// Duration, in seconds, the event is valid for unless it is broadcasted again
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ttl: Option<u64>,
     },
 }
 
@@ -465,6 +524,18 @@ pub enum CommunityMessagingEvents {
         state: ReactionState,
         emoji: String,
     },
+    PollVote {
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+        vote: PollVoteDocument,
+    },
+    PollClose {
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+        closed: DateTime<Utc>,
+    },
     JoinedCommunity {
         community_id: Uuid,
         user: DID,
@@ -550,6 +621,9 @@ pub enum CommunityUpdateKind {
     },
     EditIcon,
     EditBanner,
+    SetCommunityListing {
+        invite_id: Option<Uuid>,
+    },
     GrantCommunityPermission {
         permission: CommunityPermission,
         role_id: RoleId,
@@ -567,6 +641,22 @@ pub enum CommunityUpdateKind {
     RemoveCommunityMember {
         member: DID,
     },
+    BanCommunityMember {
+        member: DID,
+    },
+    UnbanCommunityMember {
+        member: DID,
+    },
+    TimeoutCommunityMember {
+        member: DID,
+        until: DateTime<Utc>,
+    },
+    TransferCommunityOwnership {
+        new_owner: Option<DID>,
+    },
+    AcceptCommunityOwnership {
+        previous_owner: DID,
+    },
     EditCommunityChannelName {
         channel_id: Uuid,
         name: String,
@@ -575,6 +665,18 @@ pub enum CommunityUpdateKind {
         channel_id: Uuid,
         description: Option<String>,
     },
+    EditCommunityChannelSlowMode {
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    },
+    FollowCommunityChannel {
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    },
+    UnfollowCommunityChannel {
+        channel_id: Uuid,
+    },
     GrantCommunityChannelPermission {
         channel_id: Uuid,
         permission: CommunityChannelPermission,
@@ -593,6 +695,277 @@ pub enum CommunityUpdateKind {
         channel_id: Uuid,
         permission: CommunityChannelPermission,
     },
+    CreateCommunityChannelCategory {
+        category: CommunityChannelCategoryDocument,
+    },
+    DeleteCommunityChannelCategory {
+        category_id: Uuid,
+    },
+    GrantCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    RevokeCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    GrantCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    RevokeCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    ReorderCommunityChannels,
+    MoveCommunityChannelToCategory {
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    },
+}
+
+impl From<&CommunityUpdateKind> for CommunityAuditAction {
+    fn from(kind: &CommunityUpdateKind) -> Self {
+        match kind {
+            CommunityUpdateKind::LeaveCommunity => CommunityAuditAction::LeaveCommunity,
+            CommunityUpdateKind::CreateCommunityInvite { invite } => {
+                CommunityAuditAction::CreateCommunityInvite {
+                    invite_id: invite.id,
+                }
+            }
+            CommunityUpdateKind::DeleteCommunityInvite { invite_id } => {
+                CommunityAuditAction::DeleteCommunityInvite {
+                    invite_id: *invite_id,
+                }
+            }
+            CommunityUpdateKind::EditCommunityInvite { invite_id } => {
+                CommunityAuditAction::EditCommunityInvite {
+                    invite_id: *invite_id,
+                }
+            }
+            CommunityUpdateKind::CreateCommunityRole { role } => {
+                CommunityAuditAction::CreateCommunityRole { role_id: role.id }
+            }
+            CommunityUpdateKind::DeleteCommunityRole { role_id } => {
+                CommunityAuditAction::DeleteCommunityRole { role_id: *role_id }
+            }
+            CommunityUpdateKind::EditCommunityRole { role_id } => {
+                CommunityAuditAction::EditCommunityRole { role_id: *role_id }
+            }
+            CommunityUpdateKind::GrantCommunityRole { role_id, user } => {
+                CommunityAuditAction::GrantCommunityRole {
+                    role_id: *role_id,
+                    user: user.clone(),
+                }
+            }
+            CommunityUpdateKind::RevokeCommunityRole { role_id, user } => {
+                CommunityAuditAction::RevokeCommunityRole {
+                    role_id: *role_id,
+                    user: user.clone(),
+                }
+            }
+            CommunityUpdateKind::CreateCommunityChannel { channel } => {
+                CommunityAuditAction::CreateCommunityChannel {
+                    channel_id: channel.id,
+                }
+            }
+            CommunityUpdateKind::DeleteCommunityChannel { channel_id } => {
+                CommunityAuditAction::DeleteCommunityChannel {
+                    channel_id: *channel_id,
+                }
+            }
+            CommunityUpdateKind::EditCommunityName { name } => {
+                CommunityAuditAction::EditCommunityName { name: name.clone() }
+            }
+            CommunityUpdateKind::EditCommunityDescription { description } => {
+                CommunityAuditAction::EditCommunityDescription {
+                    description: description.clone(),
+                }
+            }
+            CommunityUpdateKind::EditIcon => CommunityAuditAction::EditIcon,
+            CommunityUpdateKind::EditBanner => CommunityAuditAction::EditBanner,
+            CommunityUpdateKind::SetCommunityListing { invite_id } => {
+                CommunityAuditAction::SetCommunityListing {
+                    invite_id: *invite_id,
+                }
+            }
+            CommunityUpdateKind::GrantCommunityPermission {
+                permission,
+                role_id,
+            } => CommunityAuditAction::GrantCommunityPermission {
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::RevokeCommunityPermission {
+                permission,
+                role_id,
+            } => CommunityAuditAction::RevokeCommunityPermission {
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::GrantCommunityPermissionForAll { permission } => {
+                CommunityAuditAction::GrantCommunityPermissionForAll {
+                    permission: *permission,
+                }
+            }
+            CommunityUpdateKind::RevokeCommunityPermissionForAll { permission } => {
+                CommunityAuditAction::RevokeCommunityPermissionForAll {
+                    permission: *permission,
+                }
+            }
+            CommunityUpdateKind::RemoveCommunityMember { member } => {
+                CommunityAuditAction::RemoveCommunityMember {
+                    member: member.clone(),
+                }
+            }
+            CommunityUpdateKind::BanCommunityMember { member } => {
+                CommunityAuditAction::BanCommunityMember {
+                    member: member.clone(),
+                }
+            }
+            CommunityUpdateKind::UnbanCommunityMember { member } => {
+                CommunityAuditAction::UnbanCommunityMember {
+                    member: member.clone(),
+                }
+            }
+            CommunityUpdateKind::TimeoutCommunityMember { member, until } => {
+                CommunityAuditAction::TimeoutCommunityMember {
+                    member: member.clone(),
+                    until: *until,
+                }
+            }
+            CommunityUpdateKind::TransferCommunityOwnership { new_owner } => {
+                CommunityAuditAction::TransferCommunityOwnership {
+                    new_owner: new_owner.clone(),
+                }
+            }
+            CommunityUpdateKind::AcceptCommunityOwnership { previous_owner } => {
+                CommunityAuditAction::AcceptCommunityOwnership {
+                    previous_owner: previous_owner.clone(),
+                }
+            }
+            CommunityUpdateKind::EditCommunityChannelName { channel_id, name } => {
+                CommunityAuditAction::EditCommunityChannelName {
+                    channel_id: *channel_id,
+                    name: name.clone(),
+                }
+            }
+            CommunityUpdateKind::EditCommunityChannelDescription {
+                channel_id,
+                description,
+            } => CommunityAuditAction::EditCommunityChannelDescription {
+                channel_id: *channel_id,
+                description: description.clone(),
+            },
+            CommunityUpdateKind::EditCommunityChannelSlowMode {
+                channel_id,
+                slow_mode,
+            } => CommunityAuditAction::EditCommunityChannelSlowMode {
+                channel_id: *channel_id,
+                slow_mode: *slow_mode,
+            },
+            CommunityUpdateKind::FollowCommunityChannel {
+                channel_id,
+                source_community_id,
+                source_channel_id,
+            } => CommunityAuditAction::FollowCommunityChannel {
+                channel_id: *channel_id,
+                source_community_id: *source_community_id,
+                source_channel_id: *source_channel_id,
+            },
+            CommunityUpdateKind::UnfollowCommunityChannel { channel_id } => {
+                CommunityAuditAction::UnfollowCommunityChannel {
+                    channel_id: *channel_id,
+                }
+            }
+            CommunityUpdateKind::GrantCommunityChannelPermission {
+                channel_id,
+                permission,
+                role_id,
+            } => CommunityAuditAction::GrantCommunityChannelPermission {
+                channel_id: *channel_id,
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::RevokeCommunityChannelPermission {
+                channel_id,
+                permission,
+                role_id,
+            } => CommunityAuditAction::RevokeCommunityChannelPermission {
+                channel_id: *channel_id,
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::GrantCommunityChannelPermissionForAll {
+                channel_id,
+                permission,
+            } => CommunityAuditAction::GrantCommunityChannelPermissionForAll {
+                channel_id: *channel_id,
+                permission: *permission,
+            },
+            CommunityUpdateKind::RevokeCommunityChannelPermissionForAll {
+                channel_id,
+                permission,
+            } => CommunityAuditAction::RevokeCommunityChannelPermissionForAll {
+                channel_id: *channel_id,
+                permission: *permission,
+            },
+            CommunityUpdateKind::CreateCommunityChannelCategory { category } => {
+                CommunityAuditAction::CreateCommunityChannelCategory {
+                    category_id: category.id,
+                }
+            }
+            CommunityUpdateKind::DeleteCommunityChannelCategory { category_id } => {
+                CommunityAuditAction::DeleteCommunityChannelCategory {
+                    category_id: *category_id,
+                }
+            }
+            CommunityUpdateKind::GrantCommunityChannelCategoryPermission {
+                category_id,
+                permission,
+                role_id,
+            } => CommunityAuditAction::GrantCommunityChannelCategoryPermission {
+                category_id: *category_id,
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::RevokeCommunityChannelCategoryPermission {
+                category_id,
+                permission,
+                role_id,
+            } => CommunityAuditAction::RevokeCommunityChannelCategoryPermission {
+                category_id: *category_id,
+                permission: *permission,
+                role_id: *role_id,
+            },
+            CommunityUpdateKind::GrantCommunityChannelCategoryPermissionForAll {
+                category_id,
+                permission,
+            } => CommunityAuditAction::GrantCommunityChannelCategoryPermissionForAll {
+                category_id: *category_id,
+                permission: *permission,
+            },
+            CommunityUpdateKind::RevokeCommunityChannelCategoryPermissionForAll {
+                category_id,
+                permission,
+            } => CommunityAuditAction::RevokeCommunityChannelCategoryPermissionForAll {
+                category_id: *category_id,
+                permission: *permission,
+            },
+            CommunityUpdateKind::ReorderCommunityChannels => {
+                CommunityAuditAction::ReorderCommunityChannels
+            }
+            CommunityUpdateKind::MoveCommunityChannelToCategory {
+                channel_id,
+                category_id,
+            } => CommunityAuditAction::MoveCommunityChannelToCategory {
+                channel_id: *channel_id,
+                category_id: *category_id,
+            },
+        }
+    }
 }
 
 // Note that this are temporary
@@ -0,0 +1,154 @@
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use ogg::{PacketWriteEndInfo, PacketWriter};
+use warp::error::Error;
+use warp::raygun::VoiceNote;
+
+use crate::store::{MAX_VOICE_NOTE_DURATION, VOICE_NOTE_WAVEFORM_LENGTH};
+
+const SAMPLE_RATE: usize = VoiceNote::SAMPLE_RATE as usize;
+
+/// 20ms of audio per opus packet
+const FRAME_SIZE: usize = SAMPLE_RATE / 50;
+
+/// Recommended maximum size of an opus packet
+const MAX_PACKET_SIZE: usize = 4000;
+
+/// Samples the decoder should discard at the start of the stream to account for the encoder delay
+const PRE_SKIP: u16 = 312;
+
+const STREAM_SERIAL: u32 = 1;
+
+/// Encode a stream of mono PCM samples into an Ogg/Opus file, returning the file along with
+/// the duration and waveform of the recording
+pub async fn encode(mut pcm: BoxStream<'static, Vec<f32>>) -> Result<(Bytes, VoiceNote), Error> {
+    let max_samples = MAX_VOICE_NOTE_DURATION * SAMPLE_RATE;
+    let mut samples = Vec::new();
+
+    while let Some(chunk) = pcm.next().await {
+        samples.extend(chunk);
+        if samples.len() > max_samples {
+            return Err(Error::InvalidLength {
+                context: "voice note".into(),
+                current: samples.len() / SAMPLE_RATE,
+                minimum: None,
+                maximum: Some(MAX_VOICE_NOTE_DURATION),
+            });
+        }
+    }
+
+    if samples.is_empty() {
+        return Err(Error::EmptyMessage);
+    }
+
+    tokio::task::spawn_blocking(move || encode_samples(&samples))
+        .await
+        .map_err(anyhow::Error::from)?
+}
+
+fn encode_samples(samples: &[f32]) -> Result<(Bytes, VoiceNote), Error> {
+    let mut encoder = opus::Encoder::new(
+        VoiceNote::SAMPLE_RATE,
+        opus::Channels::Mono,
+        opus::Application::Voip,
+    )
+    .map_err(anyhow::Error::from)?;
+
+    let mut writer = PacketWriter::new(Vec::new());
+
+    writer.write_packet(opus_head(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+    writer.write_packet(opus_tags(), STREAM_SERIAL, PacketWriteEndInfo::EndPage, 0)?;
+
+    let mut output = vec![0; MAX_PACKET_SIZE];
+    let mut frame = vec![0.0; FRAME_SIZE];
+    let frames = samples.len().div_ceil(FRAME_SIZE);
+
+    for (index, chunk) in samples.chunks(FRAME_SIZE).enumerate() {
+        // The last frame is padded with silence, which is then trimmed by the granule position
+        frame.fill(0.0);
+        frame[..chunk.len()].copy_from_slice(chunk);
+
+        let size = encoder
+            .encode_float(&frame, &mut output)
+            .map_err(anyhow::Error::from)?;
+
+        let (end, granule) = match index + 1 == frames {
+            true => (PacketWriteEndInfo::EndStream, samples.len()),
+            false => (PacketWriteEndInfo::NormalPacket, (index + 1) * FRAME_SIZE),
+        };
+
+        writer.write_packet(
+            output[..size].to_vec(),
+            STREAM_SERIAL,
+            end,
+            (granule + PRE_SKIP as usize) as u64,
+        )?;
+    }
+
+    let duration = (samples.len() * 1000 / SAMPLE_RATE) as u64;
+
+    let data = Bytes::from(writer.into_inner());
+
+    Ok((data, VoiceNote::new(duration, waveform(samples))))
+}
+
+/// Downsample the recording into the peak amplitude of evenly sized buckets
+fn waveform(samples: &[f32]) -> Vec<u8> {
+    let bucket_size = samples.len().div_ceil(VOICE_NOTE_WAVEFORM_LENGTH).max(1);
+    samples
+        .chunks(bucket_size)
+        .map(|bucket| {
+            let peak = bucket
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+            (peak.min(1.0) * u8::MAX as f32).round() as u8
+        })
+        .collect()
+}
+
+fn opus_head() -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channel count
+    head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+    head.extend_from_slice(&VoiceNote::SAMPLE_RATE.to_le_bytes());
+    head.extend_from_slice(&0_i16.to_le_bytes()); // output gain
+    head.push(0); // channel mapping family
+    head
+}
+
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"warp-ipfs";
+    let mut tags = Vec::with_capacity(16 + vendor.len());
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&0_u32.to_le_bytes()); // user comment count
+    tags
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_samples, SAMPLE_RATE};
+    use crate::store::VOICE_NOTE_WAVEFORM_LENGTH;
+
+    #[test]
+    fn encode_sine_wave() -> anyhow::Result<()> {
+        let samples = (0..SAMPLE_RATE)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect::<Vec<_>>();
+
+        let (data, voice_note) = encode_samples(&samples)?;
+
+        assert!(data.starts_with(b"OggS"));
+        assert_eq!(voice_note.duration(), 1000);
+        assert_eq!(voice_note.waveform().len(), VOICE_NOTE_WAVEFORM_LENGTH);
+        assert!(voice_note
+            .waveform()
+            .iter()
+            .all(|peak| (120..=130).contains(peak)));
+        Ok(())
+    }
+}
@@ -54,9 +54,18 @@ pub async fn mesh_connect(nodes: Vec<Ipfs>) -> anyhow::Result<()> {
 
 #[allow(dead_code)]
 pub async fn create_account(
+    username: Option<&str>,
+    passphrase: Option<&str>,
+    context: Option<String>,
+) -> anyhow::Result<(WarpIpfsInstance, DID, Identity)> {
+    create_account_with(username, passphrase, context, WarpIpfsBuilder::default()).await
+}
+
+pub async fn create_account_with(
     username: Option<&str>,
     passphrase: Option<&str>,
     _: Option<String>,
+    builder: WarpIpfsBuilder,
 ) -> anyhow::Result<(WarpIpfsInstance, DID, Identity)> {
     let mut config = warp_ipfs::config::Config::development();
     *config.listen_on_mut() = vec![Multiaddr::empty().with(Protocol::Memory(0))];
@@ -69,7 +78,7 @@ pub async fn create_account(
 
     *config.bootstrap_mut() = Bootstrap::None;
 
-    let mut instance = WarpIpfsBuilder::default().set_config(config).await;
+    let mut instance = builder.set_config(config).await;
 
     instance.tesseract().unlock(b"internal pass").unwrap();
 
@@ -82,6 +91,14 @@ pub async fn create_account(
 #[allow(dead_code)]
 pub async fn create_accounts(
     infos: Vec<(Option<&str>, Option<&str>, Option<String>)>,
+) -> anyhow::Result<Vec<(WarpIpfsInstance, DID, Identity)>> {
+    create_accounts_with(infos, WarpIpfsBuilder::default).await
+}
+
+#[allow(dead_code)]
+pub async fn create_accounts_with(
+    infos: Vec<(Option<&str>, Option<&str>, Option<String>)>,
+    builder: impl Fn() -> WarpIpfsBuilder,
 ) -> anyhow::Result<Vec<(WarpIpfsInstance, DID, Identity)>> {
     let _ = tracing_subscriber::registry()
         .with(fmt::layer().pretty())
@@ -91,7 +108,7 @@ pub async fn create_accounts(
     let mut accounts = vec![];
     let mut nodes = vec![];
     for (username, passphrase, context) in infos {
-        let account = create_account(username, passphrase, context).await?;
+        let account = create_account_with(username, passphrase, context, builder()).await?;
         let ipfs = account
             .0
             .handle()
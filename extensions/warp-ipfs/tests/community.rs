@@ -8,7 +8,8 @@ mod test {
         constellation::{Constellation, Progression},
         raygun::{
             community::{
-                Community, CommunityChannelPermission, CommunityChannelType, CommunityInvite,
+                Community, CommunityAuditAction, CommunityAuditLogEntry, CommunityAuditLogOptions,
+                CommunityChannelPermission, CommunityChannelType, CommunityInvite,
                 CommunityPermission, RayGunCommunity,
             },
             Location, Message, MessageEvent, MessageEventKind, MessageEventStream, MessageOptions,
@@ -1666,18 +1667,118 @@ mod test {
         instance_a
             .grant_community_permission(community.id(), CommunityPermission::EditRoles, role.id())
             .await?;
+        let lower_role = instance_a
+            .create_community_role(community.id(), "Role1")
+            .await?;
 
         let new_name = "new_name".to_string();
         instance_b
-            .edit_community_role_name(community.id(), role.id(), new_name.clone())
+            .edit_community_role_name(community.id(), lower_role.id(), new_name.clone())
             .await?;
         let role = instance_a
-            .get_community_role(community.id(), role.id())
+            .get_community_role(community.id(), lower_role.id())
             .await?;
         assert_eq!(new_name, role.name());
         Ok(())
     }
     #[async_test]
+    async fn community_role_hierarchy() -> anyhow::Result<()> {
+        let context = Some("test::community_role_hierarchy".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let admin = instance_a
+            .create_community_role(community.id(), "Admin")
+            .await?;
+        let moderator = instance_a
+            .create_community_role(community.id(), "Moderator")
+            .await?;
+        assert!(admin.position() < moderator.position());
+
+        instance_a
+            .grant_community_role(community.id(), moderator.id(), did_b.clone())
+            .await?;
+        for permission in [
+            CommunityPermission::EditRoles,
+            CommunityPermission::GrantRoles,
+            CommunityPermission::RemoveMembers,
+        ] {
+            instance_a
+                .grant_community_permission(community.id(), permission, moderator.id())
+                .await?;
+        }
+        instance_a
+            .edit_community_role_color(community.id(), moderator.id(), Some(0x3498DB))
+            .await?;
+        instance_a
+            .edit_community_role_hoisted(community.id(), moderator.id(), true)
+            .await?;
+        let role = instance_a
+            .get_community_role(community.id(), moderator.id())
+            .await?;
+        assert_eq!(role.color(), Some(0x3498DB));
+        assert!(role.hoisted());
+        assert!(!role.mentionable());
+
+        let result = instance_b
+            .grant_community_role(community.id(), admin.id(), did_b.clone())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        let result = instance_b
+            .edit_community_role_name(community.id(), admin.id(), "Renamed".into())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        let result = instance_b
+            .edit_community_role_position(community.id(), moderator.id(), admin.position())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        let result = instance_b
+            .remove_community_member(community.id(), did_a.clone())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+        Ok(())
+    }
+    #[async_test]
     async fn unauthorized_edit_community_permissions() -> anyhow::Result<()> {
         let context = Some("test::unauthorized_edit_community_permissions".into());
         let acc = (None, None, context);
@@ -1947,6 +2048,1112 @@ mod test {
         Ok(())
     }
 
+    #[async_test]
+    async fn ban_community_member_prevents_rejoin() -> anyhow::Result<()> {
+        let context = Some("test::ban_community_member_prevents_rejoin".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), None, None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let result = instance_b
+            .ban_community_member(community.id(), did_a.clone(), None, None)
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        instance_a
+            .ban_community_member(community.id(), did_b.clone(), Some("Spam".into()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::BannedCommunityMember {
+                community_id: community.id(),
+                member: did_b.clone()
+            }
+        );
+
+        let bans = instance_a.list_community_bans(community.id()).await?;
+        assert_eq!(bans.len(), 1);
+        assert_eq!(bans[0].user(), &*did_b);
+        assert_eq!(bans[0].issuer(), &*did_a);
+        assert_eq!(bans[0].reason(), Some("Spam"));
+
+        let community_doc = instance_a.get_community(community.id()).await?;
+        assert!(!community_doc.members().contains(&*did_b));
+
+        instance_b.request_join_community(community.id()).await?;
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(RayGunEventKind::CommunityJoinRejected { community_id }) =
+                    rg_stream_b.next().await
+                {
+                    assert_eq!(community_id, community.id());
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        instance_a
+            .unban_community_member(community.id(), did_b.clone())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::UnbannedCommunityMember {
+                community_id: community.id(),
+                member: did_b.clone()
+            }
+        );
+        assert!(instance_a
+            .list_community_bans(community.id())
+            .await?
+            .is_empty());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn timeout_community_member_prevents_sending() -> anyhow::Result<()> {
+        let context = Some("test::timeout_community_member_prevents_sending".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let channel = instance_a
+            .create_community_channel(community.id(), "Channel0", CommunityChannelType::Standard)
+            .await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let until = chrono::Utc::now() + chrono::Duration::hours(1);
+        let result = instance_b
+            .timeout_community_member(community.id(), did_a.clone(), until)
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .timeout_community_member(community.id(), did_b.clone(), until)
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityMemberTimedOut {
+                community_id: community.id(),
+                member: did_b.clone(),
+                until,
+            }
+        );
+
+        let result = instance_b
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<Uuid, Error>(Error::CommunityMemberTimedOut))
+        );
+
+        let until = chrono::Utc::now() - chrono::Duration::seconds(1);
+        instance_a
+            .timeout_community_member(community.id(), did_b.clone(), until)
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityMemberTimedOut {
+                community_id: community.id(),
+                member: did_b.clone(),
+                until,
+            }
+        );
+
+        instance_b
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await?;
+        Ok(())
+    }
+
+    #[async_test]
+    async fn community_audit_log() -> anyhow::Result<()> {
+        let context = Some("test::community_audit_log".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), None, None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let result = instance_b
+            .get_community_audit_log(community.id(), CommunityAuditLogOptions::default())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!(
+                "{:?}",
+                Err::<Vec<CommunityAuditLogEntry>, Error>(Error::Unauthorized)
+            )
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .edit_community_name(community.id(), "Community1")
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityName {
+                community_id: community.id(),
+                name: "Community1".into()
+            }
+        );
+        instance_a
+            .grant_community_permission_for_all(community.id(), CommunityPermission::ViewAuditLog)
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::GrantedCommunityPermissionForAll {
+                community_id: community.id(),
+                permission: CommunityPermission::ViewAuditLog
+            }
+        );
+
+        let entries = instance_b
+            .get_community_audit_log(community.id(), CommunityAuditLogOptions::default())
+            .await?;
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|entry| entry.actor() == &*did_a));
+        assert_eq!(
+            entries[0].action(),
+            &CommunityAuditAction::GrantCommunityPermissionForAll {
+                permission: CommunityPermission::ViewAuditLog
+            }
+        );
+        assert_eq!(
+            entries[1].action(),
+            &CommunityAuditAction::EditCommunityName {
+                name: "Community1".into()
+            }
+        );
+        assert_eq!(
+            entries[2].action(),
+            &CommunityAuditAction::CreateCommunityInvite {
+                invite_id: invite.id()
+            }
+        );
+
+        let entries = instance_a
+            .get_community_audit_log(
+                community.id(),
+                CommunityAuditLogOptions::default().set_skip(1).set_limit(1),
+            )
+            .await?;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].action(),
+            &CommunityAuditAction::EditCommunityName {
+                name: "Community1".into()
+            }
+        );
+        Ok(())
+    }
+
+    #[async_test]
+    async fn community_audit_log_concurrent_updates() -> anyhow::Result<()> {
+        let context = Some("test::community_audit_log_concurrent_updates".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), None, None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .grant_community_permission_for_all(
+                community.id(),
+                CommunityPermission::EditDescription,
+            )
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::GrantedCommunityPermissionForAll {
+                community_id: community.id(),
+                permission: CommunityPermission::EditDescription
+            }
+        );
+
+        // Both updates are made from the same head of the audit log
+        let (mut instance_a_edit, mut instance_b_edit) = (instance_a.clone(), instance_b.clone());
+        futures::try_join!(
+            instance_a_edit.edit_community_description(community.id(), Some("A".into())),
+            instance_b_edit.edit_community_description(community.id(), Some("B".into())),
+        )?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityDescription {
+                community_id: community.id(),
+                description: Some("B".into())
+            }
+        );
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityDescription {
+                community_id: community.id(),
+                description: Some("A".into())
+            }
+        );
+
+        // Later updates from either member are still accepted
+        instance_b
+            .edit_community_description(community.id(), Some("C".into()))
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityDescription {
+                community_id: community.id(),
+                description: Some("C".into())
+            }
+        );
+
+        let entries = instance_a
+            .get_community_audit_log(community.id(), CommunityAuditLogOptions::default())
+            .await?;
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0].actor(), &*did_b);
+        assert_eq!(
+            entries[0].action(),
+            &CommunityAuditAction::EditCommunityDescription {
+                description: Some("C".into())
+            }
+        );
+        assert_eq!(
+            entries
+                .iter()
+                .filter(|entry| matches!(
+                    entry.action(),
+                    CommunityAuditAction::EditCommunityDescription { .. }
+                ))
+                .map(|entry| entry.actor())
+                .filter(|actor| *actor == &*did_a)
+                .count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[async_test]
+    async fn reorder_and_categorize_community_channels() -> anyhow::Result<()> {
+        let context = Some("test::reorder_and_categorize_community_channels".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, _, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let mut channels = vec![];
+        for name in ["Channel0", "Channel1", "Channel2"] {
+            let channel = instance_a
+                .create_community_channel(community.id(), name, CommunityChannelType::Standard)
+                .await?;
+            channels.push(channel.id());
+        }
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let result = instance_a
+            .reorder_community_channels(community.id(), vec![channels[0], channels[1]])
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!(
+                "{:?}",
+                Err::<(), Error>(Error::InvalidCommunityChannelOrder)
+            )
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        channels.reverse();
+        instance_a
+            .reorder_community_channels(community.id(), channels.clone())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityChannelsReordered {
+                community_id: community.id(),
+                channels: channels.iter().copied().collect(),
+            }
+        );
+        let community_b = instance_b.get_community(community.id()).await?;
+        assert_eq!(
+            community_b.channels().iter().copied().collect::<Vec<_>>(),
+            channels
+        );
+
+        let category = instance_a
+            .create_community_channel_category(community.id(), "Category0")
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::CreatedCommunityChannelCategory {
+                community_id: community.id(),
+                category: category.clone(),
+            }
+        );
+        instance_a
+            .move_channel_to_category(community.id(), channels[1], Some(category.id()))
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::MovedCommunityChannelToCategory {
+                community_id: community.id(),
+                channel_id: channels[1],
+                category_id: Some(category.id()),
+            }
+        );
+        instance_a
+            .revoke_community_channel_category_permission_for_all(
+                community.id(),
+                category.id(),
+                CommunityChannelPermission::SendMessages,
+            )
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::RevokedCommunityChannelCategoryPermissionForAll {
+                community_id: community.id(),
+                category_id: category.id(),
+                permission: CommunityChannelPermission::SendMessages,
+            }
+        );
+
+        let category_b = instance_b
+            .get_community_channel_category(community.id(), category.id())
+            .await?;
+        assert_eq!(
+            category_b.channels().iter().copied().collect::<Vec<_>>(),
+            vec![channels[1]]
+        );
+        let channel_b = instance_b
+            .get_community_channel(community.id(), channels[1])
+            .await?;
+        assert_eq!(channel_b.category(), Some(category.id()));
+
+        let result = instance_b
+            .send_community_channel_message(community.id(), channels[1], vec!["Hello".to_string()])
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<Uuid, Error>(Error::Unauthorized))
+        );
+        instance_b
+            .send_community_channel_message(community.id(), channels[0], vec!["Hello".to_string()])
+            .await?;
+        Ok(())
+    }
+
+    #[async_test]
+    async fn community_invite_usage() -> anyhow::Result<()> {
+        let context = Some("test::community_invite_usage".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+
+        let open_invite = instance_a
+            .create_community_invite(community.id(), None, None)
+            .await?;
+        assert_eq!(open_invite.creator(), Some(&*did_a));
+        assert_eq!(open_invite.max_uses(), None);
+        assert_eq!(open_invite.uses(), 0);
+
+        let mut invite = open_invite.clone();
+        invite.set_max_uses(Some(2));
+        instance_a
+            .edit_community_invite(community.id(), open_invite.id(), invite)
+            .await?;
+        let open_invite = instance_a
+            .get_community_invite(community.id(), open_invite.id())
+            .await?;
+        assert_eq!(open_invite.max_uses(), Some(2));
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite_for_b = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite_for_b.id()
+            }
+        );
+
+        let invites = instance_a.list_community_invites(community.id()).await?;
+        assert_eq!(invites, vec![open_invite.clone(), invite_for_b.clone()]);
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let invites = instance_a.list_community_invites(community.id()).await?;
+        assert_eq!(invites, vec![open_invite.clone()]);
+
+        let community = instance_a.get_community(community.id()).await?;
+        assert_eq!(
+            community.invite_joins().get(&*did_b),
+            Some(&invite_for_b.id())
+        );
+
+        let mut invite = open_invite.clone();
+        invite.set_max_uses(Some(0));
+        instance_a
+            .edit_community_invite(community.id(), open_invite.id(), invite)
+            .await?;
+        let result = instance_a
+            .get_community_invite(community.id(), open_invite.id())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!(
+                "{:?}",
+                Err::<CommunityInvite, Error>(Error::CommunityInviteDoesntExist)
+            )
+        );
+        Ok(())
+    }
+    #[async_test]
+    async fn community_listing() -> anyhow::Result<()> {
+        let context = Some("test::community_listing".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, _, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite_for_b = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite_for_b.id()
+            }
+        );
+
+        let result = instance_a
+            .set_community_listing(community.id(), Some(invite_for_b.id()))
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::InvalidCommunityInvite))
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let public_invite = instance_a
+            .create_community_invite(community.id(), None, None)
+            .await?;
+
+        let result = instance_b
+            .set_community_listing(community.id(), Some(public_invite.id()))
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        instance_a
+            .set_community_listing(community.id(), Some(public_invite.id()))
+            .await?;
+        let community = instance_a.get_community(community.id()).await?;
+        assert_eq!(community.public_invite(), Some(public_invite.id()));
+
+        instance_a
+            .delete_community_invite(community.id(), public_invite.id())
+            .await?;
+        let community = instance_a.get_community(community.id()).await?;
+        assert_eq!(community.public_invite(), None);
+        Ok(())
+    }
+    #[async_test]
+    async fn transfer_community_ownership() -> anyhow::Result<()> {
+        let context = Some("test::transfer_community_ownership".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b
+            .accept_community_invite(community.id(), invite.id())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::AcceptedCommunityInvite {
+                community_id: community.id(),
+                invite_id: invite.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let role = instance_a
+            .create_community_role(community.id(), "Role0")
+            .await?;
+        instance_a
+            .grant_community_role(community.id(), role.id(), did_a.clone())
+            .await?;
+
+        let result = instance_b.accept_community_ownership(community.id()).await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!(
+                "{:?}",
+                Err::<(), Error>(Error::CommunityOwnershipTransferDoesntExist)
+            )
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .transfer_community_ownership(community.id(), did_b.clone())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::OfferedCommunityOwnership {
+                community_id: community.id(),
+                new_owner: Some(did_b.clone())
+            }
+        );
+
+        instance_b
+            .accept_community_ownership(community.id())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::TransferredCommunityOwnership {
+                community_id: community.id(),
+                previous_owner: did_a.clone(),
+                new_owner: did_b.clone()
+            }
+        );
+
+        let community = instance_a.get_community(community.id()).await?;
+        assert_eq!(community.creator(), did_b);
+        assert_eq!(community.pending_owner(), None);
+        assert!(community.members().contains(did_a));
+
+        let role = instance_a
+            .get_community_role(community.id(), role.id())
+            .await?;
+        assert!(role.members().contains(did_a));
+
+        let result = instance_a
+            .transfer_community_ownership(community.id(), did_a.clone())
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+        Ok(())
+    }
+    #[async_test]
+    async fn transfer_community_ownership_after_missed_offer() -> anyhow::Result<()> {
+        let context = Some("test::transfer_community_ownership_after_missed_offer".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc.clone(), acc]).await?;
+        let (instance_a, did_a, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+        let (instance_c, did_c, _) = &mut accounts[2].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let mut rg_stream_c = instance_c.raygun_subscribe().await?;
+        let invite_for_b = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite_for_b.id()
+            }
+        );
+        let invite_for_c = instance_a
+            .create_community_invite(community.id(), Some(did_c.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_c, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite_for_c.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .transfer_community_ownership(community.id(), did_b.clone())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::OfferedCommunityOwnership {
+                community_id: community.id(),
+                new_owner: Some(did_b.clone())
+            }
+        );
+
+        // C only joins once the offer has been made, so never receives it
+        instance_c.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_c.clone()
+            }
+        );
+
+        let mut stream_c = instance_c.get_community_stream(community.id()).await?;
+        instance_b
+            .accept_community_ownership(community.id())
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_c, Duration::from_secs(60)).await?,
+            MessageEventKind::TransferredCommunityOwnership {
+                community_id: community.id(),
+                previous_owner: did_a.clone(),
+                new_owner: did_b.clone()
+            }
+        );
+
+        instance_b
+            .edit_community_name(community.id(), "Community1")
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_c, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityName {
+                community_id: community.id(),
+                name: "Community1".into()
+            }
+        );
+
+        let community = instance_c.get_community(community.id()).await?;
+        assert_eq!(community.creator(), did_b);
+        assert_eq!(community.name(), "Community1");
+        Ok(())
+    }
+    #[async_test]
+    async fn community_channel_slow_mode() -> anyhow::Result<()> {
+        let context = Some("test::community_channel_slow_mode".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, _, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let channel = instance_a
+            .create_community_channel(community.id(), "Channel0", CommunityChannelType::Standard)
+            .await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let slow_mode = Some(Duration::from_secs(60 * 60));
+        let result = instance_b
+            .edit_community_channel_slow_mode(community.id(), channel.id(), slow_mode)
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::Unauthorized))
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        instance_a
+            .edit_community_channel_slow_mode(community.id(), channel.id(), slow_mode)
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::EditedCommunityChannelSlowMode {
+                community_id: community.id(),
+                channel_id: channel.id(),
+                slow_mode,
+            }
+        );
+
+        let channel = instance_b
+            .get_community_channel(community.id(), channel.id())
+            .await?;
+        assert_eq!(channel.slow_mode(), slow_mode);
+
+        instance_b
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await?;
+        let result = instance_b
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<Uuid, Error>(Error::CommunityChannelSlowMode))
+        );
+
+        instance_a
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await?;
+        instance_a
+            .send_community_channel_message(community.id(), channel.id(), vec!["Hello".to_string()])
+            .await?;
+        Ok(())
+    }
+    #[async_test]
+    async fn community_announcement_channel() -> anyhow::Result<()> {
+        let context = Some("test::community_announcement_channel".into());
+        let acc = (None, None, context);
+        let accounts = create_accounts(vec![acc.clone(), acc]).await?;
+        let (instance_a, _, _) = &mut accounts[0].clone();
+        let (instance_b, did_b, _) = &mut accounts[1].clone();
+
+        let community = instance_a.create_community("Community0").await?;
+        let announcements = instance_a
+            .create_community_channel(
+                community.id(),
+                "Announcements",
+                CommunityChannelType::Announcement,
+            )
+            .await?;
+
+        let mut rg_stream_b = instance_b.raygun_subscribe().await?;
+        let invite = instance_a
+            .create_community_invite(community.id(), Some(did_b.clone()), None)
+            .await?;
+        assert_eq!(
+            next_event(&mut rg_stream_b, Duration::from_secs(60)).await?,
+            RayGunEventKind::CommunityInvited {
+                community_id: community.id(),
+                invite_id: invite.id()
+            }
+        );
+
+        let mut stream_a = instance_a.get_community_stream(community.id()).await?;
+        instance_b.request_join_community(community.id()).await?;
+        assert_eq!(
+            next_event(&mut stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityJoined {
+                community_id: community.id(),
+                user: did_b.clone()
+            }
+        );
+
+        let result = instance_b
+            .send_community_channel_message(
+                community.id(),
+                announcements.id(),
+                vec!["Hello".to_string()],
+            )
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<Uuid, Error>(Error::Unauthorized))
+        );
+
+        let other_community = instance_a.create_community("Community1").await?;
+        let following = instance_a
+            .create_community_channel(
+                other_community.id(),
+                "Channel0",
+                CommunityChannelType::Standard,
+            )
+            .await?;
+
+        let result = instance_a
+            .follow_community_channel(
+                community.id(),
+                announcements.id(),
+                other_community.id(),
+                following.id(),
+            )
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::InvalidCommunityChannelType))
+        );
+
+        // Only announcement channels can be followed
+        let standard = instance_a
+            .create_community_channel(community.id(), "Channel1", CommunityChannelType::Standard)
+            .await?;
+        let result = instance_a
+            .follow_community_channel(
+                other_community.id(),
+                following.id(),
+                community.id(),
+                standard.id(),
+            )
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::InvalidCommunityChannelType))
+        );
+
+        // The source channel has to be in a community we are a member of
+        let community_b = instance_b.create_community("Community2").await?;
+        let channel_b = instance_b
+            .create_community_channel(community_b.id(), "Channel0", CommunityChannelType::Standard)
+            .await?;
+        let other_announcements = instance_a
+            .create_community_channel(
+                other_community.id(),
+                "Announcements",
+                CommunityChannelType::Announcement,
+            )
+            .await?;
+        let result = instance_b
+            .follow_community_channel(
+                community_b.id(),
+                channel_b.id(),
+                other_community.id(),
+                other_announcements.id(),
+            )
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!("{:?}", Err::<(), Error>(Error::InvalidCommunity))
+        );
+
+        let mut other_stream_a = instance_a
+            .get_community_stream(other_community.id())
+            .await?;
+        instance_a
+            .follow_community_channel(
+                other_community.id(),
+                following.id(),
+                community.id(),
+                announcements.id(),
+            )
+            .await?;
+        assert_eq!(
+            next_event(&mut other_stream_a, Duration::from_secs(60)).await?,
+            MessageEventKind::FollowedCommunityChannel {
+                community_id: other_community.id(),
+                channel_id: following.id(),
+                source_community_id: community.id(),
+                source_channel_id: announcements.id(),
+            }
+        );
+
+        let mut stream_b = instance_b.get_community_stream(community.id()).await?;
+        let message_id = instance_a
+            .send_community_channel_message(
+                community.id(),
+                announcements.id(),
+                vec!["Hello".to_string()],
+            )
+            .await?;
+        assert_eq!(
+            next_event(&mut stream_b, Duration::from_secs(60)).await?,
+            MessageEventKind::CommunityMessageReceived {
+                community_id: community.id(),
+                channel_id: announcements.id(),
+                message_id,
+            }
+        );
+
+        instance_b
+            .react_to_community_channel_message(
+                community.id(),
+                announcements.id(),
+                message_id,
+                warp::raygun::ReactionState::Add,
+                ":thumbsup:".to_string(),
+            )
+            .await?;
+
+        let event = next_event(&mut other_stream_a, Duration::from_secs(60)).await?;
+        let crosspost_id = match event {
+            MessageEventKind::CommunityMessageSent {
+                community_id,
+                channel_id,
+                message_id,
+            } if community_id == other_community.id() && channel_id == following.id() => message_id,
+            _ => panic!(
+                "expected MessageEventKind::CommunityMessageSent event, got: {:?}",
+                event
+            ),
+        };
+        let crosspost = instance_a
+            .get_community_channel_message(other_community.id(), following.id(), crosspost_id)
+            .await?;
+        assert_eq!(crosspost.lines(), &["Hello".to_string()]);
+        Ok(())
+    }
     #[async_test]
     async fn unauthorized_edit_community_channel_name() -> anyhow::Result<()> {
         let context = Some("test::unauthorized_edit_community_channel_name".into());
@@ -8,12 +8,16 @@ mod test {
         constellation::Progression,
         multipass::MultiPassEventKind,
         raygun::{
-            AttachmentKind, ConversationType, Location, MessageEvent, MessageEventKind,
-            MessageType, PinState, RayGunEventKind, ReactionState,
+            AttachmentFilter, AttachmentKind, ConversationQuery, ConversationType, Location,
+            MessageBuilder, MessageEvent, MessageEventKind, MessageType, PinState, Poll,
+            RayGunEventKind, ReactionState, SignatureState, SyncStatus,
         },
     };
 
-    use crate::common::{create_accounts, PROFILE_IMAGE};
+    use crate::common::{create_accounts, create_accounts_with, PROFILE_IMAGE};
+
+    use warp_ipfs::filter::{FilterAction, FilterKind, FilterSource, MessageFilter};
+    use warp_ipfs::WarpIpfsBuilder;
 
     #[cfg(target_arch = "wasm32")]
     use wasm_bindgen_test::wasm_bindgen_test as async_test;
@@ -24,9 +28,12 @@ mod test {
     #[cfg(not(target_arch = "wasm32"))]
     use tokio::test as async_test;
     use warp::constellation::Constellation;
-    use warp::multipass::{Friends, MultiPassEvent};
+    use warp::multipass::IdentityInformation;
+    use warp::multipass::{Friends, MultiPass, MultiPassEvent};
+    use warp::raygun::bot::{BotCommand, BotCommandRegistry};
     use warp::raygun::{
-        RayGun, RayGunAttachment, RayGunConversationInformation, RayGunEvents, RayGunStream,
+        GroupPermission, Message, RayGun, RayGunAttachment, RayGunConversationInformation,
+        RayGunEvents, RayGunStream,
     };
 
     #[async_test]
@@ -285,17 +292,17 @@ mod test {
     }
 
     #[async_test]
-    async fn send_and_download_attachment_in_conversation() -> anyhow::Result<()> {
+    async fn send_message_with_metadata_in_conversation() -> anyhow::Result<()> {
         let accounts = create_accounts(vec![
             (
                 None,
                 None,
-                Some("test::send_and_download_attachment_in_conversation".into()),
+                Some("test::send_message_with_metadata_in_conversation".into()),
             ),
             (
                 None,
                 None,
-                Some("test::send_and_download_attachment_in_conversation".into()),
+                Some("test::send_message_with_metadata_in_conversation".into()),
             ),
         ])
         .await?;
@@ -328,104 +335,149 @@ mod test {
             }
         }).await?;
 
-        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
         let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
 
-        //upload file to constellation to attach file from constellation
-
-        instance_a.put_buffer("image.png", PROFILE_IMAGE).await?;
-
-        let (_, mut stream) = instance_a
-            .attach(
-                conversation_id,
-                None,
-                vec![Location::Constellation {
-                    path: "image.png".into(),
-                }],
-                vec![],
-            )
-            .await?;
+        let message = MessageBuilder::new(vec!["Hello, World".into()])
+            .add_mention(did_b.clone())
+            .add_metadata("client", "test");
 
-        while let Some(event) = stream.next().await {
-            match event {
-                AttachmentKind::AttachedProgress(
-                    _location,
-                    Progression::CurrentProgress { .. },
-                ) => {}
-                AttachmentKind::AttachedProgress(
-                    _location,
-                    Progression::ProgressComplete { name, total },
-                ) => {
-                    assert_eq!(name, "image.png");
-                    assert_eq!(total, Some(PROFILE_IMAGE.len()));
-                }
-                AttachmentKind::AttachedProgress(_location, Progression::ProgressFailed { .. }) => {
-                    unreachable!("should not fail")
-                }
-                AttachmentKind::Pending(result) => {
-                    result?;
-                }
-            }
-        }
+        instance_a.send_with(conversation_id, message).await?;
 
-        let message_a = crate::common::timeout(Duration::from_secs(60), async {
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
             loop {
-                if let Some(MessageEventKind::MessageSent {
+                if let Some(MessageEventKind::MessageReceived {
                     conversation_id,
                     message_id,
-                }) = conversation_a.next().await
+                }) = conversation_b.next().await
                 {
-                    break instance_a.get_message(conversation_id, message_id).await;
+                    break instance_b.get_message(conversation_id, message_id).await;
                 }
             }
         })
         .await??;
 
-        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+        assert_eq!(message_b.lines(), ["Hello, World".to_string()]);
+        assert_eq!(message_b.mentions(), [did_b]);
+        assert_eq!(
+            message_b.metadata().get("client").map(String::as_str),
+            Some("test")
+        );
+        assert_eq!(message_b.signature_state(), SignatureState::Verified);
+        Ok(())
+    }
+
+    #[async_test]
+    async fn conversation_labels_and_query() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::conversation_labels_and_query".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::conversation_labels_and_query".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
             loop {
-                if let Some(MessageEventKind::MessageReceived {
-                    conversation_id,
-                    message_id,
-                }) = conversation_b.next().await
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        instance_a
+            .add_conversation_label(conversation_id, "work")
+            .await?;
+
+        assert_eq!(instance_a.list_conversation_labels().await?, ["work"]);
+
+        let list = instance_a
+            .list_conversations(ConversationQuery::default().set_label("work"))
+            .await?;
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].conversation().id(), conversation_id);
+        assert_eq!(list[0].labels(), ["work"]);
+
+        let list = instance_a
+            .list_conversations(ConversationQuery::default().set_label("personal"))
+            .await?;
+        assert!(list.is_empty());
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+
+        instance_b
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived { .. }) = conversation_a.next().await
                 {
-                    break instance_b.get_message(conversation_id, message_id).await;
+                    break;
                 }
             }
         })
-        .await??;
-
-        assert_eq!(message_a, message_b);
-        assert_eq!(message_a.message_type(), MessageType::Attachment);
-        let attachments = message_a.attachments();
-        assert!(!attachments.is_empty());
+        .await?;
 
-        let file = attachments.first().expect("attachment exist");
+        let list = instance_a
+            .list_conversations(ConversationQuery::default().set_unread())
+            .await?;
+        assert_eq!(list.len(), 1);
+        let last_message = list[0].last_message().expect("message exist");
+        assert_eq!(last_message.lines(), ["Hello, World".to_string()]);
 
-        assert_eq!(file.name(), "image.png");
+        instance_a.mark_conversation_read(conversation_id).await?;
 
-        let stream = instance_b
-            .download_stream(conversation_id, message_a.id(), "image.png")
+        let list = instance_a
+            .list_conversations(ConversationQuery::default().set_unread())
             .await?;
+        assert!(list.is_empty());
 
-        let data = stream.try_collect::<Vec<_>>().await?.concat();
+        instance_a
+            .remove_conversation_label(conversation_id, "work")
+            .await?;
 
-        assert_eq!(data, PROFILE_IMAGE);
+        assert!(instance_a.list_conversation_labels().await?.is_empty());
         Ok(())
     }
 
     #[async_test]
-    async fn send_attachment_stream_and_download_attachment_in_conversation() -> anyhow::Result<()>
-    {
+    async fn send_and_download_attachment_in_conversation() -> anyhow::Result<()> {
         let accounts = create_accounts(vec![
             (
                 None,
                 None,
-                Some("test::send_attachment_stream_and_download_attachment_in_conversation".into()),
+                Some("test::send_and_download_attachment_in_conversation".into()),
             ),
             (
                 None,
                 None,
-                Some("test::send_attachment_stream_and_download_attachment_in_conversation".into()),
+                Some("test::send_and_download_attachment_in_conversation".into()),
             ),
         ])
         .await?;
@@ -461,14 +513,16 @@ mod test {
         let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
         let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
 
+        //upload file to constellation to attach file from constellation
+
+        instance_a.put_buffer("image.png", PROFILE_IMAGE).await?;
+
         let (_, mut stream) = instance_a
             .attach(
                 conversation_id,
                 None,
-                vec![Location::Stream {
-                    name: "image.png".into(),
-                    stream: futures::stream::iter(vec![Ok(PROFILE_IMAGE.into())]).boxed(),
-                    size: Some(PROFILE_IMAGE.len()),
+                vec![Location::Constellation {
+                    path: "image.png".into(),
                 }],
                 vec![],
             )
@@ -542,22 +596,22 @@ mod test {
     }
 
     #[async_test]
-    async fn delete_message_in_conversation() -> anyhow::Result<()> {
+    async fn list_attachments_in_conversation() -> anyhow::Result<()> {
         let accounts = create_accounts(vec![
             (
                 None,
                 None,
-                Some("test::delete_message_in_conversation".into()),
+                Some("test::list_attachments_in_conversation".into()),
             ),
             (
                 None,
                 None,
-                Some("test::delete_message_in_conversation".into()),
+                Some("test::list_attachments_in_conversation".into()),
             ),
         ])
         .await?;
 
-        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_a, did_a, _) = accounts.first().cloned().unwrap();
         let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
 
         let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
@@ -585,79 +639,100 @@ mod test {
             }
         }).await?;
 
-        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
         let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
 
         instance_a
             .send(conversation_id, vec!["Hello, World".into()])
             .await?;
 
-        let message_a = crate::common::timeout(Duration::from_secs(60), async {
-            loop {
-                if let Some(MessageEventKind::MessageSent {
-                    conversation_id,
-                    message_id,
-                }) = conversation_a.next().await
-                {
-                    break instance_a.get_message(conversation_id, message_id).await;
-                }
-            }
-        })
-        .await??;
-
-        let message_b = crate::common::timeout(Duration::from_secs(60), async {
-            loop {
-                if let Some(MessageEventKind::MessageReceived {
-                    conversation_id,
-                    message_id,
-                }) = conversation_b.next().await
-                {
-                    break instance_b.get_message(conversation_id, message_id).await;
-                }
-            }
-        })
-        .await??;
-
-        assert_eq!(message_a, message_b);
+        instance_a.put_buffer("image.png", PROFILE_IMAGE).await?;
 
-        instance_a
-            .delete(conversation_id, Some(message_a.id()))
+        let (message_id, mut stream) = instance_a
+            .attach(
+                conversation_id,
+                None,
+                vec![Location::Constellation {
+                    path: "image.png".into(),
+                }],
+                vec![],
+            )
             .await?;
 
-        // Maybe cross check the message id and convo
-        crate::common::timeout(Duration::from_secs(60), async {
-            loop {
-                if let Some(MessageEventKind::MessageDeleted { .. }) = conversation_a.next().await {
-                    break;
-                }
+        while let Some(event) = stream.next().await {
+            if let AttachmentKind::Pending(result) = event {
+                result?;
             }
-        })
-        .await?;
+        }
 
         crate::common::timeout(Duration::from_secs(60), async {
             loop {
-                if let Some(MessageEventKind::MessageDeleted { .. }) = conversation_b.next().await {
-                    break;
+                if let Some(MessageEventKind::MessageReceived { message_id: id, .. }) =
+                    conversation_b.next().await
+                {
+                    if id == message_id {
+                        break;
+                    }
                 }
             }
         })
         .await?;
 
+        for instance in [&instance_a, &instance_b] {
+            let attachments = instance
+                .list_attachments(conversation_id, AttachmentFilter::default())
+                .await?;
+            assert_eq!(attachments.len(), 1);
+
+            let attachment = attachments.first().expect("attachment exist");
+            assert_eq!(attachment.message_id(), message_id);
+            assert_eq!(attachment.sender(), &did_a);
+            assert_eq!(attachment.file().name(), "image.png");
+
+            let filter = AttachmentFilter::default().set_file_type(attachment.file().file_type());
+            assert_eq!(
+                instance
+                    .list_attachments(conversation_id, filter)
+                    .await?
+                    .len(),
+                1
+            );
+        }
+
+        let filter = AttachmentFilter::default().set_sender(did_b.clone());
+        assert!(instance_a
+            .list_attachments(conversation_id, filter)
+            .await?
+            .is_empty());
+
+        let filter = AttachmentFilter::default().set_skip(1);
+        assert!(instance_a
+            .list_attachments(conversation_id, filter)
+            .await?
+            .is_empty());
+
+        instance_a.delete(conversation_id, Some(message_id)).await?;
+
+        assert!(instance_a
+            .list_attachments(conversation_id, AttachmentFilter::default())
+            .await?
+            .is_empty());
+
         Ok(())
     }
 
     #[async_test]
-    async fn edit_message_in_conversation() -> anyhow::Result<()> {
+    async fn send_attachment_stream_and_download_attachment_in_conversation() -> anyhow::Result<()>
+    {
         let accounts = create_accounts(vec![
             (
                 None,
                 None,
-                Some("test::edit_message_in_conversation".into()),
+                Some("test::send_attachment_stream_and_download_attachment_in_conversation".into()),
             ),
             (
                 None,
                 None,
-                Some("test::edit_message_in_conversation".into()),
+                Some("test::send_attachment_stream_and_download_attachment_in_conversation".into()),
             ),
         ])
         .await?;
@@ -693,10 +768,41 @@ mod test {
         let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
         let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
 
-        instance_a
-            .send(conversation_id, vec!["Hello, World".into()])
+        let (_, mut stream) = instance_a
+            .attach(
+                conversation_id,
+                None,
+                vec![Location::Stream {
+                    name: "image.png".into(),
+                    stream: futures::stream::iter(vec![Ok(PROFILE_IMAGE.into())]).boxed(),
+                    size: Some(PROFILE_IMAGE.len()),
+                }],
+                vec![],
+            )
             .await?;
 
+        while let Some(event) = stream.next().await {
+            match event {
+                AttachmentKind::AttachedProgress(
+                    _location,
+                    Progression::CurrentProgress { .. },
+                ) => {}
+                AttachmentKind::AttachedProgress(
+                    _location,
+                    Progression::ProgressComplete { name, total },
+                ) => {
+                    assert_eq!(name, "image.png");
+                    assert_eq!(total, Some(PROFILE_IMAGE.len()));
+                }
+                AttachmentKind::AttachedProgress(_location, Progression::ProgressFailed { .. }) => {
+                    unreachable!("should not fail")
+                }
+                AttachmentKind::Pending(result) => {
+                    result?;
+                }
+            }
+        }
+
         let message_a = crate::common::timeout(Duration::from_secs(60), async {
             loop {
                 if let Some(MessageEventKind::MessageSent {
@@ -724,8 +830,564 @@ mod test {
         .await??;
 
         assert_eq!(message_a, message_b);
+        assert_eq!(message_a.message_type(), MessageType::Attachment);
+        let attachments = message_a.attachments();
+        assert!(!attachments.is_empty());
 
-        instance_a
+        let file = attachments.first().expect("attachment exist");
+
+        assert_eq!(file.name(), "image.png");
+
+        let stream = instance_b
+            .download_stream(conversation_id, message_a.id(), "image.png")
+            .await?;
+
+        let data = stream.try_collect::<Vec<_>>().await?.concat();
+
+        assert_eq!(data, PROFILE_IMAGE);
+        Ok(())
+    }
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "voice-note"))]
+    #[async_test]
+    async fn send_voice_note_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::send_voice_note_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::send_voice_note_in_conversation".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        // two seconds of a 440hz tone, delivered in 10ms chunks
+        let sample_rate = warp::raygun::VoiceNote::SAMPLE_RATE as usize;
+        let chunks = (0..sample_rate * 2)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / sample_rate as f32).sin())
+            .collect::<Vec<_>>()
+            .chunks(sample_rate / 100)
+            .map(<[f32]>::to_vec)
+            .collect::<Vec<_>>();
+
+        let (message_id, mut stream) = instance_a
+            .send_voice_note(conversation_id, futures::stream::iter(chunks).boxed())
+            .await?;
+
+        while let Some(event) = stream.next().await {
+            match event {
+                AttachmentKind::AttachedProgress(_location, Progression::ProgressFailed { .. }) => {
+                    unreachable!("should not fail")
+                }
+                AttachmentKind::AttachedProgress(..) => {}
+                AttachmentKind::Pending(result) => {
+                    result?;
+                }
+            }
+        }
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_b.id(), message_id);
+        assert_eq!(message_b.message_type(), MessageType::VoiceNote);
+        assert_eq!(message_b.attachments().len(), 1);
+
+        let voice_note = message_b.voice_note().expect("voice note is set");
+        assert_eq!(voice_note.duration(), 2000);
+        assert!(!voice_note.waveform().is_empty());
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn delete_message_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::delete_message_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::delete_message_in_conversation".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        let message_a = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageSent {
+                    conversation_id,
+                    message_id,
+                }) = conversation_a.next().await
+                {
+                    break instance_a.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_a, message_b);
+
+        instance_a
+            .delete(conversation_id, Some(message_a.id()))
+            .await?;
+
+        // Maybe cross check the message id and convo
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageDeleted { .. }) = conversation_a.next().await {
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageDeleted { .. }) = conversation_b.next().await {
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn save_message_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::save_message_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::save_message_in_conversation".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        let message_id = instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_b.id(), message_id);
+
+        instance_b
+            .save_message(conversation_id, message_id, Some("read later".into()))
+            .await?;
+
+        instance_a.delete(conversation_id, Some(message_id)).await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageDeleted { .. }) = conversation_b.next().await {
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        assert!(instance_b
+            .get_message(conversation_id, message_id)
+            .await
+            .is_err());
+
+        let saved = instance_b.list_saved_messages().await?;
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].conversation_id(), conversation_id);
+        assert_eq!(saved[0].message(), &message_b);
+        assert_eq!(saved[0].note(), Some("read later"));
+
+        instance_b
+            .unsave_message(conversation_id, message_id)
+            .await?;
+
+        assert!(instance_b.list_saved_messages().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn draft_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (None, None, Some("test::draft_in_conversation".into())),
+            (None, None, Some("test::draft_in_conversation".into())),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+
+        assert!(instance_a.get_draft(conversation_id).await?.is_none());
+
+        instance_a
+            .set_draft(
+                conversation_id,
+                vec!["Hello,".into(), "World".into()],
+                vec!["image.png".into()],
+            )
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::DraftUpdated {
+                    conversation_id: id,
+                }) = conversation_a.next().await
+                {
+                    assert_eq!(id, conversation_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        let draft = instance_a
+            .get_draft(conversation_id)
+            .await?
+            .expect("draft is stored");
+        assert_eq!(draft.lines(), ["Hello,", "World"]);
+        assert_eq!(draft.attachments(), [std::path::PathBuf::from("image.png")]);
+
+        assert!(instance_b.get_draft(conversation_id).await?.is_none());
+
+        instance_a
+            .set_draft(conversation_id, vec![], vec![])
+            .await?;
+
+        assert!(instance_a.get_draft(conversation_id).await?.is_none());
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn force_resync_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (None, None, Some("test::force_resync_conversation".into())),
+            (None, None, Some("test::force_resync_conversation".into())),
+        ])
+        .await?;
+
+        let (mut instance_a, did_a, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        let message_id = instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived { message_id: id, .. }) =
+                    conversation_b.next().await
+                {
+                    assert_eq!(id, message_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        instance_b.force_resync(conversation_id, &did_a).await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::SyncProgress {
+                    conversation_id: id,
+                    status: SyncStatus::InSync,
+                }) = conversation_b.next().await
+                {
+                    assert_eq!(id, conversation_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        assert_eq!(
+            instance_b.sync_status(conversation_id).await?,
+            SyncStatus::InSync
+        );
+
+        assert_eq!(instance_b.get_message_count(conversation_id).await?, 1);
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn edit_message_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::edit_message_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::edit_message_in_conversation".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        let message_a = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageSent {
+                    conversation_id,
+                    message_id,
+                }) = conversation_a.next().await
+                {
+                    break instance_a.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_a, message_b);
+
+        instance_a
             .edit(conversation_id, message_a.id(), vec!["New Message".into()])
             .await?;
 
@@ -1006,84 +1668,378 @@ mod test {
                     break conversation_id;
                 }
             }
-        })
-        .await?;
+        })
+        .await?;
+
+        let mut conversation_a = instance_a.get_conversation_stream(id_a).await?;
+        let mut conversation_b = instance_b.get_conversation_stream(id_b).await?;
+
+        instance_a
+            .set_conversation_description(id_a, Some("hello, world!"))
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::ConversationDescriptionChanged {
+                    conversation_id,
+                    description,
+                }) = conversation_a.next().await
+                {
+                    assert_eq!(id_a, conversation_id);
+                    assert_eq!(description, Some("hello, world!".into()));
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::ConversationDescriptionChanged {
+                    conversation_id,
+                    description,
+                }) = conversation_b.next().await
+                {
+                    assert_eq!(id_a, conversation_id);
+                    assert_eq!(description, Some("hello, world!".into()));
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        let conversation = instance_a.get_conversation(id_a).await?;
+        assert_eq!(conversation.description(), Some("hello, world!"));
+
+        instance_a.set_conversation_description(id_a, None).await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::ConversationDescriptionChanged {
+                    conversation_id,
+                    description,
+                }) = conversation_a.next().await
+                {
+                    assert_eq!(id_a, conversation_id);
+                    assert_eq!(description, None);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::ConversationDescriptionChanged {
+                    conversation_id,
+                    description,
+                }) = conversation_b.next().await
+                {
+                    assert_eq!(id_a, conversation_id);
+                    assert_eq!(description, None);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        let conversation = instance_a.get_conversation(id_a).await?;
+        assert_eq!(conversation.description(), None);
+
+        Ok(())
+    }
+
+    #[derive(Clone)]
+    struct KeywordFilter;
+
+    #[async_trait::async_trait]
+    impl MessageFilter for KeywordFilter {
+        async fn filter(&self, _: FilterSource, _: FilterKind, message: &Message) -> FilterAction {
+            let lines = message.lines().join(" ");
+            if lines.contains("spam") {
+                return FilterAction::Reject {
+                    reason: "spam".into(),
+                };
+            }
+            if lines.contains("hold") {
+                return FilterAction::Quarantine {
+                    reason: "held for review".into(),
+                };
+            }
+            FilterAction::Accept
+        }
+    }
+
+    #[async_test]
+    async fn filter_message_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts_with(
+            vec![
+                (
+                    None,
+                    None,
+                    Some("test::filter_message_in_conversation".into()),
+                ),
+                (
+                    None,
+                    None,
+                    Some("test::filter_message_in_conversation".into()),
+                ),
+            ],
+            || WarpIpfsBuilder::default().set_message_filter(KeywordFilter),
+        )
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        instance_a
+            .send(conversation_id, vec!["buy spam".into()])
+            .await?;
+        let held_id = instance_a
+            .send(conversation_id, vec!["hold this".into()])
+            .await?;
+        instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_b.lines(), ["Hello, World"]);
+        assert_eq!(instance_b.get_message_count(conversation_id).await?, 1);
+
+        let quarantined = instance_b
+            .list_quarantined_messages(conversation_id)
+            .await?;
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].message().id(), held_id);
+        assert_eq!(quarantined[0].reason(), "held for review");
+        assert!(!quarantined[0].is_edit());
+
+        instance_b
+            .release_quarantined_message(conversation_id, held_id)
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived { message_id, .. }) =
+                    conversation_b.next().await
+                {
+                    assert_eq!(message_id, held_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        assert_eq!(instance_b.get_message_count(conversation_id).await?, 2);
+        assert!(instance_b
+            .list_quarantined_messages(conversation_id)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn bot_command_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (None, None, Some("test::bot_command_in_conversation".into())),
+            (None, None, Some("test::bot_command_in_conversation".into())),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
 
-        let mut conversation_a = instance_a.get_conversation_stream(id_a).await?;
-        let mut conversation_b = instance_b.get_conversation_stream(id_b).await?;
+        let mut registry = BotCommandRegistry::new();
+        registry.register(
+            BotCommand::new("echo", |invocation| async move {
+                Ok(vec![invocation.args().join(" ")])
+            })
+            .set_description("Repeat the arguments back"),
+        );
+        registry.register(
+            BotCommand::new("rename", |_| async move { Ok(vec!["renamed".into()]) })
+                .set_permission(GroupPermission::EditGroupInfo),
+        );
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
 
         instance_a
-            .set_conversation_description(id_a, Some("hello, world!"))
+            .send(conversation_id, vec!["/rename test".into()])
+            .await?;
+        let command_id = instance_a
+            .send(conversation_id, vec!["/echo hello world".into()])
             .await?;
 
         crate::common::timeout(Duration::from_secs(60), async {
-            loop {
-                if let Some(MessageEventKind::ConversationDescriptionChanged {
+            let mut handled = 0;
+            while handled < 2 {
+                if let Some(MessageEventKind::MessageReceived {
                     conversation_id,
-                    description,
-                }) = conversation_a.next().await
+                    message_id,
+                }) = conversation_b.next().await
                 {
-                    assert_eq!(id_a, conversation_id);
-                    assert_eq!(description, Some("hello, world!".into()));
-                    break;
+                    let message = instance_b.get_message(conversation_id, message_id).await?;
+                    let result = registry.handle_message(&mut instance_b, message).await;
+                    if message_id == command_id {
+                        assert!(result?.is_some());
+                    } else {
+                        // direct conversations do not have group permissions
+                        assert!(matches!(result, Err(warp::error::Error::Unauthorized)));
+                    }
+                    handled += 1;
                 }
             }
+            Ok::<_, anyhow::Error>(())
         })
-        .await?;
+        .await??;
 
-        crate::common::timeout(Duration::from_secs(60), async {
+        let reply = crate::common::timeout(Duration::from_secs(60), async {
             loop {
-                if let Some(MessageEventKind::ConversationDescriptionChanged {
+                if let Some(MessageEventKind::MessageReceived {
                     conversation_id,
-                    description,
-                }) = conversation_b.next().await
+                    message_id,
+                }) = conversation_a.next().await
                 {
-                    assert_eq!(id_a, conversation_id);
-                    assert_eq!(description, Some("hello, world!".into()));
-                    break;
+                    break instance_a.get_message(conversation_id, message_id).await;
                 }
             }
         })
+        .await??;
+
+        assert_eq!(reply.lines(), ["hello world"]);
+        assert_eq!(reply.replied(), Some(command_id));
+        assert!(!instance_b.identity_is_bot(&did_b).await?);
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn pinned_message_limit() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (None, None, Some("test::pinned_message_limit".into())),
+            (None, None, Some("test::pinned_message_limit".into())),
+        ])
         .await?;
 
-        let conversation = instance_a.get_conversation(id_a).await?;
-        assert_eq!(conversation.description(), Some("hello, world!"));
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
 
-        instance_a.set_conversation_description(id_a, None).await?;
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
 
-        crate::common::timeout(Duration::from_secs(60), async {
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
             loop {
-                if let Some(MessageEventKind::ConversationDescriptionChanged {
-                    conversation_id,
-                    description,
-                }) = conversation_a.next().await
-                {
-                    assert_eq!(id_a, conversation_id);
-                    assert_eq!(description, None);
-                    break;
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
                 }
-            }
-        })
-        .await?;
 
-        crate::common::timeout(Duration::from_secs(60), async {
-            loop {
-                if let Some(MessageEventKind::ConversationDescriptionChanged {
-                    conversation_id,
-                    description,
-                }) = conversation_b.next().await
-                {
-                    assert_eq!(id_a, conversation_id);
-                    assert_eq!(description, None);
-                    break;
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
                 }
             }
-        })
-        .await?;
+        }).await?;
 
-        let conversation = instance_a.get_conversation(id_a).await?;
-        assert_eq!(conversation.description(), None);
+        let limit = warp_ipfs::config::Config::development()
+            .store_setting()
+            .max_pinned_messages;
+
+        for _ in 0..limit {
+            let message_id = instance_a
+                .send(conversation_id, vec!["Hello, World".into()])
+                .await?;
+            instance_a
+                .pin(conversation_id, message_id, PinState::Pin)
+                .await?;
+        }
 
+        let message_id = instance_a
+            .send(conversation_id, vec!["Hello, World".into()])
+            .await?;
+        let result = instance_a
+            .pin(conversation_id, message_id, PinState::Pin)
+            .await;
+        assert!(matches!(
+            result,
+            Err(warp::error::Error::PinnedMessageLimitReached)
+        ));
+
+        assert_eq!(instance_a.list_pinned(conversation_id).await?.len(), limit);
         Ok(())
     }
 
@@ -1095,7 +2051,7 @@ mod test {
         ])
         .await?;
 
-        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_a, did_a, _) = accounts.first().cloned().unwrap();
         let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
 
         let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
@@ -1200,6 +2156,11 @@ mod test {
         })
         .await?;
 
+        let pinned = instance_b.list_pinned(conversation_id).await?;
+        assert_eq!(pinned.len(), 1);
+        assert_eq!(pinned[0].message_id(), message_b.id());
+        assert_eq!(pinned[0].pinned_by(), &did_a);
+
         instance_a
             .pin(conversation_id, message_a.id(), PinState::Unpin)
             .await?;
@@ -1223,6 +2184,8 @@ mod test {
         })
         .await?;
 
+        assert!(instance_a.list_pinned(conversation_id).await?.is_empty());
+
         crate::common::timeout(Duration::from_secs(60), async {
             loop {
                 if let Some(MessageEventKind::MessageUnpinned {
@@ -1245,6 +2208,214 @@ mod test {
         Ok(())
     }
 
+    #[async_test]
+    async fn poll_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (None, None, Some("test::poll_in_conversation".into())),
+            (None, None, Some("test::poll_in_conversation".into())),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts.first().cloned().unwrap();
+        let (mut instance_b, did_b, _) = accounts.last().cloned().unwrap();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        let mut conversation_a = instance_a.get_conversation_stream(conversation_id).await?;
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        let poll = Poll::new("Favorite color?", vec!["Red".into(), "Blue".into()]);
+
+        let poll_id = instance_a.send_poll(conversation_id, poll.clone()).await?;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_b.id(), poll_id);
+        assert_eq!(message_b.message_type(), MessageType::Poll);
+        assert_eq!(message_b.poll(), Some(&poll));
+
+        let result = instance_b
+            .vote_poll(conversation_id, poll_id, vec![0, 1])
+            .await;
+        assert!(result.is_err());
+
+        instance_b
+            .vote_poll(conversation_id, poll_id, vec![1])
+            .await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::PollUpdated { message_id, .. }) =
+                    conversation_a.next().await
+                {
+                    assert_eq!(message_id, poll_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        let results = instance_a.poll_results(conversation_id, poll_id).await?;
+        assert_eq!(results.tally(), vec![0, 1]);
+        assert!(!results.is_closed());
+
+        instance_a.close_poll(conversation_id, poll_id).await?;
+
+        crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::PollUpdated { message_id, .. }) =
+                    conversation_b.next().await
+                {
+                    assert_eq!(message_id, poll_id);
+                    break;
+                }
+            }
+        })
+        .await?;
+
+        let results = instance_b.poll_results(conversation_id, poll_id).await?;
+        assert!(results.is_closed());
+
+        let result = instance_b
+            .vote_poll(conversation_id, poll_id, vec![0])
+            .await;
+        assert_eq!(
+            format!("{:?}", result),
+            format!(
+                "{:?}",
+                Err::<(), warp::error::Error>(warp::error::Error::PollClosed)
+            )
+        );
+
+        Ok(())
+    }
+
+    #[async_test]
+    async fn contact_card_in_conversation() -> anyhow::Result<()> {
+        let accounts = create_accounts(vec![
+            (
+                None,
+                None,
+                Some("test::contact_card_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::contact_card_in_conversation".into()),
+            ),
+            (
+                None,
+                None,
+                Some("test::contact_card_in_conversation".into()),
+            ),
+        ])
+        .await?;
+
+        let (mut instance_a, _, _) = accounts[0].clone();
+        let (mut instance_b, did_b, _) = accounts[1].clone();
+        let (_, did_c, _) = accounts[2].clone();
+
+        let mut chat_subscribe_a = instance_a.raygun_subscribe().await?;
+        let mut chat_subscribe_b = instance_b.raygun_subscribe().await?;
+
+        instance_a.create_conversation(&did_b).await?;
+
+        let conversation_id = crate::common::timeout(Duration::from_secs(60), async {
+            let mut id_a = None;
+            let mut id_b = None;
+            loop {
+                tokio::select! {
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_a.next() => {
+                        id_a.replace(conversation_id);
+                    },
+                    Some(RayGunEventKind::ConversationCreated { conversation_id }) = chat_subscribe_b.next() => {
+                        id_b.replace(conversation_id);
+                    },
+                }
+
+                if id_a.is_some() && id_b.is_some() {
+                    assert_eq!(id_a, id_b);
+                    break id_a.expect("valid conversation_id")
+                }
+            }
+        }).await?;
+
+        // wait for the identity to be discovered before sharing it
+        let identity_c = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Ok(identity) = instance_a.get_identity(did_c.clone()).await {
+                    break identity;
+                }
+            }
+        })
+        .await?;
+
+        let mut conversation_b = instance_b.get_conversation_stream(conversation_id).await?;
+
+        let card_id = instance_a
+            .send_contact_card(conversation_id, &did_c)
+            .await?;
+
+        let message_b = crate::common::timeout(Duration::from_secs(60), async {
+            loop {
+                if let Some(MessageEventKind::MessageReceived {
+                    conversation_id,
+                    message_id,
+                }) = conversation_b.next().await
+                {
+                    break instance_b.get_message(conversation_id, message_id).await;
+                }
+            }
+        })
+        .await??;
+
+        assert_eq!(message_b.id(), card_id);
+        assert_eq!(message_b.message_type(), MessageType::ContactCard);
+        assert_eq!(message_b.contact_card(), Some(&identity_c));
+
+        let identity = instance_b.get_identity(did_c.clone()).await?;
+        assert_eq!(identity.did_key(), &did_c);
+
+        instance_b.send_request_from_card(&message_b).await?;
+        assert!(instance_b.sent_friend_request_to(&did_c).await?);
+
+        Ok(())
+    }
+
     #[async_test]
     async fn event_in_conversation() -> anyhow::Result<()> {
         let accounts = create_accounts(vec![
@@ -1311,6 +2482,11 @@ mod test {
         })
         .await?;
 
+        assert_eq!(
+            instance_b.typing_participants(conversation_id).await?,
+            [did_a.clone()]
+        );
+
         instance_a
             .cancel_event(conversation_id, MessageEvent::Typing)
             .await?;
@@ -1331,6 +2507,11 @@ mod test {
         })
         .await?;
 
+        assert!(instance_b
+            .typing_participants(conversation_id)
+            .await?
+            .is_empty());
+
         Ok(())
     }
 
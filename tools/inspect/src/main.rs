@@ -118,7 +118,12 @@ async fn main() -> anyhow::Result<()> {
     }
 
     let start_time = Instant::now();
-    let conversations = instance.list_conversations().await?;
+    let conversations = instance
+        .list_conversations(Default::default())
+        .await?
+        .into_iter()
+        .map(|summary| summary.conversation().clone())
+        .collect::<Vec<_>>();
     let end_time = start_time.elapsed();
     println!(
         "Took {}ms to load list of conversations",
@@ -149,6 +149,16 @@ pub enum Error {
     MessagePinned,
     #[error("Message is not pinned")]
     MessageNotPinned,
+    #[error("Maximum pinned messages has been reached")]
+    PinnedMessageLimitReached,
+    #[error("Message is not a poll")]
+    MessageNotPoll,
+    #[error("Poll is closed")]
+    PollClosed,
+    #[error("Invalid poll vote")]
+    InvalidPollVote,
+    #[error("Message is not a contact card")]
+    MessageNotContactCard,
     #[error("Message exist within conversation")]
     MessageFound,
     #[error("Message not found within conversation")]
@@ -179,12 +189,30 @@ pub enum Error {
     CommunityInviteExpired,
     #[error("Community invite doesn't exist")]
     CommunityInviteDoesntExist,
+    #[error("Member is banned from the community")]
+    CommunityMemberBanned,
+    #[error("Community ban doesn't exist")]
+    CommunityBanDoesntExist,
+    #[error("Member is timed out in the community")]
+    CommunityMemberTimedOut,
+    #[error("Community ownership transfer doesn't exist")]
+    CommunityOwnershipTransferDoesntExist,
     #[error("Community channel doesn't exist")]
     CommunityChannelDoesntExist,
+    #[error("Slow mode is enabled in the community channel")]
+    CommunityChannelSlowMode,
+    #[error("Community channel type doesn't support this operation")]
+    InvalidCommunityChannelType,
+    #[error("Community channel category doesn't exist")]
+    CommunityChannelCategoryDoesntExist,
+    #[error("Channel order must contain every channel in the community exactly once")]
+    InvalidCommunityChannelOrder,
     #[error("Community role doesn't exist")]
     CommunityRoleDoesntExist,
     #[error("Community channel limit reached")]
     CommunityChannelLimitReached,
+    #[error("Community channel category limit reached")]
+    CommunityChannelCategoryLimitReached,
     #[error("Cant revoke inexistent permission")]
     CantRevokeInexistentPermission,
     #[error("Permission already granted")]
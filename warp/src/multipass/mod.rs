@@ -14,6 +14,7 @@ use identity::Identity;
 use crate::crypto::DID;
 use crate::error::Error;
 use crate::multipass::identity::{FriendRequest, Identifier, IdentityUpdate};
+use crate::raygun::Message;
 use crate::tesseract::Tesseract;
 use crate::{Extension, SingleHandle};
 
@@ -131,6 +132,11 @@ pub trait Friends: Sync + Send {
         Err(Error::Unimplemented)
     }
 
+    /// Send friend request to the identity shared in a contact card message
+    async fn send_request_from_card(&mut self, _: &Message) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
     /// Accept friend request from public key
     async fn accept_request(&mut self, _: &DID) -> Result<(), Error> {
         Err(Error::Unimplemented)
@@ -241,6 +247,11 @@ pub trait IdentityInformation: Send + Sync {
     async fn identity_platform(&self, _: &DID) -> Result<Platform, Error> {
         Err(Error::Unimplemented)
     }
+
+    /// Returns true if the identity is marked as an automated account
+    async fn identity_is_bot(&self, _: &DID) -> Result<bool, Error> {
+        Err(Error::Unimplemented)
+    }
 }
 
 pub struct GetIdentity {
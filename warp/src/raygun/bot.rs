@@ -0,0 +1,408 @@
+//! Command router for automated identities.
+//!
+//! A [`BotCommandRegistry`] holds a set of [`BotCommand`] that are matched against messages
+//! in the form of `/command args`. The output of the command is sent as a reply to the message
+//! that invoked it.
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt};
+use indexmap::IndexMap;
+use uuid::Uuid;
+
+use crate::crypto::DID;
+use crate::error::Error;
+
+use super::community::{CommunityPermission, RayGunCommunity};
+use super::{GroupPermission, ImplGroupPermissions, Message, MessageEventKind, RayGun};
+
+/// Prefix that a message must start with to be considered a command
+pub const COMMAND_PREFIX: char = '/';
+
+type CommandHandler =
+    Arc<dyn Fn(CommandInvocation) -> BoxFuture<'static, Result<Vec<String>, Error>> + Send + Sync>;
+
+/// Permission required by the sender in order to invoke a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandPermission {
+    /// Checked against the conversation permissions. Commands with this permission cannot be
+    /// used in direct conversations or communities
+    Group(GroupPermission),
+    /// Checked against the community permissions. Commands with this permission cannot be
+    /// used in conversations
+    Community(CommunityPermission),
+}
+
+impl From<GroupPermission> for CommandPermission {
+    fn from(permission: GroupPermission) -> Self {
+        CommandPermission::Group(permission)
+    }
+}
+
+impl From<CommunityPermission> for CommandPermission {
+    fn from(permission: CommunityPermission) -> Self {
+        CommandPermission::Community(permission)
+    }
+}
+
+/// Location where a command was invoked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    Conversation {
+        conversation_id: Uuid,
+    },
+    Community {
+        community_id: Uuid,
+        channel_id: Uuid,
+    },
+}
+
+/// Details of a command passed to its handler
+#[derive(Debug, Clone)]
+pub struct CommandInvocation {
+    source: CommandSource,
+    command: String,
+    args: Vec<String>,
+    message: Message,
+}
+
+impl CommandInvocation {
+    pub fn source(&self) -> CommandSource {
+        self.source
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn sender(&self) -> &DID {
+        self.message.sender()
+    }
+
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+}
+
+#[derive(Clone)]
+pub struct BotCommand {
+    name: String,
+    description: String,
+    permission: Option<CommandPermission>,
+    handler: CommandHandler,
+}
+
+impl std::fmt::Debug for BotCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BotCommand")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("permission", &self.permission)
+            .finish()
+    }
+}
+
+impl BotCommand {
+    /// Create a command with a handler that returns the lines to reply with.
+    /// If the handler returns no lines, no reply is sent.
+    pub fn new<F, Fut>(name: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(CommandInvocation) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<String>, Error>> + Send + 'static,
+    {
+        Self {
+            name: name.into().to_lowercase(),
+            description: String::new(),
+            permission: None,
+            handler: Arc::new(move |invocation| handler(invocation).boxed()),
+        }
+    }
+
+    pub fn set_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn set_permission(mut self, permission: impl Into<CommandPermission>) -> Self {
+        self.permission = Some(permission.into());
+        self
+    }
+}
+
+impl BotCommand {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn permission(&self) -> Option<CommandPermission> {
+        self.permission
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct BotCommandRegistry {
+    commands: IndexMap<String, BotCommand>,
+}
+
+impl BotCommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a command, returning the previous command registered under the same name
+    pub fn register(&mut self, command: BotCommand) -> Option<BotCommand> {
+        self.commands.insert(command.name.clone(), command)
+    }
+
+    pub fn unregister(&mut self, name: &str) -> Option<BotCommand> {
+        self.commands.shift_remove(&name.to_lowercase())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&BotCommand> {
+        self.commands.get(&name.to_lowercase())
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &BotCommand> {
+        self.commands.values()
+    }
+
+    /// Parse the lines of a message into a command name and its arguments.
+    /// Returns `None` if the message is not a command
+    pub fn parse(lines: &[String]) -> Option<(String, Vec<String>)> {
+        let text = lines.join("\n");
+        let text = text.trim_start().strip_prefix(COMMAND_PREFIX)?;
+        let mut parts = text.split_whitespace();
+        let command = parts.next()?.to_lowercase();
+        let args = parts.map(ToString::to_string).collect();
+        Some((command, args))
+    }
+}
+
+impl BotCommandRegistry {
+    /// Handle a message from a conversation, replying with the output of the command.
+    /// Returns the id of the reply, if one was sent.
+    ///
+    /// Note: Messages that are not commands or are for unknown commands are ignored.
+    pub async fn handle_message<R: RayGun + ?Sized>(
+        &self,
+        raygun: &mut R,
+        message: Message,
+    ) -> Result<Option<Uuid>, Error> {
+        let conversation_id = message.conversation_id();
+        let Some((command, invocation)) =
+            self.invocation(CommandSource::Conversation { conversation_id }, &message)
+        else {
+            return Ok(None);
+        };
+
+        if let Some(permission) = command.permission {
+            let CommandPermission::Group(permission) = permission else {
+                return Err(Error::Unauthorized);
+            };
+            let conversation = raygun.get_conversation(conversation_id).await?;
+            let sender = message.sender();
+            let authorized = conversation.creator() == Some(sender)
+                || conversation
+                    .permissions()
+                    .has_permission(sender, permission);
+            if !authorized {
+                return Err(Error::Unauthorized);
+            }
+        }
+
+        let lines = (command.handler)(invocation).await?;
+        if lines.is_empty() {
+            return Ok(None);
+        }
+
+        raygun
+            .reply(conversation_id, message.id(), lines)
+            .await
+            .map(Some)
+    }
+
+    /// Handle a message from a community channel, replying with the output of the command.
+    /// Returns the id of the reply, if one was sent.
+    ///
+    /// Note: Messages that are not commands or are for unknown commands are ignored.
+    pub async fn handle_community_message<R: RayGunCommunity + ?Sized>(
+        &self,
+        raygun: &mut R,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message: Message,
+    ) -> Result<Option<Uuid>, Error> {
+        let Some((command, invocation)) = self.invocation(
+            CommandSource::Community {
+                community_id,
+                channel_id,
+            },
+            &message,
+        ) else {
+            return Ok(None);
+        };
+
+        if let Some(permission) = command.permission {
+            let CommandPermission::Community(permission) = permission else {
+                return Err(Error::Unauthorized);
+            };
+            if !has_community_permission(raygun, community_id, message.sender(), permission).await?
+            {
+                return Err(Error::Unauthorized);
+            }
+        }
+
+        let lines = (command.handler)(invocation).await?;
+        if lines.is_empty() {
+            return Ok(None);
+        }
+
+        raygun
+            .reply_to_community_channel_message(community_id, channel_id, message.id(), lines)
+            .await
+            .map(Some)
+    }
+
+    /// Process commands received in a conversation until the stream ends
+    pub async fn run<R: RayGun + ?Sized>(
+        &self,
+        raygun: &mut R,
+        conversation_id: Uuid,
+    ) -> Result<(), Error> {
+        let mut stream = raygun.get_conversation_stream(conversation_id).await?;
+        while let Some(event) = stream.next().await {
+            let MessageEventKind::MessageReceived {
+                conversation_id,
+                message_id,
+            } = event
+            else {
+                continue;
+            };
+
+            let message = match raygun.get_message(conversation_id, message_id).await {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::warn!(%conversation_id, %message_id, error = %e, "unable to get message");
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.handle_message(raygun, message).await {
+                tracing::warn!(%conversation_id, %message_id, error = %e, "unable to handle command");
+            }
+        }
+        Ok(())
+    }
+
+    /// Process commands received in a community until the stream ends
+    pub async fn run_community<R: RayGunCommunity + ?Sized>(
+        &self,
+        raygun: &mut R,
+        community_id: Uuid,
+    ) -> Result<(), Error> {
+        let mut stream = raygun.get_community_stream(community_id).await?;
+        while let Some(event) = stream.next().await {
+            let MessageEventKind::CommunityMessageReceived {
+                community_id,
+                channel_id,
+                message_id,
+            } = event
+            else {
+                continue;
+            };
+
+            let message = match raygun
+                .get_community_channel_message(community_id, channel_id, message_id)
+                .await
+            {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::warn!(%community_id, %channel_id, %message_id, error = %e, "unable to get message");
+                    continue;
+                }
+            };
+
+            if let Err(e) = self
+                .handle_community_message(raygun, community_id, channel_id, message)
+                .await
+            {
+                tracing::warn!(%community_id, %channel_id, %message_id, error = %e, "unable to handle command");
+            }
+        }
+        Ok(())
+    }
+
+    fn invocation(
+        &self,
+        source: CommandSource,
+        message: &Message,
+    ) -> Option<(&BotCommand, CommandInvocation)> {
+        let (name, args) = Self::parse(message.lines())?;
+        let command = self.commands.get(&name)?;
+        let invocation = CommandInvocation {
+            source,
+            command: name,
+            args,
+            message: message.clone(),
+        };
+        Some((command, invocation))
+    }
+}
+
+async fn has_community_permission<R: RayGunCommunity + ?Sized>(
+    raygun: &mut R,
+    community_id: Uuid,
+    user: &DID,
+    permission: CommunityPermission,
+) -> Result<bool, Error> {
+    let community = raygun.get_community(community_id).await?;
+
+    if community.creator() == user {
+        return Ok(true);
+    }
+
+    if !community.members().contains(user) {
+        return Ok(false);
+    }
+
+    let Some(roles) = community.permissions().get(&permission) else {
+        return Ok(true);
+    };
+
+    for role_id in roles {
+        let role = raygun.get_community_role(community_id, *role_id).await?;
+        if role.members().contains(user) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::BotCommandRegistry;
+
+    #[test]
+    fn parse_command() {
+        assert_eq!(
+            BotCommandRegistry::parse(&["/Echo hello  world".into()]),
+            Some(("echo".into(), vec!["hello".into(), "world".into()]))
+        );
+        assert_eq!(
+            BotCommandRegistry::parse(&["/ping".into()]),
+            Some(("ping".into(), vec![]))
+        );
+        assert_eq!(BotCommandRegistry::parse(&["hello /ping".into()]), None);
+        assert_eq!(BotCommandRegistry::parse(&["/".into()]), None);
+    }
+}
@@ -1,4 +1,6 @@
+use std::ops::Range;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
@@ -13,7 +15,8 @@ use crate::raygun::{Error, Location};
 
 use super::{
     AttachmentEventStream, ConversationImage, Message, MessageEvent, MessageEventStream,
-    MessageOptions, MessageReference, MessageStatus, Messages, PinState, ReactionState,
+    MessageOptions, MessageReference, MessageStatus, Messages, PinState, Poll, PollResults,
+    QuarantinedMessage, ReactionState,
 };
 
 pub type RoleId = Uuid;
@@ -26,6 +29,10 @@ pub struct CommunityRole {
     id: RoleId,
     name: String,
     members: IndexSet<DID>,
+    position: u32,
+    color: Option<u32>,
+    hoisted: bool,
+    mentionable: bool,
 }
 impl CommunityRole {
     pub fn id(&self) -> RoleId {
@@ -37,6 +44,22 @@ impl CommunityRole {
     pub fn members(&self) -> &IndexSet<DID> {
         &self.members
     }
+    /// Position of the role within the hierarchy. Roles with a lower position rank higher, and
+    /// members can only manage roles and members ranked below their highest role
+    pub fn position(&self) -> u32 {
+        self.position
+    }
+    /// Display color of the role as a RGB value (eg `0xff0000`)
+    pub fn color(&self) -> Option<u32> {
+        self.color
+    }
+    /// Whether members with the role should be displayed separately from other members
+    pub fn hoisted(&self) -> bool {
+        self.hoisted
+    }
+    pub fn mentionable(&self) -> bool {
+        self.mentionable
+    }
 }
 impl CommunityRole {
     pub fn set_id(&mut self, id: RoleId) {
@@ -48,14 +71,29 @@ impl CommunityRole {
     pub fn set_members(&mut self, members: IndexSet<DID>) {
         self.members = members;
     }
+    pub fn set_position(&mut self, position: u32) {
+        self.position = position;
+    }
+    pub fn set_color(&mut self, color: Option<u32>) {
+        self.color = color;
+    }
+    pub fn set_hoisted(&mut self, hoisted: bool) {
+        self.hoisted = hoisted;
+    }
+    pub fn set_mentionable(&mut self, mentionable: bool) {
+        self.mentionable = mentionable;
+    }
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CommunityInvite {
     id: Uuid,
     target_user: Option<DID>,
+    creator: Option<DID>,
     created: DateTime<Utc>,
     expiry: Option<DateTime<Utc>>,
+    max_uses: Option<u32>,
+    uses: u32,
 }
 impl CommunityInvite {
     pub fn id(&self) -> Uuid {
@@ -64,12 +102,24 @@ impl CommunityInvite {
     pub fn target_user(&self) -> Option<&DID> {
         self.target_user.as_ref()
     }
+    /// Member that created the invite. This is `None` for invites created before the creator was recorded
+    pub fn creator(&self) -> Option<&DID> {
+        self.creator.as_ref()
+    }
     pub fn created(&self) -> DateTime<Utc> {
         self.created
     }
     pub fn expiry(&self) -> Option<DateTime<Utc>> {
         self.expiry
     }
+    /// Number of times the invite can be used before it is deleted
+    pub fn max_uses(&self) -> Option<u32> {
+        self.max_uses
+    }
+    /// Number of members that joined the community using the invite
+    pub fn uses(&self) -> u32 {
+        self.uses
+    }
 }
 impl CommunityInvite {
     pub fn set_id(&mut self, id: Uuid) {
@@ -78,6 +128,58 @@ impl CommunityInvite {
     pub fn set_target_user(&mut self, target_user: Option<DID>) {
         self.target_user = target_user;
     }
+    pub fn set_creator(&mut self, creator: Option<DID>) {
+        self.creator = creator;
+    }
+    pub fn set_created(&mut self, created: DateTime<Utc>) {
+        self.created = created;
+    }
+    pub fn set_expiry(&mut self, expiry: Option<DateTime<Utc>>) {
+        self.expiry = expiry;
+    }
+    pub fn set_max_uses(&mut self, max_uses: Option<u32>) {
+        self.max_uses = max_uses;
+    }
+    pub fn set_uses(&mut self, uses: u32) {
+        self.uses = uses;
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityBan {
+    user: DID,
+    reason: Option<String>,
+    issuer: DID,
+    created: DateTime<Utc>,
+    expiry: Option<DateTime<Utc>>,
+}
+impl CommunityBan {
+    pub fn user(&self) -> &DID {
+        &self.user
+    }
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+    pub fn issuer(&self) -> &DID {
+        &self.issuer
+    }
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        self.expiry
+    }
+}
+impl CommunityBan {
+    pub fn set_user(&mut self, user: DID) {
+        self.user = user;
+    }
+    pub fn set_reason(&mut self, reason: Option<String>) {
+        self.reason = reason;
+    }
+    pub fn set_issuer(&mut self, issuer: DID) {
+        self.issuer = issuer;
+    }
     pub fn set_created(&mut self, created: DateTime<Utc>) {
         self.created = created;
     }
@@ -86,6 +188,239 @@ impl CommunityInvite {
     }
 }
 
+/// Change that was made to a community, as recorded in its audit log
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum CommunityAuditAction {
+    LeaveCommunity,
+    CreateCommunityInvite {
+        invite_id: Uuid,
+    },
+    DeleteCommunityInvite {
+        invite_id: Uuid,
+    },
+    EditCommunityInvite {
+        invite_id: Uuid,
+    },
+    CreateCommunityRole {
+        role_id: RoleId,
+    },
+    DeleteCommunityRole {
+        role_id: RoleId,
+    },
+    EditCommunityRole {
+        role_id: RoleId,
+    },
+    GrantCommunityRole {
+        role_id: RoleId,
+        user: DID,
+    },
+    RevokeCommunityRole {
+        role_id: RoleId,
+        user: DID,
+    },
+    CreateCommunityChannel {
+        channel_id: Uuid,
+    },
+    DeleteCommunityChannel {
+        channel_id: Uuid,
+    },
+    EditCommunityName {
+        name: String,
+    },
+    EditCommunityDescription {
+        description: Option<String>,
+    },
+    EditIcon,
+    EditBanner,
+    SetCommunityListing {
+        invite_id: Option<Uuid>,
+    },
+    GrantCommunityPermission {
+        permission: CommunityPermission,
+        role_id: RoleId,
+    },
+    RevokeCommunityPermission {
+        permission: CommunityPermission,
+        role_id: RoleId,
+    },
+    GrantCommunityPermissionForAll {
+        permission: CommunityPermission,
+    },
+    RevokeCommunityPermissionForAll {
+        permission: CommunityPermission,
+    },
+    RemoveCommunityMember {
+        member: DID,
+    },
+    BanCommunityMember {
+        member: DID,
+    },
+    UnbanCommunityMember {
+        member: DID,
+    },
+    TimeoutCommunityMember {
+        member: DID,
+        until: DateTime<Utc>,
+    },
+    TransferCommunityOwnership {
+        new_owner: Option<DID>,
+    },
+    AcceptCommunityOwnership {
+        previous_owner: DID,
+    },
+    EditCommunityChannelName {
+        channel_id: Uuid,
+        name: String,
+    },
+    EditCommunityChannelDescription {
+        channel_id: Uuid,
+        description: Option<String>,
+    },
+    EditCommunityChannelSlowMode {
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    },
+    FollowCommunityChannel {
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    },
+    UnfollowCommunityChannel {
+        channel_id: Uuid,
+    },
+    GrantCommunityChannelPermission {
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    RevokeCommunityChannelPermission {
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    GrantCommunityChannelPermissionForAll {
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    RevokeCommunityChannelPermissionForAll {
+        channel_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    CreateCommunityChannelCategory {
+        category_id: Uuid,
+    },
+    DeleteCommunityChannelCategory {
+        category_id: Uuid,
+    },
+    GrantCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    RevokeCommunityChannelCategoryPermission {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    GrantCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    RevokeCommunityChannelCategoryPermissionForAll {
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    ReorderCommunityChannels,
+    MoveCommunityChannelToCategory {
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityAuditLogEntry {
+    id: Uuid,
+    actor: DID,
+    date: DateTime<Utc>,
+    action: CommunityAuditAction,
+}
+impl CommunityAuditLogEntry {
+    pub fn new(id: Uuid, actor: DID, date: DateTime<Utc>, action: CommunityAuditAction) -> Self {
+        Self {
+            id,
+            actor,
+            date,
+            action,
+        }
+    }
+}
+impl CommunityAuditLogEntry {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    /// User that made the change
+    pub fn actor(&self) -> &DID {
+        &self.actor
+    }
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+    pub fn action(&self) -> &CommunityAuditAction {
+        &self.action
+    }
+}
+
+/// Options for reading the audit log of a community
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CommunityAuditLogOptions {
+    actor: Option<DID>,
+    date_range: Option<Range<DateTime<Utc>>>,
+    skip: usize,
+    limit: Option<usize>,
+}
+
+impl CommunityAuditLogOptions {
+    /// Only include changes made by the given user
+    pub fn set_actor(mut self, actor: DID) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    pub fn set_date_range(mut self, range: Range<DateTime<Utc>>) -> Self {
+        self.date_range = Some(range);
+        self
+    }
+
+    pub fn set_skip(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    pub fn set_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl CommunityAuditLogOptions {
+    pub fn actor(&self) -> Option<&DID> {
+        self.actor.as_ref()
+    }
+
+    pub fn date_range(&self) -> Option<Range<DateTime<Utc>>> {
+        self.date_range.clone()
+    }
+
+    pub fn skip(&self) -> usize {
+        self.skip
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
 #[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct Community {
     id: Uuid,
@@ -96,9 +431,13 @@ pub struct Community {
     modified: DateTime<Utc>,
     members: IndexSet<DID>,
     channels: IndexSet<Uuid>,
+    categories: IndexSet<Uuid>,
     roles: IndexSet<RoleId>,
     permissions: CommunityPermissions,
     invites: IndexSet<Uuid>,
+    invite_joins: IndexMap<DID, Uuid>,
+    public_invite: Option<Uuid>,
+    pending_owner: Option<DID>,
 }
 impl Community {
     pub fn id(&self) -> Uuid {
@@ -122,9 +461,13 @@ impl Community {
     pub fn members(&self) -> &IndexSet<DID> {
         &self.members
     }
+    /// Channels within the community, in the order they should be displayed
     pub fn channels(&self) -> &IndexSet<Uuid> {
         &self.channels
     }
+    pub fn categories(&self) -> &IndexSet<Uuid> {
+        &self.categories
+    }
     pub fn roles(&self) -> &IndexSet<RoleId> {
         &self.roles
     }
@@ -134,6 +477,19 @@ impl Community {
     pub fn invites(&self) -> &IndexSet<Uuid> {
         &self.invites
     }
+    /// Invite used by each member to join the community
+    pub fn invite_joins(&self) -> &IndexMap<DID, Uuid> {
+        &self.invite_joins
+    }
+    /// Invite advertised in the public community directory. The community is only listed
+    /// when this is set
+    pub fn public_invite(&self) -> Option<Uuid> {
+        self.public_invite
+    }
+    /// Member that ownership of the community has been offered to, if any
+    pub fn pending_owner(&self) -> Option<&DID> {
+        self.pending_owner.as_ref()
+    }
 }
 impl Community {
     pub fn set_id(&mut self, id: Uuid) {
@@ -160,6 +516,9 @@ impl Community {
     pub fn set_channels(&mut self, channels: IndexSet<Uuid>) {
         self.channels = channels;
     }
+    pub fn set_categories(&mut self, categories: IndexSet<Uuid>) {
+        self.categories = categories;
+    }
     pub fn set_roles(&mut self, roles: IndexSet<RoleId>) {
         self.roles = roles;
     }
@@ -169,6 +528,82 @@ impl Community {
     pub fn set_invites(&mut self, invites: IndexSet<Uuid>) {
         self.invites = invites;
     }
+    pub fn set_invite_joins(&mut self, invite_joins: IndexMap<DID, Uuid>) {
+        self.invite_joins = invite_joins;
+    }
+    pub fn set_public_invite(&mut self, public_invite: Option<Uuid>) {
+        self.public_invite = public_invite;
+    }
+    pub fn set_pending_owner(&mut self, pending_owner: Option<DID>) {
+        self.pending_owner = pending_owner;
+    }
+}
+
+/// Entry of a community in the public directory
+#[derive(Default, Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CommunityListing {
+    id: Uuid,
+    owner: DID,
+    name: String,
+    description: Option<String>,
+    icon: Option<String>,
+    member_count: usize,
+    invite_id: Uuid,
+    modified: DateTime<Utc>,
+}
+impl CommunityListing {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    pub fn owner(&self) -> &DID {
+        &self.owner
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+    /// CID of the community icon
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+    pub fn member_count(&self) -> usize {
+        self.member_count
+    }
+    /// Invite used to join the community
+    pub fn invite_id(&self) -> Uuid {
+        self.invite_id
+    }
+    pub fn modified(&self) -> DateTime<Utc> {
+        self.modified
+    }
+}
+impl CommunityListing {
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+    pub fn set_owner(&mut self, owner: DID) {
+        self.owner = owner;
+    }
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+    pub fn set_icon(&mut self, icon: Option<String>) {
+        self.icon = icon;
+    }
+    pub fn set_member_count(&mut self, member_count: usize) {
+        self.member_count = member_count;
+    }
+    pub fn set_invite_id(&mut self, invite_id: Uuid) {
+        self.invite_id = invite_id;
+    }
+    pub fn set_modified(&mut self, modified: DateTime<Utc>) {
+        self.modified = modified;
+    }
 }
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -180,6 +615,9 @@ pub struct CommunityChannel {
     modified: DateTime<Utc>,
     channel_type: CommunityChannelType,
     permissions: CommunityChannelPermissions,
+    category: Option<Uuid>,
+    slow_mode: Option<Duration>,
+    following: Option<CommunityChannelFollow>,
 }
 
 impl CommunityChannel {
@@ -204,6 +642,17 @@ impl CommunityChannel {
     pub fn permissions(&self) -> &CommunityChannelPermissions {
         &self.permissions
     }
+    pub fn category(&self) -> Option<Uuid> {
+        self.category
+    }
+    /// Minimum time members have to wait between sending messages in the channel
+    pub fn slow_mode(&self) -> Option<Duration> {
+        self.slow_mode
+    }
+    /// Announcement channel that copies of messages are cross-posted from
+    pub fn following(&self) -> Option<&CommunityChannelFollow> {
+        self.following.as_ref()
+    }
 }
 impl CommunityChannel {
     pub fn set_id(&mut self, id: Uuid) {
@@ -227,6 +676,100 @@ impl CommunityChannel {
     pub fn set_permissions(&mut self, permissions: CommunityChannelPermissions) {
         self.permissions = permissions;
     }
+    pub fn set_category(&mut self, category: Option<Uuid>) {
+        self.category = category;
+    }
+    pub fn set_slow_mode(&mut self, slow_mode: Option<Duration>) {
+        self.slow_mode = slow_mode;
+    }
+    pub fn set_following(&mut self, following: Option<CommunityChannelFollow>) {
+        self.following = following;
+    }
+}
+
+/// Announcement channel in a community that a channel follows. Copies of its messages are
+/// cross-posted into the following channel by the member who followed it
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityChannelFollow {
+    community_id: Uuid,
+    channel_id: Uuid,
+    follower: DID,
+}
+impl CommunityChannelFollow {
+    pub fn community_id(&self) -> Uuid {
+        self.community_id
+    }
+    pub fn channel_id(&self) -> Uuid {
+        self.channel_id
+    }
+    pub fn follower(&self) -> &DID {
+        &self.follower
+    }
+}
+impl CommunityChannelFollow {
+    pub fn set_community_id(&mut self, community_id: Uuid) {
+        self.community_id = community_id;
+    }
+    pub fn set_channel_id(&mut self, channel_id: Uuid) {
+        self.channel_id = channel_id;
+    }
+    pub fn set_follower(&mut self, follower: DID) {
+        self.follower = follower;
+    }
+}
+
+/// Named group of channels. Permissions set on a category apply to every channel within it,
+/// unless the channel sets the same permission itself
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommunityChannelCategory {
+    id: Uuid,
+    name: String,
+    created: DateTime<Utc>,
+    modified: DateTime<Utc>,
+    permissions: CommunityChannelPermissions,
+    channels: IndexSet<Uuid>,
+}
+
+impl CommunityChannelCategory {
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn created(&self) -> DateTime<Utc> {
+        self.created
+    }
+    pub fn modified(&self) -> DateTime<Utc> {
+        self.modified
+    }
+    pub fn permissions(&self) -> &CommunityChannelPermissions {
+        &self.permissions
+    }
+    /// Channels within the category, in the order they should be displayed
+    pub fn channels(&self) -> &IndexSet<Uuid> {
+        &self.channels
+    }
+}
+impl CommunityChannelCategory {
+    pub fn set_id(&mut self, id: Uuid) {
+        self.id = id;
+    }
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+    pub fn set_created(&mut self, created: DateTime<Utc>) {
+        self.created = created;
+    }
+    pub fn set_modified(&mut self, modified: DateTime<Utc>) {
+        self.modified = modified;
+    }
+    pub fn set_permissions(&mut self, permissions: CommunityChannelPermissions) {
+        self.permissions = permissions;
+    }
+    pub fn set_channels(&mut self, channels: IndexSet<Uuid>) {
+        self.channels = channels;
+    }
 }
 
 #[derive(Default, Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -235,6 +778,9 @@ pub enum CommunityChannelType {
     #[default]
     Standard,
     VoiceEnabled,
+    /// Only members with [`CommunityPermission::PostAnnouncements`] can send messages, while
+    /// everyone able to view the channel can react to them
+    Announcement,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -264,9 +810,16 @@ pub enum CommunityPermission {
     DeleteChannels,
 
     RemoveMembers,
+    BanMembers,
+    TimeoutMembers,
+
+    ViewAuditLog,
 
     DeleteMessages,
     PinMessages,
+    PostAnnouncements,
+
+    BypassSlowMode,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -302,6 +855,17 @@ pub trait RayGunCommunity: Sync + Send {
     async fn list_communities_invited_to(&self) -> Result<Vec<(Uuid, CommunityInvite)>, Error> {
         Err(Error::Unimplemented)
     }
+    /// Search the public community directory on shuttle nodes. Communities are matched by
+    /// name or description, and all listings are returned when no query is provided. Results are
+    /// ordered by the most recently modified listing first
+    async fn search_public_communities(
+        &self,
+        _query: Option<&str>,
+        _skip: usize,
+        _limit: usize,
+    ) -> Result<Vec<CommunityListing>, Error> {
+        Err(Error::Unimplemented)
+    }
     async fn leave_community(&mut self, _community_id: Uuid) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
@@ -326,6 +890,16 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+    /// Publish the community to the public directory on shuttle nodes, advertising the given
+    /// invite so others are able to join. Passing `None` removes the community from the directory.
+    /// Only the owner is able to list the community
+    async fn set_community_listing(
+        &mut self,
+        _community_id: Uuid,
+        _invite_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
 
     async fn create_community_invite(
         &mut self,
@@ -349,9 +923,17 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<CommunityInvite, Error> {
         Err(Error::Unimplemented)
     }
+    /// List the invites of the community along with how many times each has been used
+    async fn list_community_invites(
+        &self,
+        _community_id: Uuid,
+    ) -> Result<Vec<CommunityInvite>, Error> {
+        Err(Error::Unimplemented)
+    }
     async fn request_join_community(&mut self, _community_id: Uuid) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+    /// Edit the invite. Setting `max_uses` to a limit the invite has already reached deletes the invite
     async fn edit_community_invite(
         &mut self,
         _community_id: Uuid,
@@ -390,6 +972,40 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+    /// Move a role within the hierarchy. The role cannot be placed at or above the highest role
+    /// of the member making the change
+    async fn edit_community_role_position(
+        &mut self,
+        _community_id: Uuid,
+        _role_id: RoleId,
+        _position: u32,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn edit_community_role_color(
+        &mut self,
+        _community_id: Uuid,
+        _role_id: RoleId,
+        _color: Option<u32>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn edit_community_role_hoisted(
+        &mut self,
+        _community_id: Uuid,
+        _role_id: RoleId,
+        _hoisted: bool,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn edit_community_role_mentionable(
+        &mut self,
+        _community_id: Uuid,
+        _role_id: RoleId,
+        _mentionable: bool,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
     async fn grant_community_role(
         &mut self,
         _community_id: Uuid,
@@ -429,6 +1045,47 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<CommunityChannel, Error> {
         Err(Error::Unimplemented)
     }
+    /// Set the order in which channels are displayed. The list must contain every channel within
+    /// the community exactly once
+    async fn reorder_community_channels(
+        &mut self,
+        _community_id: Uuid,
+        _channels: Vec<Uuid>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    async fn create_community_channel_category(
+        &mut self,
+        _community_id: Uuid,
+        _name: &str,
+    ) -> Result<CommunityChannelCategory, Error> {
+        Err(Error::Unimplemented)
+    }
+    /// Delete a category. Channels within it are kept and no longer belong to a category
+    async fn delete_community_channel_category(
+        &mut self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn get_community_channel_category(
+        &self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+    ) -> Result<CommunityChannelCategory, Error> {
+        Err(Error::Unimplemented)
+    }
+    /// Move a channel into a category, or out of its current one when `category_id` is `None`
+    async fn move_channel_to_category(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _category_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
 
     async fn edit_community_name(&mut self, _community_id: Uuid, _name: &str) -> Result<(), Error> {
         Err(Error::Unimplemented)
@@ -477,6 +1134,64 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+    /// Remove a member from the community and prevent them from rejoining until the ban is
+    /// lifted or expires
+    async fn ban_community_member(
+        &mut self,
+        _community_id: Uuid,
+        _member: DID,
+        _reason: Option<String>,
+        _expiry: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn unban_community_member(
+        &mut self,
+        _community_id: Uuid,
+        _member: DID,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    /// List the bans that are currently in effect
+    async fn list_community_bans(&self, _community_id: Uuid) -> Result<Vec<CommunityBan>, Error> {
+        Err(Error::Unimplemented)
+    }
+    /// Prevent a member from sending, editing or reacting to messages until the given time.
+    /// Passing a time that has already elapsed lifts an existing timeout
+    async fn timeout_community_member(
+        &mut self,
+        _community_id: Uuid,
+        _member: DID,
+        _until: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Offer ownership of the community to another member. The transfer only takes effect once
+    /// the new owner accepts it with [`RayGunCommunity::accept_community_ownership`], which
+    /// counter-signs the community with their key. Offering ownership to yourself cancels a
+    /// pending transfer
+    async fn transfer_community_ownership(
+        &mut self,
+        _community_id: Uuid,
+        _new_owner: DID,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    /// Accept ownership of the community that was offered to us. The previous owner remains
+    /// a member of the community and keeps their roles
+    async fn accept_community_ownership(&mut self, _community_id: Uuid) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Retrieve the changes made to the community, starting with the most recent
+    async fn get_community_audit_log(
+        &self,
+        _community_id: Uuid,
+        _options: CommunityAuditLogOptions,
+    ) -> Result<Vec<CommunityAuditLogEntry>, Error> {
+        Err(Error::Unimplemented)
+    }
 
     async fn edit_community_channel_name(
         &mut self,
@@ -494,6 +1209,35 @@ pub trait RayGunCommunity: Sync + Send {
     ) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+    /// Set the minimum time members have to wait between sending messages in the channel.
+    /// Members with [`CommunityPermission::BypassSlowMode`] are exempt
+    async fn edit_community_channel_slow_mode(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _slow_mode: Option<Duration>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    /// Follow an announcement channel, which may be in another community, so that copies of its
+    /// messages are cross-posted into the channel. Copies are posted by us, so we must be able to
+    /// view the announcement channel and send messages in the following channel
+    async fn follow_community_channel(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _source_community_id: Uuid,
+        _source_channel_id: Uuid,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn unfollow_community_channel(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
     async fn grant_community_channel_permission(
         &mut self,
         _community_id: Uuid,
@@ -529,6 +1273,41 @@ pub trait RayGunCommunity: Sync + Send {
         Err(Error::Unimplemented)
     }
 
+    async fn grant_community_channel_category_permission(
+        &mut self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+        _permission: CommunityChannelPermission,
+        _role_id: RoleId,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn revoke_community_channel_category_permission(
+        &mut self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+        _permission: CommunityChannelPermission,
+        _role_id: RoleId,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn grant_community_channel_category_permission_for_all(
+        &mut self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+        _permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+    async fn revoke_community_channel_category_permission_for_all(
+        &mut self,
+        _community_id: Uuid,
+        _category_id: Uuid,
+        _permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
     /// Retrieve all messages from a conversation
     async fn get_community_channel_message(
         &self,
@@ -629,6 +1408,67 @@ pub trait RayGunCommunity: Sync + Send {
         Err(Error::Unimplemented)
     }
 
+    /// List messages within a channel that were held back by a message filter
+    async fn list_quarantined_community_channel_messages(
+        &self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Release a quarantined message, storing it as if it was accepted by the filter
+    async fn release_quarantined_community_channel_message(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _message_id: Uuid,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Send a poll to a channel
+    async fn send_community_channel_poll(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _poll: Poll,
+    ) -> Result<Uuid, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Vote on a poll within a channel. Voting again will replace the previous vote,
+    /// while an empty list of options will retract it
+    async fn vote_community_channel_poll(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _message_id: Uuid,
+        _options: Vec<usize>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Close a poll, preventing any further votes. Only the creator of the poll can close it
+    async fn close_community_channel_poll(
+        &mut self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _message_id: Uuid,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Results of a poll within a channel
+    async fn community_channel_poll_results(
+        &self,
+        _community_id: Uuid,
+        _channel_id: Uuid,
+        _message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        Err(Error::Unimplemented)
+    }
+
     /// Pin a message within a conversation
     async fn pin_community_channel_message(
         &mut self,
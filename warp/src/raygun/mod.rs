@@ -1,3 +1,4 @@
+pub mod bot;
 pub mod community;
 pub mod group;
 
@@ -5,12 +6,13 @@ use crate::constellation::file::{File, FileType};
 use crate::constellation::{ConstellationProgressStream, Progression};
 use crate::crypto::DID;
 use crate::error::Error;
+use crate::multipass::identity::Identity;
 use crate::raygun::community::RayGunCommunity;
 use crate::{Extension, SingleHandle};
 
 use community::{
-    CommunityChannel, CommunityChannelPermission, CommunityInvite, CommunityPermission,
-    CommunityRole, RoleId,
+    CommunityChannel, CommunityChannelCategory, CommunityChannelPermission, CommunityInvite,
+    CommunityPermission, CommunityRole, RoleId,
 };
 use derive_more::Display;
 use futures::stream::BoxStream;
@@ -23,6 +25,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashSet};
 use std::fmt::Debug;
 use std::path::PathBuf;
+use std::time::Duration;
 use uuid::Uuid;
 
 #[allow(unused_imports)]
@@ -190,6 +193,10 @@ pub enum MessageEventKind {
         community_id: Uuid,
         description: Option<String>,
     },
+    EditedCommunityListing {
+        community_id: Uuid,
+        invite_id: Option<Uuid>,
+    },
     EditedCommunityIcon {
         community_id: Uuid,
     },
@@ -218,6 +225,28 @@ pub enum MessageEventKind {
         community_id: Uuid,
         member: DID,
     },
+    BannedCommunityMember {
+        community_id: Uuid,
+        member: DID,
+    },
+    UnbannedCommunityMember {
+        community_id: Uuid,
+        member: DID,
+    },
+    CommunityMemberTimedOut {
+        community_id: Uuid,
+        member: DID,
+        until: DateTime<Utc>,
+    },
+    OfferedCommunityOwnership {
+        community_id: Uuid,
+        new_owner: Option<DID>,
+    },
+    TransferredCommunityOwnership {
+        community_id: Uuid,
+        previous_owner: DID,
+        new_owner: DID,
+    },
     EditedCommunityChannelName {
         community_id: Uuid,
         channel_id: Uuid,
@@ -228,6 +257,21 @@ pub enum MessageEventKind {
         channel_id: Uuid,
         description: Option<String>,
     },
+    EditedCommunityChannelSlowMode {
+        community_id: Uuid,
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    },
+    FollowedCommunityChannel {
+        community_id: Uuid,
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    },
+    UnfollowedCommunityChannel {
+        community_id: Uuid,
+        channel_id: Uuid,
+    },
     GrantedCommunityChannelPermission {
         community_id: Uuid,
         channel_id: Uuid,
@@ -250,6 +294,45 @@ pub enum MessageEventKind {
         channel_id: Uuid,
         permission: CommunityChannelPermission,
     },
+    CommunityChannelsReordered {
+        community_id: Uuid,
+        channels: IndexSet<Uuid>,
+    },
+    CreatedCommunityChannelCategory {
+        community_id: Uuid,
+        category: CommunityChannelCategory,
+    },
+    DeletedCommunityChannelCategory {
+        community_id: Uuid,
+        category_id: Uuid,
+    },
+    MovedCommunityChannelToCategory {
+        community_id: Uuid,
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    },
+    GrantedCommunityChannelCategoryPermission {
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    RevokedCommunityChannelCategoryPermission {
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    },
+    GrantedCommunityChannelCategoryPermissionForAll {
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
+    RevokedCommunityChannelCategoryPermissionForAll {
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    },
     CommunityMessageSent {
         community_id: Uuid,
         channel_id: Uuid,
@@ -294,9 +377,25 @@ pub enum MessageEventKind {
         did_key: DID,
         reaction: String,
     },
+    PollUpdated {
+        conversation_id: Uuid,
+        message_id: Uuid,
+    },
+    CommunityPollUpdated {
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    },
+    DraftUpdated {
+        conversation_id: Uuid,
+    },
+    SyncProgress {
+        conversation_id: Uuid,
+        status: SyncStatus,
+    },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 #[repr(C)]
 pub enum MessageEvent {
@@ -426,6 +525,136 @@ impl MessageOptions {
     }
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MessageBuilder {
+    lines: Vec<String>,
+    replied: Option<Uuid>,
+    mentions: Vec<DID>,
+    metadata: IndexMap<String, String>,
+}
+
+impl MessageBuilder {
+    pub fn new(lines: Vec<String>) -> Self {
+        Self {
+            lines,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_lines(mut self, lines: Vec<String>) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    pub fn set_replied(mut self, message_id: Uuid) -> Self {
+        self.replied = Some(message_id);
+        self
+    }
+
+    pub fn set_mentions(mut self, mentions: Vec<DID>) -> Self {
+        self.mentions = mentions;
+        self
+    }
+
+    pub fn add_mention(mut self, did: DID) -> Self {
+        if !self.mentions.contains(&did) {
+            self.mentions.push(did);
+        }
+        self
+    }
+
+    pub fn set_metadata(mut self, metadata: IndexMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn add_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl MessageBuilder {
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn replied(&self) -> Option<Uuid> {
+        self.replied
+    }
+
+    pub fn mentions(&self) -> &[DID] {
+        &self.mentions
+    }
+
+    pub fn metadata(&self) -> &IndexMap<String, String> {
+        &self.metadata
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConversationQuery {
+    label: Option<String>,
+    participants: Vec<DID>,
+    name: Option<String>,
+    unread: bool,
+    sort_by_last_activity: bool,
+}
+
+impl ConversationQuery {
+    /// Only include conversations with the given label
+    pub fn set_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Only include conversations where all of the participants are recipients
+    pub fn set_participants(mut self, participants: Vec<DID>) -> Self {
+        self.participants = participants;
+        self
+    }
+
+    /// Only include conversations where the name contains the substring. Case insensitive
+    pub fn set_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Only include conversations that contains unread messages
+    pub fn set_unread(mut self) -> Self {
+        self.unread = true;
+        self
+    }
+
+    /// Sort conversations by their last activity, most recent first
+    pub fn set_sort_by_last_activity(mut self) -> Self {
+        self.sort_by_last_activity = true;
+        self
+    }
+}
+
+impl ConversationQuery {
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn participants(&self) -> &[DID] {
+        &self.participants
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn unread(&self) -> bool {
+        self.unread
+    }
+
+    pub fn sort_by_last_activity(&self) -> bool {
+        self.sort_by_last_activity
+    }
+}
+
 #[derive(Default, Debug, Hash, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Display)]
 #[serde(rename_all = "lowercase")]
 pub enum MessagesType {
@@ -883,6 +1112,59 @@ impl Conversation {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ConversationSummary {
+    conversation: Conversation,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_message: Option<Message>,
+    unread: bool,
+}
+
+impl ConversationSummary {
+    pub fn new(
+        conversation: Conversation,
+        labels: Vec<String>,
+        last_message: Option<Message>,
+        unread: bool,
+    ) -> Self {
+        Self {
+            conversation,
+            labels,
+            last_message,
+            unread,
+        }
+    }
+}
+
+impl ConversationSummary {
+    pub fn conversation(&self) -> &Conversation {
+        &self.conversation
+    }
+
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    /// Preview of the last message sent within the conversation
+    pub fn last_message(&self) -> Option<&Message> {
+        self.last_message.as_ref()
+    }
+
+    pub fn unread(&self) -> bool {
+        self.unread
+    }
+
+    /// Timestamp of the last message, or when the conversation was last modified
+    pub fn last_activity(&self) -> DateTime<Utc> {
+        self.last_message
+            .as_ref()
+            .map(|message| message.date())
+            .unwrap_or(self.conversation.modified)
+    }
+}
+
 impl Conversation {
     pub fn set_id(&mut self, id: Uuid) {
         self.id = id;
@@ -964,6 +1246,48 @@ pub enum MessageType {
     /// TBD
     #[display(fmt = "event")]
     Event,
+    /// Poll that participants can vote on. See [`Message::poll`]
+    #[display(fmt = "poll")]
+    Poll,
+    /// Recorded audio stored as an Ogg/Opus attachment. See [`Message::voice_note`]
+    #[display(fmt = "voice_note")]
+    VoiceNote,
+    /// Identity shared to the conversation. See [`Message::contact_card`]
+    #[display(fmt = "contact_card")]
+    ContactCard,
+}
+
+/// Hybrid logical clock used to order messages within a conversation independently of
+/// the wall clock of each participant
+#[derive(
+    Default, Clone, Copy, Debug, Hash, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct HybridTimestamp {
+    /// Milliseconds since the unix epoch
+    physical: u64,
+
+    /// Counter to order events sharing the same physical time
+    logical: u32,
+}
+
+impl HybridTimestamp {
+    pub fn new(physical: u64, logical: u32) -> Self {
+        Self { physical, logical }
+    }
+
+    pub fn physical(&self) -> u64 {
+        self.physical
+    }
+
+    pub fn logical(&self) -> u32 {
+        self.logical
+    }
+}
+
+impl From<DateTime<Utc>> for HybridTimestamp {
+    fn from(date: DateTime<Utc>) -> Self {
+        Self::new(date.timestamp_millis().max(0) as u64, 0)
+    }
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
@@ -980,6 +1304,9 @@ pub struct MessageReference {
     /// Timestamp of the message
     date: DateTime<Utc>,
 
+    /// Logical timestamp used to order the message
+    clock: HybridTimestamp,
+
     /// Timestamp of when message was modified
     modified: Option<DateTime<Utc>>,
 
@@ -1001,7 +1328,7 @@ impl PartialOrd for MessageReference {
 
 impl Ord for MessageReference {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.date.cmp(&other.date)
+        self.clock.cmp(&other.clock)
     }
 }
 
@@ -1023,6 +1350,10 @@ impl MessageReference {
         self.date
     }
 
+    pub fn clock(&self) -> HybridTimestamp {
+        self.clock
+    }
+
     pub fn modified(&self) -> Option<DateTime<Utc>> {
         self.modified
     }
@@ -1057,6 +1388,10 @@ impl MessageReference {
         self.date = date
     }
 
+    pub fn set_clock(&mut self, clock: HybridTimestamp) {
+        self.clock = clock
+    }
+
     pub fn set_modified(&mut self, date: DateTime<Utc>) {
         self.modified = Some(date)
     }
@@ -1088,9 +1423,13 @@ pub struct Message {
     /// ID of the sender of the message
     sender: DID,
 
-    /// Timestamp of the message
+    /// Timestamp of the message. Only intended for display
     date: DateTime<Utc>,
 
+    /// Logical timestamp used to order the message
+    #[serde(default)]
+    clock: HybridTimestamp,
+
     /// Timestamp of when message was modified
     /// Note: Only applies if the message itself was modified and not
     ///       related to being pinned, reacted, etc.
@@ -1116,6 +1455,22 @@ pub struct Message {
     /// List of Attachment
     attachment: Vec<File>,
 
+    /// State of the signature attached to the message
+    #[serde(default)]
+    signature_state: SignatureState,
+
+    /// Poll attached to the message if it is [`MessageType::Poll`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    poll: Option<Poll>,
+
+    /// Details of the recording if it is [`MessageType::VoiceNote`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    voice_note: Option<VoiceNote>,
+
+    /// Identity shared if it is [`MessageType::ContactCard`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contact_card: Option<Identity>,
+
     /// Metadata related to the message. Can be used externally, but more internally focused
     #[serde(flatten)]
     metadata: IndexMap<String, String>,
@@ -1129,6 +1484,7 @@ impl Default for Message {
             conversation_id: Uuid::nil(),
             sender: Default::default(),
             date: Utc::now(),
+            clock: HybridTimestamp::default(),
             modified: None,
             pinned: false,
             reactions: IndexMap::new(),
@@ -1136,6 +1492,10 @@ impl Default for Message {
             replied: None,
             lines: Vec::new(),
             attachment: Vec::new(),
+            signature_state: SignatureState::default(),
+            poll: None,
+            voice_note: None,
+            contact_card: None,
             metadata: IndexMap::new(),
         }
     }
@@ -1149,7 +1509,7 @@ impl PartialOrd for Message {
 
 impl Ord for Message {
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
-        self.date.cmp(&other.date)
+        self.clock.cmp(&other.clock)
     }
 }
 
@@ -1181,6 +1541,10 @@ impl Message {
         self.date
     }
 
+    pub fn clock(&self) -> HybridTimestamp {
+        self.clock
+    }
+
     pub fn modified(&self) -> Option<DateTime<Utc>> {
         self.modified
     }
@@ -1212,6 +1576,22 @@ impl Message {
     pub fn replied(&self) -> Option<Uuid> {
         self.replied
     }
+
+    pub fn signature_state(&self) -> SignatureState {
+        self.signature_state
+    }
+
+    pub fn poll(&self) -> Option<&Poll> {
+        self.poll.as_ref()
+    }
+
+    pub fn voice_note(&self) -> Option<&VoiceNote> {
+        self.voice_note.as_ref()
+    }
+
+    pub fn contact_card(&self) -> Option<&Identity> {
+        self.contact_card.as_ref()
+    }
 }
 
 impl Message {
@@ -1235,6 +1615,10 @@ impl Message {
         self.date = date
     }
 
+    pub fn set_clock(&mut self, clock: HybridTimestamp) {
+        self.clock = clock
+    }
+
     pub fn set_modified(&mut self, date: DateTime<Utc>) {
         self.modified = Some(date)
     }
@@ -1266,6 +1650,22 @@ impl Message {
     pub fn set_replied(&mut self, replied: Option<Uuid>) {
         self.replied = replied
     }
+
+    pub fn set_signature_state(&mut self, state: SignatureState) {
+        self.signature_state = state
+    }
+
+    pub fn set_poll(&mut self, poll: Option<Poll>) {
+        self.poll = poll
+    }
+
+    pub fn set_voice_note(&mut self, voice_note: Option<VoiceNote>) {
+        self.voice_note = voice_note
+    }
+
+    pub fn set_contact_card(&mut self, contact_card: Option<Identity>) {
+        self.contact_card = contact_card
+    }
 }
 
 // Mutable functions
@@ -1304,60 +1704,535 @@ pub enum MessageStatus {
     Delivered,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[serde(rename_all = "snake_case")]
 #[repr(C)]
-pub enum ReactionState {
-    Add,
-    Remove,
-}
+pub enum SignatureState {
+    /// Signature of the message was checked against the sender and is valid
+    #[display(fmt = "verified")]
+    Verified,
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(C)]
-pub enum PinState {
-    Pin,
-    Unpin,
+    /// Message does not contain a signature
+    #[display(fmt = "unsigned")]
+    #[default]
+    Unsigned,
+
+    /// Signature of the message does not match the sender or its contents
+    #[display(fmt = "invalid")]
+    Invalid,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
-pub enum EmbedState {
-    Enabled,
-    Disable,
+pub enum ReactionState {
+    Add,
+    Remove,
 }
 
-pub enum Location {
-    /// Use [`Constellation`] to send a file from constellation
-    Constellation { path: String },
-
-    /// Use file from disk
-    Disk { path: PathBuf },
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PinnedMessage {
+    /// Conversion id where the pinned message is associated with.
+    conversation_id: Uuid,
 
-    /// Stream of bytes
-    Stream {
-        name: String,
-        size: Option<usize>,
-        stream: BoxStream<'static, std::io::Result<Bytes>>,
-    },
-}
+    /// ID of the pinned message
+    message_id: Uuid,
 
-#[derive(Serialize, Hash, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub enum LocationKind {
-    /// Use [`Constellation`] to send a file from constellation
-    Constellation { path: String },
+    /// Public key of the user who pinned the message
+    pinned_by: DID,
 
-    /// Use file from disk
-    Disk { path: PathBuf },
+    /// Timestamp of when the message was pinned
+    date: DateTime<Utc>,
 
-    /// Stream of bytes
-    Stream { name: String },
+    /// Position of the message within the pinned list
+    position: usize,
 }
 
-impl From<&Location> for LocationKind {
-    fn from(location: &Location) -> Self {
-        match location {
-            Location::Constellation { path } => LocationKind::Constellation { path: path.clone() },
-            Location::Disk { path } => LocationKind::Disk { path: path.clone() },
-            Location::Stream { name, .. } => LocationKind::Stream { name: name.clone() },
+impl PinnedMessage {
+    pub fn new(
+        conversation_id: Uuid,
+        message_id: Uuid,
+        pinned_by: DID,
+        date: DateTime<Utc>,
+        position: usize,
+    ) -> Self {
+        Self {
+            conversation_id,
+            message_id,
+            pinned_by,
+            date,
+            position,
+        }
+    }
+}
+
+impl PinnedMessage {
+    pub fn conversation_id(&self) -> Uuid {
+        self.conversation_id
+    }
+
+    pub fn message_id(&self) -> Uuid {
+        self.message_id
+    }
+
+    pub fn pinned_by(&self) -> &DID {
+        &self.pinned_by
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    /// Lines of the unsent message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    lines: Vec<String>,
+
+    /// Paths of files to be attached once the draft is sent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<PathBuf>,
+
+    /// Timestamp of when the draft was last updated
+    modified: DateTime<Utc>,
+}
+
+impl Draft {
+    pub fn new(lines: Vec<String>, attachments: Vec<PathBuf>, modified: DateTime<Utc>) -> Self {
+        Self {
+            lines,
+            attachments,
+            modified,
+        }
+    }
+}
+
+impl Draft {
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn attachments(&self) -> &[PathBuf] {
+        &self.attachments
+    }
+
+    pub fn modified(&self) -> DateTime<Utc> {
+        self.modified
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|line| line.trim().is_empty()) && self.attachments.is_empty()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    /// No messages are known to be missing from the conversation
+    #[default]
+    InSync,
+
+    /// Missing messages are being retrieved from other participants
+    Syncing { received: usize, total: usize },
+
+    /// Retrieval stopped making progress while waiting on the listed peers
+    Stalled { peers: Vec<DID> },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SavedMessage {
+    /// Copy of the message at the time it was saved
+    message: Message,
+
+    /// Optional note attached by the user
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+
+    /// Timestamp of when the message was saved
+    saved: DateTime<Utc>,
+}
+
+impl SavedMessage {
+    pub fn new(message: Message, note: Option<String>, saved: DateTime<Utc>) -> Self {
+        Self {
+            message,
+            note,
+            saved,
+        }
+    }
+}
+
+impl SavedMessage {
+    pub fn conversation_id(&self) -> Uuid {
+        self.message.conversation_id()
+    }
+
+    pub fn message_id(&self) -> Uuid {
+        self.message.id()
+    }
+
+    /// Copy of the message, which remains available even if the original was deleted
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    pub fn saved(&self) -> DateTime<Utc> {
+        self.saved
+    }
+}
+
+/// Options for listing the attachments within a conversation
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AttachmentFilter {
+    file_type: Option<FileType>,
+    sender: Option<DID>,
+    date_range: Option<Range<DateTime<Utc>>>,
+    skip: usize,
+    limit: Option<usize>,
+}
+
+impl AttachmentFilter {
+    /// Only include files of the given type. A mime type with a wildcard subtype, such as `image/*`,
+    /// will match any file of that type
+    pub fn set_file_type(mut self, file_type: FileType) -> Self {
+        self.file_type = Some(file_type);
+        self
+    }
+
+    pub fn set_sender(mut self, sender: DID) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    pub fn set_date_range(mut self, range: Range<DateTime<Utc>>) -> Self {
+        self.date_range = Some(range);
+        self
+    }
+
+    pub fn set_skip(mut self, skip: usize) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    pub fn set_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+impl AttachmentFilter {
+    pub fn file_type(&self) -> Option<&FileType> {
+        self.file_type.as_ref()
+    }
+
+    pub fn sender(&self) -> Option<&DID> {
+        self.sender.as_ref()
+    }
+
+    pub fn date_range(&self) -> Option<Range<DateTime<Utc>>> {
+        self.date_range.clone()
+    }
+
+    pub fn skip(&self) -> usize {
+        self.skip
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+}
+
+/// File attached to a message within a conversation
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConversationAttachment {
+    conversation_id: Uuid,
+    message_id: Uuid,
+    sender: DID,
+    date: DateTime<Utc>,
+    file: File,
+}
+
+impl ConversationAttachment {
+    pub fn new(
+        conversation_id: Uuid,
+        message_id: Uuid,
+        sender: DID,
+        date: DateTime<Utc>,
+        file: File,
+    ) -> Self {
+        Self {
+            conversation_id,
+            message_id,
+            sender,
+            date,
+            file,
+        }
+    }
+}
+
+impl ConversationAttachment {
+    pub fn conversation_id(&self) -> Uuid {
+        self.conversation_id
+    }
+
+    pub fn message_id(&self) -> Uuid {
+        self.message_id
+    }
+
+    pub fn sender(&self) -> &DID {
+        &self.sender
+    }
+
+    /// Timestamp of the message the file was attached to
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+
+    /// File attached to the message, including its thumbnail if one was generated
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Poll {
+    /// Question being asked
+    question: String,
+
+    /// Options that can be voted on
+    options: Vec<String>,
+
+    /// Allow voting for more than one option
+    #[serde(default)]
+    multiple_choice: bool,
+
+    /// Timestamp of when the poll stops accepting votes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    closes: Option<DateTime<Utc>>,
+}
+
+impl Poll {
+    pub fn new(question: impl Into<String>, options: Vec<String>) -> Self {
+        Self {
+            question: question.into(),
+            options,
+            multiple_choice: false,
+            closes: None,
+        }
+    }
+
+    pub fn set_multiple_choice(mut self, multiple_choice: bool) -> Self {
+        self.multiple_choice = multiple_choice;
+        self
+    }
+
+    pub fn set_close_date(mut self, closes: impl Into<Option<DateTime<Utc>>>) -> Self {
+        self.closes = closes.into();
+        self
+    }
+}
+
+impl Poll {
+    pub fn question(&self) -> &str {
+        &self.question
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    pub fn multiple_choice(&self) -> bool {
+        self.multiple_choice
+    }
+
+    pub fn close_date(&self) -> Option<DateTime<Utc>> {
+        self.closes
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PollResults {
+    /// Poll the results belong to
+    poll: Poll,
+
+    /// Options selected by each voter
+    votes: IndexMap<DID, IndexSet<usize>>,
+
+    /// Timestamp of when the poll was closed by its creator
+    closed: Option<DateTime<Utc>>,
+}
+
+impl PollResults {
+    pub fn new(
+        poll: Poll,
+        votes: IndexMap<DID, IndexSet<usize>>,
+        closed: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            poll,
+            votes,
+            closed,
+        }
+    }
+}
+
+impl PollResults {
+    pub fn poll(&self) -> &Poll {
+        &self.poll
+    }
+
+    pub fn votes(&self) -> &IndexMap<DID, IndexSet<usize>> {
+        &self.votes
+    }
+
+    /// Amount of votes for each option, in the same order as [`Poll::options`]
+    pub fn tally(&self) -> Vec<usize> {
+        let mut tally = vec![0; self.poll.options.len()];
+        for option in self.votes.values().flatten() {
+            if let Some(count) = tally.get_mut(*option) {
+                *count += 1;
+            }
+        }
+        tally
+    }
+
+    /// Returns true if the poll was closed or its close date has passed
+    pub fn is_closed(&self) -> bool {
+        self.closed.is_some() || self.poll.closes.is_some_and(|closes| closes <= Utc::now())
+    }
+
+    pub fn closed(&self) -> Option<DateTime<Utc>> {
+        self.closed
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoiceNote {
+    /// Length of the recording in milliseconds
+    duration: u64,
+
+    /// Peak amplitude of the recording, downsampled for previewing
+    waveform: Vec<u8>,
+}
+
+impl VoiceNote {
+    /// Sample rate expected of the PCM passed to [`RayGun::send_voice_note`]
+    pub const SAMPLE_RATE: u32 = 48_000;
+
+    pub fn new(duration: u64, waveform: Vec<u8>) -> Self {
+        Self { duration, waveform }
+    }
+}
+
+impl VoiceNote {
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    pub fn waveform(&self) -> &[u8] {
+        &self.waveform
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedMessage {
+    /// Message that was held back. If the message was an edit, it will contain the edited lines
+    message: Message,
+
+    /// Indicates the quarantined message is an edit to an existing message
+    edit: bool,
+
+    /// Reason given by the filter
+    reason: String,
+
+    /// Timestamp of when the message was quarantined
+    date: DateTime<Utc>,
+}
+
+impl QuarantinedMessage {
+    pub fn new(message: Message, edit: bool, reason: String, date: DateTime<Utc>) -> Self {
+        Self {
+            message,
+            edit,
+            reason,
+            date,
+        }
+    }
+}
+
+impl QuarantinedMessage {
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+
+    pub fn is_edit(&self) -> bool {
+        self.edit
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn date(&self) -> DateTime<Utc> {
+        self.date
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum PinState {
+    Pin,
+    Unpin,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub enum EmbedState {
+    Enabled,
+    Disable,
+}
+
+pub enum Location {
+    /// Use [`Constellation`] to send a file from constellation
+    Constellation { path: String },
+
+    /// Use file from disk
+    Disk { path: PathBuf },
+
+    /// Stream of bytes
+    Stream {
+        name: String,
+        size: Option<usize>,
+        stream: BoxStream<'static, std::io::Result<Bytes>>,
+    },
+}
+
+#[derive(Serialize, Hash, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum LocationKind {
+    /// Use [`Constellation`] to send a file from constellation
+    Constellation { path: String },
+
+    /// Use file from disk
+    Disk { path: PathBuf },
+
+    /// Stream of bytes
+    Stream { name: String },
+}
+
+impl From<&Location> for LocationKind {
+    fn from(location: &Location) -> Self {
+        match location {
+            Location::Constellation { path } => LocationKind::Constellation { path: path.clone() },
+            Location::Disk { path } => LocationKind::Disk { path: path.clone() },
+            Location::Stream { name, .. } => LocationKind::Stream { name: name.clone() },
         }
     }
 }
@@ -1438,8 +2313,31 @@ pub trait RayGun:
         Err(Error::Unimplemented)
     }
 
-    /// List all active conversations
-    async fn list_conversations(&self) -> Result<Vec<Conversation>, Error> {
+    /// List active conversations matching the [`ConversationQuery`]
+    async fn list_conversations(
+        &self,
+        _: ConversationQuery,
+    ) -> Result<Vec<ConversationSummary>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Add a label to a conversation
+    async fn add_conversation_label(&mut self, _: Uuid, _: &str) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Remove a label from a conversation
+    async fn remove_conversation_label(&mut self, _: Uuid, _: &str) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// List all labels that are in use
+    async fn list_conversation_labels(&self) -> Result<Vec<String>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Mark all messages within a conversation as read
+    async fn mark_conversation_read(&mut self, _: Uuid) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
 
@@ -1482,6 +2380,12 @@ pub trait RayGun:
     /// Sends a message to a conversation.
     async fn send(&mut self, conversation_id: Uuid, message: Vec<String>) -> Result<Uuid, Error>;
 
+    /// Sends a message built with [`MessageBuilder`] to a conversation, allowing
+    /// metadata, mentions and a reply target to be attached
+    async fn send_with(&mut self, _: Uuid, _: MessageBuilder) -> Result<Uuid, Error> {
+        Err(Error::Unimplemented)
+    }
+
     /// Edit an existing message in a conversation.
     async fn edit(
         &mut self,
@@ -1514,6 +2418,103 @@ pub trait RayGun:
         state: PinState,
     ) -> Result<(), Error>;
 
+    /// List pinned messages within a conversation in the order they were pinned
+    async fn list_pinned(&self, _: Uuid) -> Result<Vec<PinnedMessage>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Store an unsent draft for a conversation. Empty lines and attachments will clear the draft
+    async fn set_draft(&mut self, _: Uuid, _: Vec<String>, _: Vec<PathBuf>) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Get the unsent draft for a conversation, if any
+    async fn get_draft(&self, _: Uuid) -> Result<Option<Draft>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Get the progress of retrieving messages missing from a conversation
+    async fn sync_status(&self, _: Uuid) -> Result<SyncStatus, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Reconcile the messages of a conversation with a participant, retrieving any that are missing
+    async fn force_resync(&mut self, _: Uuid, _: &DID) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Save a copy of a message, along with an optional note.
+    /// Saving an already saved message will refresh the copy and replace its note
+    async fn save_message(&mut self, _: Uuid, _: Uuid, _: Option<String>) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Remove a message from the saved list
+    async fn unsave_message(&mut self, _: Uuid, _: Uuid) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// List saved messages, with the most recently saved first
+    async fn list_saved_messages(&self) -> Result<Vec<SavedMessage>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// List files attached within a conversation, with the most recent first
+    async fn list_attachments(
+        &self,
+        _: Uuid,
+        _: AttachmentFilter,
+    ) -> Result<Vec<ConversationAttachment>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// List messages within a conversation that were held back by a message filter
+    async fn list_quarantined_messages(&self, _: Uuid) -> Result<Vec<QuarantinedMessage>, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Release a quarantined message, storing it as if it was accepted by the filter
+    async fn release_quarantined_message(&mut self, _: Uuid, _: Uuid) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Send a poll to a conversation
+    async fn send_poll(&mut self, _: Uuid, _: Poll) -> Result<Uuid, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Vote on a poll within a conversation. Voting again will replace the previous vote,
+    /// while an empty list of options will retract it
+    async fn vote_poll(&mut self, _: Uuid, _: Uuid, _: Vec<usize>) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Close a poll, preventing any further votes. Only the creator of the poll can close it
+    async fn close_poll(&mut self, _: Uuid, _: Uuid) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Results of a poll within a conversation
+    async fn poll_results(&self, _: Uuid, _: Uuid) -> Result<PollResults, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Share an identity to a conversation as a contact card.
+    /// The identity must be known locally
+    async fn send_contact_card(&mut self, _: Uuid, _: &DID) -> Result<Uuid, Error> {
+        Err(Error::Unimplemented)
+    }
+
+    /// Record a voice note from a stream of mono PCM samples at [`VoiceNote::SAMPLE_RATE`]
+    /// and send it to a conversation once the stream ends
+    async fn send_voice_note(
+        &mut self,
+        _: Uuid,
+        _: BoxStream<'static, Vec<f32>>,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+        Err(Error::Unimplemented)
+    }
+
     /// Reply to a message within a conversation
     async fn reply(
         &mut self,
@@ -1650,6 +2651,11 @@ pub trait RayGunEvents: Sync + Send {
     async fn cancel_event(&mut self, _: Uuid, _: MessageEvent) -> Result<(), Error> {
         Err(Error::Unimplemented)
     }
+
+    /// List of participants that are currently typing within a conversation
+    async fn typing_participants(&self, _: Uuid) -> Result<Vec<DID>, Error> {
+        Err(Error::Unimplemented)
+    }
 }
 
 #[async_trait::async_trait]
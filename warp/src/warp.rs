@@ -20,12 +20,17 @@ use crate::raygun::community::{
 };
 use crate::raygun::{
     community::{
-        Community, CommunityChannel, CommunityChannelType, CommunityInvite, RayGunCommunity,
+        Community, CommunityAuditLogEntry, CommunityAuditLogOptions, CommunityBan,
+        CommunityChannel, CommunityChannelCategory, CommunityChannelType, CommunityInvite,
+        CommunityListing, RayGunCommunity,
     },
-    AttachmentEventStream, Conversation, ConversationImage, EmbedState, GroupPermissionOpt,
-    Location, Message, MessageEvent, MessageEventStream, MessageOptions, MessageReference,
-    MessageStatus, Messages, PinState, RayGun, RayGunAttachment, RayGunConversationInformation,
+    AttachmentEventStream, AttachmentFilter, Conversation, ConversationAttachment,
+    ConversationImage, ConversationQuery, ConversationSummary, Draft, EmbedState,
+    GroupPermissionOpt, Location, Message, MessageBuilder, MessageEvent, MessageEventStream,
+    MessageOptions, MessageReference, MessageStatus, Messages, PinState, PinnedMessage, Poll,
+    PollResults, QuarantinedMessage, RayGun, RayGunAttachment, RayGunConversationInformation,
     RayGunEventStream, RayGunEvents, RayGunGroupConversation, RayGunStream, ReactionState,
+    SavedMessage, SyncStatus,
 };
 use crate::tesseract::Tesseract;
 use crate::warp::dummy::Dummy;
@@ -36,6 +41,7 @@ use futures::stream::BoxStream;
 use indexmap::IndexSet;
 use std::any::Any;
 use std::path::PathBuf;
+use std::time::Duration;
 use uuid::Uuid;
 
 pub struct Warp<M, R, C>
@@ -263,6 +269,11 @@ where
     async fn identity_platform(&self, identity: &DID) -> Result<Platform, Error> {
         self.multipass.identity_platform(identity).await
     }
+
+    /// Returns true if the identity is marked as an automated account
+    async fn identity_is_bot(&self, identity: &DID) -> Result<bool, Error> {
+        self.multipass.identity_is_bot(identity).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -297,6 +308,10 @@ where
         self.multipass.send_request(identity).await
     }
 
+    async fn send_request_from_card(&mut self, message: &Message) -> Result<(), Error> {
+        self.multipass.send_request_from_card(message).await
+    }
+
     /// Accept friend request from public key
     async fn accept_request(&mut self, identity: &DID) -> Result<(), Error> {
         self.multipass.accept_request(identity).await
@@ -593,6 +608,16 @@ where
     async fn list_communities_invited_to(&self) -> Result<Vec<(Uuid, CommunityInvite)>, Error> {
         self.raygun.list_communities_invited_to().await
     }
+    async fn search_public_communities(
+        &self,
+        query: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Vec<CommunityListing>, Error> {
+        self.raygun
+            .search_public_communities(query, skip, limit)
+            .await
+    }
     async fn leave_community(&mut self, community_id: Uuid) -> Result<(), Error> {
         self.raygun.leave_community(community_id).await
     }
@@ -621,6 +646,15 @@ where
             .edit_community_banner(community_id, location)
             .await
     }
+    async fn set_community_listing(
+        &mut self,
+        community_id: Uuid,
+        invite_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .set_community_listing(community_id, invite_id)
+            .await
+    }
 
     async fn create_community_invite(
         &mut self,
@@ -650,6 +684,12 @@ where
             .get_community_invite(community_id, invite_id)
             .await
     }
+    async fn list_community_invites(
+        &self,
+        community_id: Uuid,
+    ) -> Result<Vec<CommunityInvite>, Error> {
+        self.raygun.list_community_invites(community_id).await
+    }
     async fn request_join_community(&mut self, community_id: Uuid) -> Result<(), Error> {
         self.raygun.request_join_community(community_id).await
     }
@@ -697,6 +737,46 @@ where
             .edit_community_role_name(community_id, role_id, new_name)
             .await
     }
+    async fn edit_community_role_position(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        position: u32,
+    ) -> Result<(), Error> {
+        self.raygun
+            .edit_community_role_position(community_id, role_id, position)
+            .await
+    }
+    async fn edit_community_role_color(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        color: Option<u32>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .edit_community_role_color(community_id, role_id, color)
+            .await
+    }
+    async fn edit_community_role_hoisted(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        hoisted: bool,
+    ) -> Result<(), Error> {
+        self.raygun
+            .edit_community_role_hoisted(community_id, role_id, hoisted)
+            .await
+    }
+    async fn edit_community_role_mentionable(
+        &mut self,
+        community_id: Uuid,
+        role_id: RoleId,
+        mentionable: bool,
+    ) -> Result<(), Error> {
+        self.raygun
+            .edit_community_role_mentionable(community_id, role_id, mentionable)
+            .await
+    }
     async fn grant_community_role(
         &mut self,
         community_id: Uuid,
@@ -746,6 +826,52 @@ where
             .get_community_channel(community_id, channel_id)
             .await
     }
+    async fn reorder_community_channels(
+        &mut self,
+        community_id: Uuid,
+        channels: Vec<Uuid>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .reorder_community_channels(community_id, channels)
+            .await
+    }
+    async fn create_community_channel_category(
+        &mut self,
+        community_id: Uuid,
+        name: &str,
+    ) -> Result<CommunityChannelCategory, Error> {
+        self.raygun
+            .create_community_channel_category(community_id, name)
+            .await
+    }
+    async fn delete_community_channel_category(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .delete_community_channel_category(community_id, category_id)
+            .await
+    }
+    async fn get_community_channel_category(
+        &self,
+        community_id: Uuid,
+        category_id: Uuid,
+    ) -> Result<CommunityChannelCategory, Error> {
+        self.raygun
+            .get_community_channel_category(community_id, category_id)
+            .await
+    }
+    async fn move_channel_to_category(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        category_id: Option<Uuid>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .move_channel_to_category(community_id, channel_id, category_id)
+            .await
+    }
 
     async fn edit_community_name(&mut self, community_id: Uuid, name: &str) -> Result<(), Error> {
         self.raygun.edit_community_name(community_id, name).await
@@ -806,6 +932,62 @@ where
             .remove_community_member(community_id, member)
             .await
     }
+    async fn ban_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+        reason: Option<String>,
+        expiry: Option<DateTime<Utc>>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .ban_community_member(community_id, member, reason, expiry)
+            .await
+    }
+    async fn unban_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+    ) -> Result<(), Error> {
+        self.raygun
+            .unban_community_member(community_id, member)
+            .await
+    }
+    async fn list_community_bans(&self, community_id: Uuid) -> Result<Vec<CommunityBan>, Error> {
+        self.raygun.list_community_bans(community_id).await
+    }
+    async fn timeout_community_member(
+        &mut self,
+        community_id: Uuid,
+        member: DID,
+        until: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .timeout_community_member(community_id, member, until)
+            .await
+    }
+
+    async fn transfer_community_ownership(
+        &mut self,
+        community_id: Uuid,
+        new_owner: DID,
+    ) -> Result<(), Error> {
+        self.raygun
+            .transfer_community_ownership(community_id, new_owner)
+            .await
+    }
+    async fn accept_community_ownership(&mut self, community_id: Uuid) -> Result<(), Error> {
+        self.raygun.accept_community_ownership(community_id).await
+    }
+
+    async fn get_community_audit_log(
+        &self,
+        community_id: Uuid,
+        options: CommunityAuditLogOptions,
+    ) -> Result<Vec<CommunityAuditLogEntry>, Error> {
+        self.raygun
+            .get_community_audit_log(community_id, options)
+            .await
+    }
 
     async fn edit_community_channel_name(
         &mut self,
@@ -827,6 +1009,41 @@ where
             .edit_community_channel_description(community_id, channel_id, description)
             .await
     }
+    async fn edit_community_channel_slow_mode(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        slow_mode: Option<Duration>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .edit_community_channel_slow_mode(community_id, channel_id, slow_mode)
+            .await
+    }
+    async fn follow_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        source_community_id: Uuid,
+        source_channel_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .follow_community_channel(
+                community_id,
+                channel_id,
+                source_community_id,
+                source_channel_id,
+            )
+            .await
+    }
+    async fn unfollow_community_channel(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .unfollow_community_channel(community_id, channel_id)
+            .await
+    }
     async fn grant_community_channel_permission(
         &mut self,
         community_id: Uuid,
@@ -870,6 +1087,67 @@ where
             .await
     }
 
+    async fn grant_community_channel_category_permission(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        self.raygun
+            .grant_community_channel_category_permission(
+                community_id,
+                category_id,
+                permission,
+                role_id,
+            )
+            .await
+    }
+    async fn revoke_community_channel_category_permission(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+        role_id: RoleId,
+    ) -> Result<(), Error> {
+        self.raygun
+            .revoke_community_channel_category_permission(
+                community_id,
+                category_id,
+                permission,
+                role_id,
+            )
+            .await
+    }
+    async fn grant_community_channel_category_permission_for_all(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        self.raygun
+            .grant_community_channel_category_permission_for_all(
+                community_id,
+                category_id,
+                permission,
+            )
+            .await
+    }
+    async fn revoke_community_channel_category_permission_for_all(
+        &mut self,
+        community_id: Uuid,
+        category_id: Uuid,
+        permission: CommunityChannelPermission,
+    ) -> Result<(), Error> {
+        self.raygun
+            .revoke_community_channel_category_permission_for_all(
+                community_id,
+                category_id,
+                permission,
+            )
+            .await
+    }
+
     async fn get_community_channel_message(
         &self,
         community_id: Uuid,
@@ -971,6 +1249,66 @@ where
             .delete_community_channel_message(community_id, channel_id, message_id)
             .await
     }
+    async fn list_quarantined_community_channel_messages(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        self.raygun
+            .list_quarantined_community_channel_messages(community_id, channel_id)
+            .await
+    }
+    async fn release_quarantined_community_channel_message(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .release_quarantined_community_channel_message(community_id, channel_id, message_id)
+            .await
+    }
+    async fn send_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        poll: Poll,
+    ) -> Result<Uuid, Error> {
+        self.raygun
+            .send_community_channel_poll(community_id, channel_id, poll)
+            .await
+    }
+    async fn vote_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .vote_community_channel_poll(community_id, channel_id, message_id, options)
+            .await
+    }
+    async fn close_community_channel_poll(
+        &mut self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .close_community_channel_poll(community_id, channel_id, message_id)
+            .await
+    }
+    async fn community_channel_poll_results(
+        &self,
+        community_id: Uuid,
+        channel_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        self.raygun
+            .community_channel_poll_results(community_id, channel_id, message_id)
+            .await
+    }
     async fn pin_community_channel_message(
         &mut self,
         community_id: Uuid,
@@ -1165,6 +1503,10 @@ where
     ) -> Result<(), Error> {
         self.raygun.cancel_event(conversation_id, event).await
     }
+
+    async fn typing_participants(&self, conversation_id: Uuid) -> Result<Vec<DID>, Error> {
+        self.raygun.typing_participants(conversation_id).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -1221,8 +1563,39 @@ where
             .await
     }
 
-    async fn list_conversations(&self) -> Result<Vec<Conversation>, Error> {
-        self.raygun.list_conversations().await
+    async fn list_conversations(
+        &self,
+        query: ConversationQuery,
+    ) -> Result<Vec<ConversationSummary>, Error> {
+        self.raygun.list_conversations(query).await
+    }
+
+    async fn add_conversation_label(
+        &mut self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        self.raygun
+            .add_conversation_label(conversation_id, label)
+            .await
+    }
+
+    async fn remove_conversation_label(
+        &mut self,
+        conversation_id: Uuid,
+        label: &str,
+    ) -> Result<(), Error> {
+        self.raygun
+            .remove_conversation_label(conversation_id, label)
+            .await
+    }
+
+    async fn list_conversation_labels(&self) -> Result<Vec<String>, Error> {
+        self.raygun.list_conversation_labels().await
+    }
+
+    async fn mark_conversation_read(&mut self, conversation_id: Uuid) -> Result<(), Error> {
+        self.raygun.mark_conversation_read(conversation_id).await
     }
 
     async fn get_message(&self, conversation_id: Uuid, message_id: Uuid) -> Result<Message, Error> {
@@ -1275,6 +1648,14 @@ where
         self.raygun.send(conversation_id, message).await
     }
 
+    async fn send_with(
+        &mut self,
+        conversation_id: Uuid,
+        message: MessageBuilder,
+    ) -> Result<Uuid, Error> {
+        self.raygun.send_with(conversation_id, message).await
+    }
+
     async fn edit(
         &mut self,
         conversation_id: Uuid,
@@ -1313,6 +1694,122 @@ where
         self.raygun.pin(conversation_id, message_id, state).await
     }
 
+    async fn list_pinned(&self, conversation_id: Uuid) -> Result<Vec<PinnedMessage>, Error> {
+        self.raygun.list_pinned(conversation_id).await
+    }
+
+    async fn set_draft(
+        &mut self,
+        conversation_id: Uuid,
+        lines: Vec<String>,
+        attachments: Vec<PathBuf>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .set_draft(conversation_id, lines, attachments)
+            .await
+    }
+
+    async fn get_draft(&self, conversation_id: Uuid) -> Result<Option<Draft>, Error> {
+        self.raygun.get_draft(conversation_id).await
+    }
+
+    async fn sync_status(&self, conversation_id: Uuid) -> Result<SyncStatus, Error> {
+        self.raygun.sync_status(conversation_id).await
+    }
+
+    async fn force_resync(&mut self, conversation_id: Uuid, peer: &DID) -> Result<(), Error> {
+        self.raygun.force_resync(conversation_id, peer).await
+    }
+
+    async fn save_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .save_message(conversation_id, message_id, note)
+            .await
+    }
+
+    async fn unsave_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .unsave_message(conversation_id, message_id)
+            .await
+    }
+
+    async fn list_saved_messages(&self) -> Result<Vec<SavedMessage>, Error> {
+        self.raygun.list_saved_messages().await
+    }
+
+    async fn list_attachments(
+        &self,
+        conversation_id: Uuid,
+        filter: AttachmentFilter,
+    ) -> Result<Vec<ConversationAttachment>, Error> {
+        self.raygun.list_attachments(conversation_id, filter).await
+    }
+
+    async fn list_quarantined_messages(
+        &self,
+        conversation_id: Uuid,
+    ) -> Result<Vec<QuarantinedMessage>, Error> {
+        self.raygun.list_quarantined_messages(conversation_id).await
+    }
+
+    async fn release_quarantined_message(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<(), Error> {
+        self.raygun
+            .release_quarantined_message(conversation_id, message_id)
+            .await
+    }
+
+    async fn send_poll(&mut self, conversation_id: Uuid, poll: Poll) -> Result<Uuid, Error> {
+        self.raygun.send_poll(conversation_id, poll).await
+    }
+
+    async fn vote_poll(
+        &mut self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+        options: Vec<usize>,
+    ) -> Result<(), Error> {
+        self.raygun
+            .vote_poll(conversation_id, message_id, options)
+            .await
+    }
+
+    async fn close_poll(&mut self, conversation_id: Uuid, message_id: Uuid) -> Result<(), Error> {
+        self.raygun.close_poll(conversation_id, message_id).await
+    }
+
+    async fn poll_results(
+        &self,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    ) -> Result<PollResults, Error> {
+        self.raygun.poll_results(conversation_id, message_id).await
+    }
+
+    async fn send_contact_card(&mut self, conversation_id: Uuid, did: &DID) -> Result<Uuid, Error> {
+        self.raygun.send_contact_card(conversation_id, did).await
+    }
+
+    async fn send_voice_note(
+        &mut self,
+        conversation_id: Uuid,
+        pcm: BoxStream<'static, Vec<f32>>,
+    ) -> Result<(Uuid, AttachmentEventStream), Error> {
+        self.raygun.send_voice_note(conversation_id, pcm).await
+    }
+
     async fn reply(
         &mut self,
         conversation_id: Uuid,
@@ -15,10 +15,10 @@ use crate::multipass::{
 };
 use crate::raygun::community::RayGunCommunity;
 use crate::raygun::{
-    Conversation, ConversationImage, EmbedState, GroupPermissionOpt, Location, Message,
-    MessageOptions, MessageReference, MessageStatus, Messages, PinState, RayGun, RayGunAttachment,
-    RayGunConversationInformation, RayGunEvents, RayGunGroupConversation, RayGunStream,
-    ReactionState,
+    Conversation, ConversationImage, ConversationQuery, ConversationSummary, EmbedState,
+    GroupPermissionOpt, Location, Message, MessageOptions, MessageReference, MessageStatus,
+    Messages, PinState, RayGun, RayGunAttachment, RayGunConversationInformation, RayGunEvents,
+    RayGunGroupConversation, RayGunStream, ReactionState,
 };
 use crate::tesseract::Tesseract;
 use crate::{Extension, SingleHandle};
@@ -354,7 +354,10 @@ impl RayGun for Dummy {
         Err(Error::Unimplemented)
     }
 
-    async fn list_conversations(&self) -> Result<Vec<Conversation>, Error> {
+    async fn list_conversations(
+        &self,
+        _: ConversationQuery,
+    ) -> Result<Vec<ConversationSummary>, Error> {
         Err(Error::Unimplemented)
     }
 